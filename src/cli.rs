@@ -2,6 +2,7 @@ use clap::Parser;
 
 #[derive(Debug, Parser)]
 pub struct Cli {
+    /// Defaults to $XDG_CONFIG_HOME/dbclient/config.toml, then ./dbclient.toml
     #[arg(short, long)]
-    pub config_path: std::path::PathBuf
+    pub config_path: Option<std::path::PathBuf>
 }
@@ -0,0 +1,128 @@
+use std::{fmt, fs, io, ops::Range, path::{Path, PathBuf}};
+
+use super::{Config, Connection};
+
+pub const CONFIG_DIR_NAME: &str = "dbclient";
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+pub const LOCAL_CONFIG_FILE_NAME: &str = "dbclient.toml";
+pub const CONFIG_INCLUDE_DIR_NAME: &str = "conf.d";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    NotFound(Vec<PathBuf>),
+    Io(PathBuf, io::Error),
+    Invalid(PathBuf, String),
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NotFound(candidates) => {
+                let candidates: Vec<String> = candidates.iter().map(|path| path.display().to_string()).collect();
+                write!(f, "Config file not found, looked at: {}", candidates.join(", "))
+            },
+            ConfigError::Io(path, err) => write!(f, "Failed to access config file {}: {}", path.display(), err),
+            ConfigError::Invalid(path, message) => write!(f, "{}: {}", path.display(), message),
+            ConfigError::Parse { path, line, column, message } => write!(f, "{}:{}:{}: {}", path.display(), line, column, message),
+        }
+    }
+}
+
+impl ConfigError {
+    pub fn parse(path: &Path, content: &str, span: Option<Range<usize>>, message: &str) -> Self {
+        let offset = span.map(|span| span.start).unwrap_or(0).min(content.len());
+        let before = &content[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|ch| *ch != '\n').count() + 1;
+        ConfigError::Parse {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Config {
+    pub fn discover(path: Option<PathBuf>) -> Result<PathBuf, ConfigError> {
+        if let Some(path) = path {
+            return Ok(path);
+        }
+
+        let mut candidates = vec![];
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+        if let Some(config_home) = config_home {
+            candidates.push(config_home.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME));
+        }
+        candidates.push(PathBuf::from(".").join(LOCAL_CONFIG_FILE_NAME));
+
+        match candidates.iter().find(|path| path.is_file()) {
+            Some(path) => Ok(path.clone()),
+            None => Err(ConfigError::NotFound(candidates)),
+        }
+    }
+
+    // Loads the `include` list of the file, then the file itself, then `conf.d/*.toml` next to it.
+    // Connections loaded later replace earlier ones with the same name.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let mut visited = vec![];
+        let mut config = load_file(path, &mut visited)?;
+        config.path = Some(path.to_path_buf());
+
+        let include_dir = path.parent().unwrap_or(Path::new(".")).join(CONFIG_INCLUDE_DIR_NAME);
+        if include_dir.is_dir() {
+            let mut files: Vec<PathBuf> = fs::read_dir(&include_dir)
+                .map_err(|err| ConfigError::Io(include_dir.clone(), err))?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "toml"))
+                .collect();
+            files.sort();
+            for file in files {
+                let included = load_file(&file, &mut visited)?;
+                merge_connections(&mut config.connections, included.connections);
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn load_file(path: &Path, visited: &mut Vec<PathBuf>) -> Result<Config, ConfigError> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if visited.contains(&canonical) {
+        return Ok(Config { path: None, include: vec![], connections: vec![] });
+    }
+    visited.push(canonical);
+
+    let content = fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+    let mut config: Config = toml::from_str(&content).map_err(|err| ConfigError::parse(path, &content, err.span(), err.message()))?;
+    config.connections.iter_mut().for_each(|connection| connection.source = Some(path.to_path_buf()));
+
+    // includes come first so that the including file overrides them
+    let own = std::mem::take(&mut config.connections);
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    for include in config.include.clone() {
+        let included = load_file(&base_dir.join(include), visited)?;
+        merge_connections(&mut config.connections, included.connections);
+    }
+    merge_connections(&mut config.connections, own);
+
+    Ok(config)
+}
+
+fn merge_connections(connections: &mut Vec<Connection>, other: Vec<Connection>) {
+    for connection in other {
+        match connections.iter_mut().find(|existing| existing.name == connection.name) {
+            Some(existing) => *existing = connection,
+            None => connections.push(connection),
+        }
+    }
+}
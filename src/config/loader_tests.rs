@@ -0,0 +1,68 @@
+use std::{fs, path::Path};
+
+use crate::config::Config;
+
+fn write_connections(path: &Path, include: &[&str], connections: &[(&str, &str)]) {
+    let mut content = String::new();
+    if !include.is_empty() {
+        let include: Vec<String> = include.iter().map(|file| format!("\"{}\"", file)).collect();
+        content.push_str(&format!("include = [{}]\n", include.join(", ")));
+    }
+    for (name, connection_string) in connections {
+        content.push_str(&format!("\n[[connections]]\nconnection_type = \"Redis\"\nname = \"{}\"\nconnection_string = \"{}\"\n", name, connection_string));
+    }
+    fs::write(path, content).unwrap();
+}
+
+// name and connection string of every loaded connection, in order
+fn loaded(config: &Config) -> Vec<(&str, &str)> {
+    config.connections.iter().map(|connection| (connection.name.as_str(), connection.connection_string.as_str())).collect()
+}
+
+#[test]
+fn test_load_includes_before_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let main = dir.path().join("config.toml");
+    write_connections(&main, &["work.toml", "personal.toml"], &[("local", "redis://main"), ("own", "redis://main")]);
+    write_connections(&dir.path().join("work.toml"), &[], &[("local", "redis://work"), ("shared", "redis://work")]);
+    write_connections(&dir.path().join("personal.toml"), &[], &[("shared", "redis://personal")]);
+
+    let config = Config::load(&main).unwrap();
+    assert_eq!(loaded(&config), vec![
+        ("local", "redis://main"),
+        ("shared", "redis://personal"),
+        ("own", "redis://main"),
+    ]);
+    assert_eq!(config.connections[0].source.as_deref(), Some(main.as_path()));
+    assert_eq!(config.connections[1].source.as_deref(), Some(dir.path().join("personal.toml").as_path()));
+}
+
+#[test]
+fn test_load_conf_d_in_name_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let main = dir.path().join("config.toml");
+    write_connections(&main, &[], &[("cache", "redis://main"), ("queue", "redis://main")]);
+    let conf_d = dir.path().join("conf.d");
+    fs::create_dir(&conf_d).unwrap();
+    write_connections(&conf_d.join("20-late.toml"), &[], &[("queue", "redis://late")]);
+    write_connections(&conf_d.join("10-early.toml"), &[], &[("queue", "redis://early"), ("extra", "redis://early")]);
+    fs::write(conf_d.join("notes.txt"), "not a config").unwrap();
+
+    let config = Config::load(&main).unwrap();
+    assert_eq!(loaded(&config), vec![
+        ("cache", "redis://main"),
+        ("queue", "redis://late"),
+        ("extra", "redis://early"),
+    ]);
+}
+
+#[test]
+fn test_load_include_cycle() {
+    let dir = tempfile::tempdir().unwrap();
+    let main = dir.path().join("config.toml");
+    write_connections(&main, &["other.toml"], &[("main", "redis://main")]);
+    write_connections(&dir.path().join("other.toml"), &["config.toml"], &[("other", "redis://other")]);
+
+    let config = Config::load(&main).unwrap();
+    assert_eq!(loaded(&config), vec![("other", "redis://other"), ("main", "redis://main")]);
+}
@@ -1,10 +1,23 @@
 use serde::{Deserialize, Serialize};
 
-use std::fmt;
+use std::{fmt, path::PathBuf};
+
+pub mod loader;
+pub mod secrets;
+pub mod writer;
+
+#[cfg(test)]
+mod loader_tests;
+#[cfg(test)]
+mod secrets_tests;
+#[cfg(test)]
+mod writer_tests;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, PartialOrd, Clone, Eq)]
 pub enum ConnectionType {
     Redis,
+    // uri lists seed nodes separated by commas
+    RedisCluster,
     Postgres,
     MySql
 }
@@ -13,6 +26,7 @@ impl fmt::Display for ConnectionType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let connection_type_str = match self {
             ConnectionType::Redis => "Redis",
+            ConnectionType::RedisCluster => "RedisCluster",
             ConnectionType::Postgres => "Postgres",
             ConnectionType::MySql => "MySql",
         };
@@ -20,14 +34,98 @@ impl fmt::Display for ConnectionType {
     }
 }
 
+impl ConnectionType {
+    pub fn is_redis(&self) -> bool {
+        matches!(self, ConnectionType::Redis | ConnectionType::RedisCluster)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ConnectionType {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "redis" => Ok(ConnectionType::Redis),
+            "rediscluster" | "redis cluster" => Ok(ConnectionType::RedisCluster),
+            "postgres" => Ok(ConnectionType::Postgres),
+            "mysql" => Ok(ConnectionType::MySql),
+            _ => Err(format!("Unknown connection type '{}', expected Redis, RedisCluster, Postgres or MySql", value)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, PartialOrd, Clone, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Dev,
+    Staging,
+    Prod
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let environment_str = match self {
+            Environment::Dev => "dev",
+            Environment::Staging => "staging",
+            Environment::Prod => "prod",
+        };
+        write!(f, "{}", environment_str)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Environment {
+    type Error = String;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "dev" => Ok(Environment::Dev),
+            "staging" => Ok(Environment::Staging),
+            "prod" => Ok(Environment::Prod),
+            _ => Err(format!("Unknown environment '{}', expected dev, staging or prod", value)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, PartialOrd, Clone, Eq)]
 pub struct Connection {
     pub connection_type: ConnectionType,
     pub name: String,
-    pub connection_string: String
+    pub connection_string: String,
+    #[serde(default)]
+    pub password_file: Option<PathBuf>,
+    #[serde(default)]
+    pub password_command: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+    // Redis only: talk RESP3 (maps, attributes, push messages) instead of RESP2
+    #[serde(default)]
+    pub resp3: bool,
+    #[serde(default)]
+    pub environment: Option<Environment>,
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // File the connection was loaded from, edits are written back there
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+}
+
+impl Connection {
+    // host[:port] part of the connection string, without credentials
+    pub fn host(&self) -> &str {
+        let rest = self.connection_string.split_once("://").map(|(_, rest)| rest).unwrap_or(&self.connection_string);
+        let authority = rest.split('/').next().unwrap_or(rest);
+        authority.rsplit_once('@').map(|(_, host)| host).unwrap_or(authority)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    #[serde(default)]
+    pub include: Vec<PathBuf>,
+    #[serde(default)]
     pub connections: Vec<Connection>
 }
@@ -0,0 +1,122 @@
+use std::{fs, path::Path};
+
+use toml_edit::{Array, ArrayOfTables, DocumentMut, Item, Table, Value};
+
+use super::{loader::ConfigError, Config, Connection};
+
+impl Config {
+    // Updates the connection named `original_name` in `path`, or appends a new one when it is None.
+    // Everything else in the file (comments, formatting, other keys) is kept as is.
+    pub fn save_connection(path: &Path, original_name: Option<&str>, connection: &Connection) -> Result<(), ConfigError> {
+        let mut document = read_document(path)?;
+        let connections = connections_mut(&mut document, path)?;
+
+        match original_name {
+            Some(name) => {
+                let table = connections.iter_mut()
+                    .find(|table| table.get("name").and_then(Item::as_str) == Some(name))
+                    .ok_or_else(|| ConfigError::Invalid(path.to_path_buf(), format!("connection '{}' not found", name)))?;
+                write_connection(table, connection);
+            },
+            None => {
+                let mut table = Table::new();
+                table.decor_mut().set_prefix("\n");
+                write_connection(&mut table, connection);
+                connections.push(table);
+            },
+        }
+
+        fs::write(path, document.to_string()).map_err(|err| ConfigError::Io(path.to_path_buf(), err))
+    }
+
+    pub fn remove_connection(path: &Path, name: &str) -> Result<(), ConfigError> {
+        let mut document = read_document(path)?;
+        let connections = connections_mut(&mut document, path)?;
+
+        let index = connections.iter()
+            .position(|table| table.get("name").and_then(Item::as_str) == Some(name))
+            .ok_or_else(|| ConfigError::Invalid(path.to_path_buf(), format!("connection '{}' not found", name)))?;
+        connections.remove(index);
+
+        fs::write(path, document.to_string()).map_err(|err| ConfigError::Io(path.to_path_buf(), err))
+    }
+}
+
+fn read_document(path: &Path) -> Result<DocumentMut, ConfigError> {
+    let content = fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+    content.parse::<DocumentMut>().map_err(|err| ConfigError::parse(path, &content, err.span(), err.message()))
+}
+
+fn connections_mut<'a>(document: &'a mut DocumentMut, path: &Path) -> Result<&'a mut ArrayOfTables, ConfigError> {
+    document.entry("connections")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| ConfigError::Invalid(path.to_path_buf(), "'connections' must be written as [[connections]] tables".to_string()))
+}
+
+fn write_connection(table: &mut Table, connection: &Connection) {
+    set_str(table, "connection_type", Some(&connection.connection_type.to_string()));
+    set_str(table, "name", Some(&connection.name));
+    set_str(table, "connection_string", Some(&connection.connection_string));
+    set_str(table, "password_file", connection.password_file.as_ref().and_then(|path| path.to_str()));
+    set_str(table, "password_command", connection.password_command.as_deref());
+    set_str(table, "environment", connection.environment.as_ref().map(|environment| environment.to_string()).as_deref());
+    set_str(table, "group", connection.group.as_deref());
+
+    if connection.tags.is_empty() {
+        table.remove("tags");
+    } else {
+        let current_tags: Option<Vec<&str>> = table.get("tags")
+            .and_then(Item::as_array)
+            .map(|tags| tags.iter().filter_map(Value::as_str).collect());
+        if current_tags.as_ref().map(|tags| tags.iter().copied().eq(connection.tags.iter().map(|tag| tag.as_str()))) != Some(true) {
+            let tags: Array = connection.tags.iter().map(|tag| tag.as_str()).collect();
+            set_value(table, "tags", Value::Array(tags));
+        }
+    }
+
+    if connection.read_only {
+        if table.get("read_only").and_then(Item::as_bool) != Some(true) {
+            set_value(table, "read_only", Value::from(true));
+        }
+    } else {
+        table.remove("read_only");
+    }
+
+    if connection.resp3 {
+        if table.get("resp3").and_then(Item::as_bool) != Some(true) {
+            set_value(table, "resp3", Value::from(true));
+        }
+    } else {
+        table.remove("resp3");
+    }
+}
+
+// Only touches keys whose value changed to keep the original formatting
+fn set_str(table: &mut Table, key: &str, new_value: Option<&str>) {
+    match new_value {
+        Some(new_value) => {
+            if table.get(key).and_then(Item::as_str) != Some(new_value) {
+                set_value(table, key, Value::from(new_value));
+            }
+        },
+        None => {
+            table.remove(key);
+        },
+    }
+}
+
+// Keeps comments around the key and the value when replacing an existing one
+fn set_value(table: &mut Table, key: &str, mut new_value: Value) {
+    match table.get_mut(key) {
+        Some(item) => {
+            if let Some(old_value) = item.as_value() {
+                *new_value.decor_mut() = old_value.decor().clone();
+            }
+            *item = Item::Value(new_value);
+        },
+        None => {
+            table.insert(key, Item::Value(new_value));
+        },
+    }
+}
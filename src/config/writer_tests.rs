@@ -0,0 +1,126 @@
+use std::{fs, path::PathBuf};
+
+use crate::config::{Config, Connection, ConnectionType, Environment};
+
+const CONFIG: &str = r#"# connections of the team
+include = ["other.toml"]
+
+[[connections]]
+connection_type = "Redis"
+name = "cache"
+connection_string = "redis://cache:6379/0" # primary
+read_only = true
+tags = ["eu", "hot"]
+
+[[connections]]
+connection_type = "Redis"
+name = "queue"
+connection_string = "redis://queue:6379/0"
+"#;
+
+fn connection(name: &str, connection_string: &str) -> Connection {
+    Connection {
+        connection_type: ConnectionType::Redis,
+        name: name.to_string(),
+        connection_string: connection_string.to_string(),
+        password_file: None,
+        password_command: None,
+        read_only: false,
+        resp3: false,
+        environment: None,
+        group: None,
+        tags: vec![],
+        source: None,
+    }
+}
+
+fn config_file(dir: &tempfile::TempDir) -> PathBuf {
+    let path = dir.path().join("config.toml");
+    fs::write(&path, CONFIG).unwrap();
+    path
+}
+
+fn parse(path: &PathBuf) -> Config {
+    toml::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_save_connection_keeps_formatting() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_file(&dir);
+    let mut cache = parse(&path).connections.remove(0);
+    cache.connection_string = "redis://cache:6380/0".to_string();
+    Config::save_connection(&path, Some("cache"), &cache).unwrap();
+
+    let content = fs::read_to_string(&path).unwrap();
+    assert_eq!(content, CONFIG.replace("redis://cache:6379/0", "redis://cache:6380/0"));
+}
+
+#[test]
+fn test_save_connection_renames_and_clears_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_file(&dir);
+    let renamed = Connection {
+        environment: Some(Environment::Prod),
+        password_command: Some("pass show cache".to_string()),
+        ..connection("primary", "redis://cache:6379/0")
+    };
+    Config::save_connection(&path, Some("cache"), &renamed).unwrap();
+
+    let config = parse(&path);
+    assert_eq!(config.include, vec![PathBuf::from("other.toml")]);
+    assert_eq!(config.connections[0], renamed);
+    assert_eq!(config.connections[1].name, "queue");
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(!content.contains("read_only"), "{}", content);
+    assert!(!content.contains("tags"), "{}", content);
+}
+
+#[test]
+fn test_save_new_connection() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_file(&dir);
+    let added = Connection {
+        read_only: true,
+        resp3: true,
+        group: Some("work".to_string()),
+        tags: vec!["new".to_string()],
+        password_file: Some(PathBuf::from("~/.secrets/sessions")),
+        ..connection("sessions", "redis://sessions:6379/0")
+    };
+    Config::save_connection(&path, None, &added).unwrap();
+
+    let config = parse(&path);
+    assert_eq!(config.connections.len(), 3);
+    assert_eq!(config.connections[2], added);
+    assert!(fs::read_to_string(&path).unwrap().starts_with(CONFIG));
+}
+
+#[test]
+fn test_save_connection_into_empty_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "").unwrap();
+    Config::save_connection(&path, None, &connection("cache", "redis://cache")).unwrap();
+    assert_eq!(parse(&path).connections, vec![connection("cache", "redis://cache")]);
+}
+
+#[test]
+fn test_save_missing_connection() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_file(&dir);
+    assert!(Config::save_connection(&path, Some("missing"), &connection("missing", "redis://x")).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), CONFIG);
+}
+
+#[test]
+fn test_remove_connection() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_file(&dir);
+    Config::remove_connection(&path, "cache").unwrap();
+
+    let config = parse(&path);
+    assert_eq!(config.connections.iter().map(|connection| connection.name.as_str()).collect::<Vec<_>>(), vec!["queue"]);
+    assert!(fs::read_to_string(&path).unwrap().starts_with("# connections of the team\n"));
+    assert!(Config::remove_connection(&path, "cache").is_err());
+}
@@ -1,4 +1,4 @@
-use super::fetcher::{FetchResult, Fetcher};
+use super::fetcher::{FetchResult, Fetcher, FetcherError, ServerInfo};
 
 pub struct DummyFetcher {
     objects: Vec<String>
@@ -20,5 +20,23 @@ impl Fetcher for DummyFetcher {
     fn fetch_db_objects(&mut self) -> Result<FetchResult, super::fetcher::FetcherError> {
         Ok(FetchResult::multiple(&self.objects))
     }
+
+    // nothing connects to the server, reporting it as healthy would hide that
+    fn server_info(&mut self) -> Result<ServerInfo, super::fetcher::FetcherError> {
+        Err(FetcherError::Unsupported("test connection".to_string()))
+    }
+
+    // nothing runs the queued queries, reporting them as committed would hide that
+    fn begin(&mut self, _: &[String]) -> Result<(), super::fetcher::FetcherError> {
+        Err(FetcherError::Unsupported("BEGIN".to_string()))
+    }
+
+    fn commit(&mut self, _: &[String]) -> Result<Option<Vec<FetchResult>>, super::fetcher::FetcherError> {
+        Err(FetcherError::Unsupported("COMMIT".to_string()))
+    }
+
+    fn rollback(&mut self) -> Result<(), super::fetcher::FetcherError> {
+        Err(FetcherError::Unsupported("ROLLBACK".to_string()))
+    }
 }
 
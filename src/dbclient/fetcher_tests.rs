@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+use crate::dbclient::fetcher::{Reply, TreeRow};
+
+fn string(value: &str) -> Reply {
+    Reply::Scalar("string".to_string(), value.to_string())
+}
+
+fn integer(value: i64) -> Reply {
+    Reply::Scalar("integer".to_string(), value.to_string())
+}
+
+fn row(path: &str, key: &str, depth: usize, type_name: &str, value: &str, children: usize) -> TreeRow {
+    TreeRow {
+        path: path.to_string(),
+        key: key.to_string(),
+        depth,
+        type_name: type_name.to_string(),
+        value: value.to_string(),
+        children,
+    }
+}
+
+// `["a", ["b", nil], {"k": [1, 2]}]`
+fn nested() -> Reply {
+    Reply::List("array".to_string(), vec![
+        string("a"),
+        Reply::List("array".to_string(), vec![string("b"), Reply::Nil]),
+        Reply::Map(vec![(string("k"), Reply::List("set".to_string(), vec![integer(1), integer(2)]))]),
+    ])
+}
+
+#[test]
+fn test_inline() {
+    let cases = [
+        (Reply::Nil, "(nil)"),
+        (integer(42), "42"),
+        (Reply::List("array".to_string(), vec![]), "[]"),
+        (Reply::Map(vec![]), "{}"),
+        (nested(), "[a, [b, (nil)], {k: [1, 2]}]"),
+        (Reply::Map(vec![(Reply::List("array".to_string(), vec![integer(1)]), Reply::Map(vec![(string("x"), Reply::Nil)]))]), "{[1]: {x: (nil)}}"),
+        (Reply::Attribute(Box::new(string("v")), vec![(string("ttl"), integer(10))]), "v"),
+    ];
+    for (reply, expected) in cases {
+        assert_eq!(reply.inline(), expected, "{:?}", reply);
+    }
+}
+
+#[test]
+fn test_tree() {
+    assert_eq!(nested().tree(&HashSet::new()), vec![
+        row("0", "0", 0, "string", "a", 0),
+        row("1", "1", 0, "array", "(2 items)", 2),
+        row("1.0", "0", 1, "string", "b", 0),
+        row("1.1", "1", 1, "nil", "(nil)", 0),
+        row("2", "2", 0, "map", "(1 entries)", 1),
+        row("2.0", "k", 1, "set", "(2 items)", 2),
+        row("2.0.0", "0", 2, "integer", "1", 0),
+        row("2.0.1", "1", 2, "integer", "2", 0),
+    ]);
+
+    // children of collapsed paths are skipped, the collapsed node itself stays
+    let collapsed = HashSet::from(["1".to_string(), "2.0".to_string()]);
+    let paths: Vec<String> = nested().tree(&collapsed).into_iter().map(|row| row.path).collect();
+    assert_eq!(paths, vec!["0", "1", "2", "2.0"]);
+}
+
+#[test]
+fn test_tree_with_attributes() {
+    let reply = Reply::Map(vec![
+        (string("k"), Reply::Attribute(Box::new(integer(3)), vec![(string("ttl"), integer(10))])),
+    ]);
+    assert_eq!(reply.tree(&HashSet::new()), vec![
+        row("0", "k", 0, "integer", "3", 1),
+        row("0.@0", "@ttl", 1, "attribute", "10", 0),
+    ]);
+    assert_eq!(reply.attributes(), vec![("ttl".to_string(), "10".to_string())]);
+}
@@ -3,22 +3,166 @@ pub mod redis;
 pub mod dummy;
 
 pub mod query_builder;
+#[cfg(test)]
+mod fetcher_tests;
+#[cfg(test)]
+mod query_builder_tests;
 
 pub(crate) mod fetcher {
-    use std::collections::HashMap;
+    use std::{collections::{HashMap, HashSet}, fmt, time::Duration};
 
     use super::query_builder::QueryElement;
 
     type IndexColumn = String;
+    // index columns and every column by name
+    pub type Table = (Vec<IndexColumn>, HashMap<String, Vec<String>>);
 
     #[derive(Debug, PartialEq, Clone)]
     pub struct FetchResult {
-        pub table: Option<(Vec<IndexColumn>, HashMap<String, Vec<String>>)>,
+        pub table: Option<Table>,
+        // Structure of the reply the table was built from, when the database has one
+        pub reply: Option<Reply>,
     }
 
     #[derive(Debug, PartialEq, Clone)]
-    pub struct Row {
-        pub columns: Vec<String>
+    pub enum Reply {
+        Nil,
+        // type name and value
+        Scalar(String, String),
+        // type name (array, set, push) and items
+        List(String, Vec<Reply>),
+        Map(Vec<(Reply, Reply)>),
+        // data and the attributes sent along with it
+        Attribute(Box<Reply>, Vec<(Reply, Reply)>),
+    }
+
+    // One line of the tree view of a reply
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct TreeRow {
+        // indices from the root, `0.2.1`, identifies the row between renders
+        pub path: String,
+        // index in a list, key in a map
+        pub key: String,
+        pub depth: usize,
+        pub type_name: String,
+        pub value: String,
+        pub children: usize,
+    }
+
+    impl Reply {
+        pub fn type_name(&self) -> &str {
+            match self {
+                Reply::Nil => "nil",
+                Reply::Scalar(type_name, _) | Reply::List(type_name, _) => type_name.as_str(),
+                Reply::Map(_) => "map",
+                Reply::Attribute(data, _) => data.type_name(),
+            }
+        }
+
+        pub fn is_nested(&self) -> bool {
+            match self {
+                Reply::Nil | Reply::Scalar(..) => false,
+                Reply::Attribute(data, _) => data.is_nested(),
+                Reply::List(..) | Reply::Map(_) => true,
+            }
+        }
+
+        // Whether any item needs more than a cell to be shown
+        pub fn has_nested_items(&self) -> bool {
+            let nested = |item: &Reply| item.is_nested() || matches!(item, Reply::Attribute(..));
+            match self {
+                Reply::List(_, items) => items.iter().any(nested),
+                Reply::Map(entries) => entries.iter().any(|(key, value)| nested(key) || nested(value)),
+                Reply::Attribute(data, _) => data.has_nested_items(),
+                Reply::Nil | Reply::Scalar(..) => false,
+            }
+        }
+
+        // Single line representation, `[a, b]` for lists and `{k: v}` for maps
+        pub fn inline(&self) -> String {
+            match self {
+                Reply::Nil => "(nil)".to_string(),
+                Reply::Scalar(_, value) => value.clone(),
+                Reply::List(_, items) => format!("[{}]", items.iter().map(Reply::inline).collect::<Vec<_>>().join(", ")),
+                Reply::Map(entries) => format!(
+                    "{{{}}}",
+                    entries.iter().map(|(key, value)| format!("{}: {}", key.inline(), value.inline())).collect::<Vec<_>>().join(", "),
+                ),
+                Reply::Attribute(data, _) => data.inline(),
+            }
+        }
+
+        // Attributes found anywhere in the reply
+        pub fn attributes(&self) -> Vec<(String, String)> {
+            match self {
+                Reply::Nil | Reply::Scalar(..) => vec![],
+                Reply::List(_, items) => items.iter().flat_map(Reply::attributes).collect(),
+                Reply::Map(entries) => entries.iter().flat_map(|(_, value)| value.attributes()).collect(),
+                Reply::Attribute(data, attributes) => attributes.iter()
+                    .map(|(key, value)| (key.inline(), value.inline()))
+                    .chain(data.attributes())
+                    .collect(),
+            }
+        }
+
+        // Depth-first rows of a nested reply without the reply itself, children of `collapsed` paths are skipped
+        pub fn tree(&self, collapsed: &HashSet<String>) -> Vec<TreeRow> {
+            let mut rows = vec![];
+            self.tree_rows("", 0, collapsed, &mut rows);
+            rows
+        }
+
+        fn tree_rows(&self, path: &str, depth: usize, collapsed: &HashSet<String>, rows: &mut Vec<TreeRow>) {
+            let (data, attributes) = match self {
+                Reply::Attribute(data, attributes) => (data.as_ref(), attributes.as_slice()),
+                reply => (reply, &[][..]),
+            };
+            let child_path = |index: String| if path.is_empty() { index } else { format!("{}.{}", path, index) };
+            for (index, (key, value)) in attributes.iter().enumerate() {
+                rows.push(TreeRow {
+                    path: child_path(format!("@{}", index)),
+                    key: format!("@{}", key.inline()),
+                    depth,
+                    type_name: "attribute".to_string(),
+                    value: value.inline(),
+                    children: 0,
+                });
+            }
+            let children: Vec<(String, &Reply)> = match data {
+                Reply::List(_, items) => items.iter().enumerate().map(|(index, item)| (index.to_string(), item)).collect(),
+                Reply::Map(entries) => entries.iter().map(|(key, value)| (key.inline(), value)).collect(),
+                _ => vec![],
+            };
+            for (index, (key, child)) in children.into_iter().enumerate() {
+                let path = child_path(index.to_string());
+                let count = child.children();
+                rows.push(TreeRow {
+                    path: path.clone(),
+                    key,
+                    depth,
+                    type_name: child.type_name().to_string(),
+                    value: match child {
+                        Reply::Nil | Reply::Scalar(..) => child.inline(),
+                        Reply::Attribute(data, _) if !data.is_nested() => data.inline(),
+                        _ => format!("({} {})", count, if child.type_name() == "map" { "entries" } else { "items" }),
+                    },
+                    children: count,
+                });
+                if count > 0 && !collapsed.contains(&path) {
+                    child.tree_rows(&path, depth + 1, collapsed, rows);
+                }
+            }
+        }
+
+        // Number of rows directly under this reply in the tree
+        fn children(&self) -> usize {
+            match self {
+                Reply::Nil | Reply::Scalar(..) => 0,
+                Reply::List(_, items) => items.len(),
+                Reply::Map(entries) => entries.len(),
+                Reply::Attribute(data, attributes) => data.children() + attributes.len(),
+            }
+        }
     }
 
     #[derive(Debug, PartialEq, Clone)]
@@ -27,12 +171,40 @@ pub(crate) mod fetcher {
         pub limit: usize,
     }
 
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct ServerInfo {
+        pub latency: Duration,
+        pub version: Option<String>,
+        pub user: Option<String>,
+    }
+
     #[derive(Debug, PartialEq)]
     pub enum FetcherError {
         InvalidQuery,
+        ReadOnly(String),
+        NoTransaction,
+        CrossSlot,
+        // cluster transaction without keys, nothing tells which node runs it
+        NoSlot,
+        // command the fetcher can't run, e.g. transactions on SQL connections until they have a driver
+        Unsupported(String),
         RedisError(redis::RedisError),
     }
 
+    impl fmt::Display for FetcherError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                FetcherError::InvalidQuery => write!(f, "Invalid query"),
+                FetcherError::ReadOnly(command) => write!(f, "Connection is read-only, '{}' is a write, admin or unknown command", command),
+                FetcherError::NoTransaction => write!(f, "No transaction in progress"),
+                FetcherError::CrossSlot => write!(f, "Keys of a cluster transaction must hash to one slot, use {{hash tags}}"),
+                FetcherError::NoSlot => write!(f, "A cluster transaction needs at least one key to pick the node it runs on"),
+                FetcherError::Unsupported(command) => write!(f, "'{}' is not supported for this connection type yet", command),
+                FetcherError::RedisError(err) => write!(f, "{}", err),
+            }
+        }
+    }
+
     impl From<redis::RedisError> for FetcherError {
         fn from(err: redis::RedisError) -> Self {
             FetcherError::RedisError(err)
@@ -42,11 +214,29 @@ pub(crate) mod fetcher {
     pub trait Fetcher {
         fn fetch_db_objects(&mut self) -> Result<FetchResult, FetcherError>;
         fn fetch(&mut self, request: &FetchRequest) -> Result<FetchResult, FetcherError>;
+        fn server_info(&mut self) -> Result<ServerInfo, FetcherError>;
+        // A transaction keeps one connection open from `begin` until `commit` or `rollback`.
+        // Redis WATCHes the keys on begin and runs the queries between MULTI and EXEC on commit,
+        // None when a watched key changed in between. SQL databases map it to BEGIN, COMMIT and ROLLBACK
+        fn begin(&mut self, watch: &[String]) -> Result<(), FetcherError>;
+        fn commit(&mut self, queries: &[String]) -> Result<Option<Vec<FetchResult>>, FetcherError>;
+        fn rollback(&mut self) -> Result<(), FetcherError>;
     }
 
     impl FetchResult {
         pub fn none() -> FetchResult {
-            FetchResult { table: None }
+            FetchResult { table: None, reply: None }
+        }
+
+        pub fn error(err: &FetcherError) -> FetchResult {
+            let mut table = HashMap::new();
+            let index_column = "error".to_string();
+            table.insert(index_column.clone(), vec![err.to_string()]);
+
+            FetchResult {
+                table: Some((vec![index_column], table)),
+                reply: None,
+            }
         }
 
         pub fn single<T>(item: &T) -> FetchResult where T: ToString {
@@ -56,24 +246,47 @@ pub(crate) mod fetcher {
 
             FetchResult {
                 table: Some((vec![index_column], table)),
+                reply: None,
             }
         }
 
-        pub fn multiple<T>(items: &Vec<T>) -> FetchResult where T: ToString {
+        pub fn multiple<T>(items: &[T]) -> FetchResult where T: ToString {
             let mut table = HashMap::new();
             let index_column = "result".to_string();
             table.insert(index_column.clone(), items.iter().map(|item| item.to_string()).collect());
-            FetchResult { table: Some((vec![index_column], table)) }
+            FetchResult { table: Some((vec![index_column], table)), reply: None }
         }
 
-        pub fn key_value(items: HashMap<String, String>) -> FetchResult {
-            let keys: Vec<String> = items.keys().cloned().collect();
-            let values: Vec<String> = items.values().cloned().collect();
-            let mut table = HashMap::new();
-            let index_column = "keys".to_string();
-            table.insert(index_column.clone(), keys);
-            table.insert("values".to_string(), values);
-            FetchResult { table: Some((vec![index_column], table)) }
+        // Scalars as a single value, lists as one column and maps as key/value pairs,
+        // nested values are written inline
+        pub fn from_reply(reply: Reply) -> FetchResult {
+            let data = match &reply {
+                Reply::Attribute(data, _) => data.as_ref(),
+                reply => reply,
+            };
+            let table = match data {
+                Reply::Nil => None,
+                Reply::Scalar(_, value) => FetchResult::single(value).table,
+                Reply::List(_, items) if items.is_empty() => None,
+                Reply::List(_, items) => FetchResult::multiple(&items.iter().map(Reply::inline).collect::<Vec<String>>()).table,
+                Reply::Map(entries) => {
+                    let mut table = HashMap::new();
+                    table.insert("key".to_string(), entries.iter().map(|(key, _)| key.inline()).collect());
+                    table.insert("value".to_string(), entries.iter().map(|(_, value)| value.inline()).collect());
+                    Some((vec!["key".to_string(), "value".to_string()], table))
+                },
+                Reply::Attribute(..) => None,
+            };
+            FetchResult { table, reply: Some(reply) }
+        }
+
+        // Set and map replies, whose order depends on the server
+        pub fn is_unordered(&self) -> bool {
+            let data = match &self.reply {
+                Some(Reply::Attribute(data, _)) => Some(data.as_ref()),
+                reply => reply.as_ref(),
+            };
+            matches!(data, Some(Reply::List(type_name, _)) if type_name == "set") || matches!(data, Some(Reply::Map(_)))
         }
 
         pub fn merge(result1: &FetchResult, result2: &FetchResult) -> FetchResult {
@@ -89,28 +302,7 @@ pub(crate) mod fetcher {
                     Some(merged_table)
                 },
             };
-            FetchResult { table }
-        }
-
-        pub fn join(result1: &FetchResult, result2: &FetchResult) -> FetchResult {
-            let table = match (&result1.table, &result2.table) {
-                (None, None) => None,
-                (None, Some(t)) => Some((t.0.clone(), t.1.clone())),
-                (Some(t), None) => Some((t.0.clone(), t.1.clone())),
-                (Some(t1), Some(t2)) => {
-                    let mut merged_table: HashMap<String, Vec<String>> = HashMap::new();
-                    let mut index_keys = t1.0.clone();
-                    index_keys.extend(t2.0.clone());
-                    for (key, value) in &t1.1 {
-                        merged_table.insert(format!("{}_1", key).to_string(), value.to_vec());
-                    }
-                    for (key, value) in &t2.1 {
-                        merged_table.insert(format!("{}_2", key).to_string(), value.to_vec());
-                    }
-                    Some((index_keys, merged_table))
-                },
-            };
-            FetchResult { table: table }
+            FetchResult { table, reply: None }
         }
     }
 }
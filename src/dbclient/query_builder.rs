@@ -2,5 +2,41 @@
 pub enum QueryElement {
     RawQuery(String),
     ListAllItemsFrom(String),
-    AddDatabaseObject(String, String, String)
+    AddDatabaseObject(String, String, String),
+    // name, lazy (free memory in background, e.g. UNLINK)
+    DeleteDatabaseObject(String, bool),
+    // name, new name, overwrite existing
+    RenameDatabaseObject(String, String, bool),
+    // name, destination name, destination database, overwrite existing
+    CopyDatabaseObject(String, String, Option<String>, bool),
+    // name, ttl in seconds (None clears the ttl)
+    ExpireDatabaseObject(String, Option<u64>),
+    // script body, KEYS, ARGV
+    EvalScript(String, Vec<String>, Vec<String>),
+}
+
+// DEL/UNLINK with at least this many keys needs a confirmation on prod connections
+const MANY_KEYS_THRESHOLD: usize = 10;
+
+impl QueryElement {
+    // Queries that need the connection name typed to run on prod connections
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            QueryElement::RawQuery(query) => {
+                let args: Vec<String> = query.split_whitespace().map(|arg| arg.to_uppercase()).collect();
+                match args.first().map(|command| command.as_str()) {
+                    Some("FLUSHDB") | Some("FLUSHALL") | Some("DROP") | Some("TRUNCATE") => true,
+                    Some("DEL") | Some("UNLINK") => args.len() > MANY_KEYS_THRESHOLD,
+                    Some("DELETE") => !args.iter().any(|arg| arg == "WHERE"),
+                    _ => false,
+                }
+            },
+            QueryElement::DeleteDatabaseObject(..) => true,
+            // overwriting drops the value of the destination key
+            QueryElement::RenameDatabaseObject(_, _, overwrite) | QueryElement::CopyDatabaseObject(_, _, _, overwrite) => *overwrite,
+            // scripts can write anything, guard_query confirms them unless the connection is read-only
+            QueryElement::ListAllItemsFrom(_) | QueryElement::AddDatabaseObject(..) | QueryElement::ExpireDatabaseObject(..)
+                | QueryElement::EvalScript(..) => false,
+        }
+    }
 }
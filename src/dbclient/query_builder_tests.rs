@@ -0,0 +1,51 @@
+use crate::dbclient::query_builder::QueryElement;
+
+fn raw(query: &str) -> QueryElement {
+    QueryElement::RawQuery(query.to_string())
+}
+
+fn keys(count: usize) -> String {
+    (0..count).map(|index| format!("key{}", index)).collect::<Vec<String>>().join(" ")
+}
+
+#[test]
+fn test_is_destructive_raw_queries() {
+    let cases = [
+        (raw("FLUSHDB"), true),
+        (raw("flushall async"), true),
+        (raw("GET key"), false),
+        (raw(""), false),
+        (raw(&format!("DEL {}", keys(1))), false),
+        (raw(&format!("DEL {}", keys(9))), false),
+        (raw(&format!("DEL {}", keys(10))), true),
+        (raw(&format!("unlink {}", keys(10))), true),
+        (raw("DROP TABLE users"), true),
+        (raw("TRUNCATE users"), true),
+        (raw("DELETE FROM users"), true),
+        (raw("DELETE FROM users WHERE id = 1"), false),
+        (raw("delete from users where id = 1"), false),
+        (raw("SELECT * FROM users"), false),
+    ];
+    for (query, destructive) in cases {
+        assert_eq!(query.is_destructive(), destructive, "{:?}", query);
+    }
+}
+
+#[test]
+fn test_is_destructive_object_actions() {
+    let cases = [
+        (QueryElement::ListAllItemsFrom("key".to_string()), false),
+        (QueryElement::AddDatabaseObject(String::new(), "string".to_string(), "key".to_string()), false),
+        (QueryElement::DeleteDatabaseObject("key".to_string(), false), true),
+        (QueryElement::DeleteDatabaseObject("key".to_string(), true), true),
+        (QueryElement::RenameDatabaseObject("key".to_string(), "new".to_string(), false), false),
+        (QueryElement::RenameDatabaseObject("key".to_string(), "new".to_string(), true), true),
+        (QueryElement::CopyDatabaseObject("key".to_string(), "new".to_string(), None, false), false),
+        (QueryElement::CopyDatabaseObject("key".to_string(), "new".to_string(), Some("1".to_string()), true), true),
+        (QueryElement::ExpireDatabaseObject("key".to_string(), Some(60)), false),
+        (QueryElement::EvalScript("return 1".to_string(), vec![], vec![]), false),
+    ];
+    for (query, destructive) in cases {
+        assert_eq!(query.is_destructive(), destructive, "{:?}", query);
+    }
+}
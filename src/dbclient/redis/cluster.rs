@@ -0,0 +1,240 @@
+use std::{collections::{BTreeSet, HashMap}, time::Duration};
+
+use redis::{cluster::{ClusterClientBuilder, ClusterConnection}, Connection, FromRedisValue, RedisError, Value};
+
+use crate::dbclient::fetcher::{FetchRequest, FetchResult, Fetcher, FetcherError, ServerInfo};
+
+use super::{connect, exec_queued, fetch_with, query_args, read_only::CommandCache, scan_keys, RedisConfig, RedisFetcher, CONNECT_TIMEOUT};
+
+const SLOTS: u16 = 16384;
+// Replies of the nodes, longer than POLL_TIMEOUT as user queries run on them, but bounded
+// so that a half-open node fails the query instead of hanging the UI
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Master of a cluster with the slots it serves
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClusterNode {
+    // host:port announced by the cluster
+    pub address: String,
+    // inclusive ranges
+    pub slots: Vec<(u16, u16)>,
+}
+
+impl ClusterNode {
+    pub fn owns(&self, slot: u16) -> bool {
+        self.slots.iter().any(|(start, end)| (*start..=*end).contains(&slot))
+    }
+}
+
+// The connection string of a cluster lists seed nodes separated by commas
+pub fn seed_uris(uri: &str) -> Vec<String> {
+    uri.split(',').map(str::trim).filter(|seed| !seed.is_empty()).map(str::to_string).collect()
+}
+
+// `seed` with its host and port replaced by `address`, keeping the scheme, credentials and options
+pub fn node_uri(seed: &str, address: &str) -> String {
+    let (scheme, rest) = seed.split_once("://").unwrap_or(("redis", seed));
+    let (credentials, rest) = match rest.rsplit_once('@') {
+        Some((credentials, rest)) => (format!("{}@", credentials), rest),
+        None => (String::new(), rest),
+    };
+    let path = rest.find(['/', '?']).map(|index| &rest[index..]).unwrap_or("");
+    format!("{}://{}{}{}", scheme, credentials, address, path)
+}
+
+// CRC16 of the key, or of its `{hash tag}`, modulo the number of slots
+pub fn key_slot(key: &str) -> u16 {
+    let bytes = key.as_bytes();
+    let hashed = bytes.iter().position(|byte| *byte == b'{')
+        .and_then(|open| {
+            let tag = &bytes[open + 1..];
+            tag.iter().position(|byte| *byte == b'}').filter(|len| *len > 0).map(|len| &tag[..len])
+        })
+        .unwrap_or(bytes);
+    crc16(hashed) % SLOTS
+}
+
+// CRC16/XMODEM, the variant Redis Cluster hashes keys with
+pub fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 })
+    })
+}
+
+// Slot of the node a transaction runs on, every key must hash to it and at least one key is needed to pick it
+pub fn transaction_slot(slots: impl IntoIterator<Item = u16>) -> Result<u16, FetcherError> {
+    RedisClusterFetcher::single_slot(slots)?.ok_or(FetcherError::NoSlot)
+}
+
+// COMMAND GETKEYS of a command without keys (PING, ...), Redis 6 and 7 word it differently
+pub fn is_keyless_error(err: &RedisError) -> bool {
+    err.detail().is_some_and(|detail| detail.contains("no key arguments") || detail == "Invalid arguments specified for command")
+}
+
+// Masters and their slots from CLUSTER SLOTS of the first seed that answers
+pub fn discover_nodes(uri: &str) -> Result<Vec<ClusterNode>, FetcherError> {
+    let mut last_error = FetcherError::InvalidQuery;
+    for seed in seed_uris(uri) {
+        let ranges = connect(&seed, Some(CONNECT_TIMEOUT))
+            .and_then(|mut connection| Ok(redis::cmd("CLUSTER").arg("SLOTS").query::<Vec<Vec<Value>>>(&mut connection)?));
+        match ranges {
+            Ok(ranges) => return parse_slots(&ranges),
+            Err(err) => last_error = err,
+        }
+    }
+    Err(last_error)
+}
+
+// [start, end, [host, port, id, ...], replicas...] for every slot range
+fn parse_slots(ranges: &[Vec<Value>]) -> Result<Vec<ClusterNode>, FetcherError> {
+    let mut nodes: Vec<ClusterNode> = vec![];
+    for range in ranges {
+        let field = |index: usize| range.get(index).ok_or(FetcherError::InvalidQuery);
+        let (start, end) = (u16::from_redis_value(field(0)?)?, u16::from_redis_value(field(1)?)?);
+        let master: Vec<Value> = Vec::from_redis_value(field(2)?)?;
+        let host = master.first().map(String::from_redis_value).transpose()?.unwrap_or_default();
+        let port = master.get(1).map(u16::from_redis_value).transpose()?.unwrap_or_default();
+        let address = format!("{}:{}", host, port);
+        match nodes.iter_mut().find(|node| node.address == address) {
+            Some(node) => node.slots.push((start, end)),
+            None => nodes.push(ClusterNode { address, slots: vec![(start, end)] }),
+        }
+    }
+    nodes.sort_by(|left, right| left.address.cmp(&right.address));
+    Ok(nodes)
+}
+
+pub struct RedisClusterFetcher {
+    pub config: RedisConfig,
+    in_transaction: bool,
+    // slot of the watched keys and the connection to their node, WATCH only holds on that connection
+    watch: Option<(u16, Connection)>,
+    commands: CommandCache,
+    // masters from the last CLUSTER SLOTS, dropped when a node can't be reached
+    nodes: Option<Vec<ClusterNode>>,
+}
+
+impl RedisClusterFetcher {
+    pub fn new(config: RedisConfig) -> Self {
+        Self { config, in_transaction: false, watch: None, commands: CommandCache::default(), nodes: None }
+    }
+
+    // Follows MOVED/ASK redirections and sends every command to the node of its slot
+    fn connection(&self) -> Result<ClusterConnection, FetcherError> {
+        let client = ClusterClientBuilder::new(seed_uris(&self.config.uri))
+            .connection_timeout(CONNECT_TIMEOUT)
+            .response_timeout(RESPONSE_TIMEOUT)
+            .build()?;
+        Ok(client.get_connection()?)
+    }
+
+    fn node_connection(&self, address: &str) -> Result<Connection, FetcherError> {
+        let seed = seed_uris(&self.config.uri).into_iter().next().unwrap_or_default();
+        connect(&node_uri(&seed, address), Some(RESPONSE_TIMEOUT))
+    }
+
+    fn nodes(&mut self) -> Result<&[ClusterNode], FetcherError> {
+        if self.nodes.is_none() {
+            self.nodes = Some(discover_nodes(&self.config.uri)?);
+        }
+        Ok(self.nodes.as_deref().unwrap_or_default())
+    }
+
+    fn slot_connection(&mut self, slot: u16) -> Result<Connection, FetcherError> {
+        let address = self.nodes()?.iter().find(|node| node.owns(slot)).map(|node| node.address.clone());
+        let connection = address.ok_or(FetcherError::InvalidQuery).and_then(|address| self.node_connection(&address));
+        if connection.is_err() {
+            // the slots may have moved, ask the cluster again next time
+            self.nodes = None;
+        }
+        connection
+    }
+
+    // Slot every key hashes to, a transaction runs on a single node
+    fn single_slot(keys: impl IntoIterator<Item = u16>) -> Result<Option<u16>, FetcherError> {
+        let slots: BTreeSet<u16> = keys.into_iter().collect();
+        match slots.len() {
+            0 => Ok(None),
+            1 => Ok(slots.first().copied()),
+            _ => Err(FetcherError::CrossSlot),
+        }
+    }
+}
+
+impl Fetcher for RedisClusterFetcher {
+    fn fetch(&mut self, request: &FetchRequest) -> Result<FetchResult, FetcherError> {
+        let mut connection = self.connection()?;
+        fetch_with(&mut connection, self.config.read_only, &mut self.commands, request)
+    }
+
+    // Keys of every master with the slot and node they live on
+    fn fetch_db_objects(&mut self) -> Result<FetchResult, FetcherError> {
+        let mut keys: Vec<(String, String)> = vec![];
+        // a reload of the object list picks up resharding
+        let nodes = discover_nodes(&self.config.uri)?;
+        self.nodes = Some(nodes.clone());
+        for node in nodes {
+            let mut connection = self.node_connection(&node.address)?;
+            keys.extend(scan_keys(&mut connection, "*")?.into_iter()
+                .map(|key| {
+                    let owner = format!("slot {} @ {}", key_slot(&key), node.address);
+                    (key, owner)
+                }));
+        }
+        keys.sort();
+        let mut table = HashMap::new();
+        table.insert("result".to_string(), keys.iter().map(|(key, _)| key.clone()).collect());
+        table.insert("node".to_string(), keys.into_iter().map(|(_, owner)| owner).collect());
+        Ok(FetchResult { table: Some((vec!["result".to_string()], table)), reply: None })
+    }
+
+    // Of the first seed node
+    fn server_info(&mut self) -> Result<ServerInfo, FetcherError> {
+        let seed = seed_uris(&self.config.uri).into_iter().next().unwrap_or_default();
+        RedisFetcher::new(RedisConfig { uri: seed, read_only: self.config.read_only }).server_info()
+    }
+
+    fn begin(&mut self, watch: &[String]) -> Result<(), FetcherError> {
+        self.watch = None;
+        if let Some(slot) = Self::single_slot(watch.iter().map(|key| key_slot(key)))? {
+            let mut connection = self.slot_connection(slot)?;
+            redis::cmd("WATCH").arg(watch).query::<()>(&mut connection)?;
+            self.watch = Some((slot, connection));
+        }
+        self.in_transaction = true;
+        Ok(())
+    }
+
+    fn commit(&mut self, queries: &[String]) -> Result<Option<Vec<FetchResult>>, FetcherError> {
+        if !std::mem::take(&mut self.in_transaction) {
+            return Err(FetcherError::NoTransaction);
+        }
+        let watch = self.watch.take();
+        // the server finds the keys of each command, sent as exec_queued will send it
+        let mut cluster = self.connection()?;
+        let mut slots: Vec<u16> = watch.iter().map(|(slot, _)| *slot).collect();
+        for query in queries {
+            let keys: Vec<String> = match redis::cmd("COMMAND").arg("GETKEYS").arg(query_args(query)).query(&mut cluster) {
+                Ok(keys) => keys,
+                Err(err) if is_keyless_error(&err) => vec![],
+                Err(err) => return Err(err.into()),
+            };
+            slots.extend(keys.iter().map(|key| key_slot(key)));
+        }
+        let slot = transaction_slot(slots)?;
+        let mut connection = match watch {
+            Some((_, connection)) => connection,
+            None => self.slot_connection(slot)?,
+        };
+        exec_queued(&mut connection, self.config.read_only, &mut self.commands, queries)
+    }
+
+    fn rollback(&mut self) -> Result<(), FetcherError> {
+        self.watch = None;
+        if std::mem::take(&mut self.in_transaction) {
+            Ok(())
+        } else {
+            Err(FetcherError::NoTransaction)
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use redis::{ErrorKind, RedisError};
+
+use crate::dbclient::{fetcher::FetcherError, redis::cluster::{crc16, is_keyless_error, key_slot, node_uri, seed_uris, transaction_slot}};
+
+#[test]
+fn test_crc16() {
+    assert_eq!(crc16(b"123456789"), 0x31C3);
+    assert_eq!(crc16(b""), 0);
+}
+
+#[test]
+fn test_key_slot() {
+    let cases = [
+        ("foo", 12182),
+        ("bar", 5061),
+        ("{user1000}.following", 3443),
+        ("{user1000}.followers", 3443),
+        // the first `{` opens the tag, an empty tag hashes the whole key
+        ("foo{}{bar}", 8363),
+        ("foo{{bar}}zap", 4015),
+    ];
+    for (key, expected) in cases {
+        assert_eq!(key_slot(key), expected, "key: {}", key);
+    }
+    assert_eq!(key_slot("foo{bar}zap"), key_slot("bar"));
+    // an unclosed tag hashes the whole key
+    assert_eq!(key_slot("foo{bar"), crc16(b"foo{bar") % 16384);
+}
+
+#[test]
+fn test_node_uri() {
+    let cases = [
+        ("redis://127.0.0.1:7000", "10.0.0.2:7001", "redis://10.0.0.2:7001"),
+        ("rediss://user:p@ss@host:7000/0?protocol=resp3", "10.0.0.2:7001", "rediss://user:p@ss@10.0.0.2:7001/0?protocol=resp3"),
+        ("redis://:secret@host:7000?protocol=resp3", "10.0.0.2:7001", "redis://:secret@10.0.0.2:7001?protocol=resp3"),
+        ("host:7000", "10.0.0.2:7001", "redis://10.0.0.2:7001"),
+    ];
+    for (seed, address, expected) in cases {
+        assert_eq!(node_uri(seed, address), expected, "seed: {}", seed);
+    }
+}
+
+#[test]
+fn test_seed_uris() {
+    assert_eq!(
+        seed_uris("redis://a:7000, redis://b:7001,,"),
+        vec!["redis://a:7000".to_string(), "redis://b:7001".to_string()],
+    );
+}
+
+#[test]
+fn test_transaction_slot() {
+    assert_eq!(transaction_slot([3443, 3443]), Ok(3443));
+    assert_eq!(transaction_slot([key_slot("foo"), key_slot("bar")]), Err(FetcherError::CrossSlot));
+    // keyless commands only, e.g. `PING`, would run on an arbitrary master
+    assert_eq!(transaction_slot([]), Err(FetcherError::NoSlot));
+}
+
+#[test]
+fn test_is_keyless_error() {
+    let error = |detail: &str| RedisError::from((ErrorKind::ResponseError, "An error was signalled by the server", detail.to_string()));
+    assert!(is_keyless_error(&error("The command has no key arguments")));
+    assert!(is_keyless_error(&error("Invalid arguments specified for command")));
+    assert!(!is_keyless_error(&error("Invalid command specified")));
+    assert!(!is_keyless_error(&error("Invalid number of arguments specified for command")));
+}
@@ -0,0 +1,124 @@
+use redis::{FromRedisValue, Value};
+
+use crate::dbclient::fetcher::FetcherError;
+
+use super::{connect, RedisConfig, POLL_TIMEOUT};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CommandDoc {
+    // uppercase, subcommands as "CONFIG GET"
+    pub name: String,
+    pub summary: String,
+    // argument syntax, e.g. "key [EX seconds | PX milliseconds]"
+    pub arguments: String,
+}
+
+impl CommandDoc {
+    pub fn hint(&self) -> String {
+        let syntax = format!("{} {}", self.name, self.arguments);
+        if self.summary.is_empty() {
+            syntax.trim_end().to_string()
+        } else {
+            format!("{} - {}", syntax.trim_end(), self.summary)
+        }
+    }
+}
+
+// Commands with their argument syntax from COMMAND DOCS (Redis 7),
+// only the names from COMMAND on older servers
+pub fn fetch_command_docs(config: &RedisConfig) -> Result<Vec<CommandDoc>, FetcherError> {
+    let mut connection = connect(&config.uri, Some(POLL_TIMEOUT))?;
+    let mut docs = match redis::cmd("COMMAND").arg("DOCS").query::<Value>(&mut connection) {
+        Ok(value) => parse_docs(&value, None),
+        Err(_) => {
+            let commands: Vec<Value> = redis::cmd("COMMAND").query(&mut connection)?;
+            commands.iter()
+                .filter_map(|command| match command {
+                    Value::Array(fields) => fields.first().and_then(|name| String::from_redis_value(name).ok()),
+                    _ => None,
+                })
+                .map(|name| CommandDoc { name: name.to_uppercase(), summary: String::new(), arguments: String::new() })
+                .collect()
+        },
+    };
+    docs.sort_by(|left, right| left.name.cmp(&right.name));
+    Ok(docs)
+}
+
+// RESP2 replies maps as flat arrays of keys and values
+pub(super) fn pairs(value: &Value) -> Vec<(&Value, &Value)> {
+    match value {
+        Value::Map(entries) => entries.iter().map(|(key, value)| (key, value)).collect(),
+        Value::Array(items) => items.chunks(2).filter(|chunk| chunk.len() == 2).map(|chunk| (&chunk[0], &chunk[1])).collect(),
+        _ => vec![],
+    }
+}
+
+pub(super) fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    pairs(value).into_iter()
+        .find(|(key, _)| String::from_redis_value(key).is_ok_and(|key| key == name))
+        .map(|(_, value)| value)
+}
+
+pub(super) fn string_field(value: &Value, name: &str) -> Option<String> {
+    field(value, name).and_then(|value| String::from_redis_value(value).ok())
+}
+
+pub fn parse_docs(value: &Value, parent: Option<&str>) -> Vec<CommandDoc> {
+    let mut docs = vec![];
+    for (name, doc) in pairs(value) {
+        let name = match String::from_redis_value(name) {
+            // subcommands are named "config|get"
+            Ok(name) => name.replace('|', " ").to_uppercase(),
+            Err(_) => continue,
+        };
+        let arguments = match field(doc, "arguments") {
+            Some(Value::Array(arguments)) => arguments.iter().map(argument_syntax).collect::<Vec<String>>().join(" "),
+            _ => String::new(),
+        };
+        docs.push(CommandDoc {
+            name: match parent {
+                Some(parent) if !name.contains(' ') => format!("{} {}", parent, name),
+                _ => name.clone(),
+            },
+            summary: string_field(doc, "summary").unwrap_or_default(),
+            arguments,
+        });
+        if let Some(subcommands) = field(doc, "subcommands") {
+            docs.extend(parse_docs(subcommands, Some(&name)));
+        }
+    }
+    docs
+}
+
+pub fn argument_syntax(argument: &Value) -> String {
+    let kind = string_field(argument, "type").unwrap_or_default();
+    let token = string_field(argument, "token");
+    let flags: Vec<String> = match field(argument, "flags") {
+        Some(value) => Vec::from_redis_value(value).unwrap_or_default(),
+        None => vec![],
+    };
+    let children = || match field(argument, "arguments") {
+        Some(Value::Array(arguments)) => arguments.iter().map(argument_syntax).collect::<Vec<String>>(),
+        _ => vec![],
+    };
+    let optional = flags.iter().any(|flag| flag == "optional");
+
+    let mut syntax = match kind.as_str() {
+        "pure-token" => token.clone().unwrap_or_default(),
+        "oneof" if optional => children().join(" | "),
+        "oneof" => format!("<{}>", children().join(" | ")),
+        "block" => children().join(" "),
+        _ => string_field(argument, "name").unwrap_or_default(),
+    };
+    if let (Some(token), false) = (token, kind == "pure-token") {
+        syntax = format!("{} {}", token, syntax);
+    }
+    if flags.iter().any(|flag| flag == "multiple" || flag == "multiple_token") {
+        syntax = format!("{} [{} ...]", syntax, syntax);
+    }
+    if optional {
+        syntax = format!("[{}]", syntax);
+    }
+    syntax
+}
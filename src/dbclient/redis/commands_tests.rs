@@ -0,0 +1,86 @@
+use redis::Value;
+
+use crate::dbclient::redis::commands::{argument_syntax, parse_docs, CommandDoc};
+
+fn string(value: &str) -> Value {
+    Value::BulkString(value.as_bytes().to_vec())
+}
+
+fn map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(entries.into_iter().map(|(key, value)| (string(key), value)).collect())
+}
+
+// Argument of COMMAND DOCS, `flags` are left out when empty as the server does
+fn argument(name: &str, kind: &str, token: Option<&str>, flags: &[&str], children: Vec<Value>) -> Value {
+    let mut entries = vec![("name", string(name)), ("type", string(kind))];
+    if let Some(token) = token {
+        entries.push(("token", string(token)));
+    }
+    if !flags.is_empty() {
+        entries.push(("flags", Value::Array(flags.iter().map(|flag| Value::SimpleString(flag.to_string())).collect())));
+    }
+    if !children.is_empty() {
+        entries.push(("arguments", Value::Array(children)));
+    }
+    map(entries)
+}
+
+#[test]
+fn test_argument_syntax() {
+    let token = |name: &str| argument(&name.to_lowercase(), "pure-token", Some(name), &[], vec![]);
+    let cases = [
+        (argument("key", "key", None, &[], vec![]), "key"),
+        (argument("seconds", "integer", Some("EX"), &[], vec![]), "EX seconds"),
+        (argument("get", "pure-token", Some("GET"), &["optional"], vec![]), "[GET]"),
+        (argument("key", "key", None, &["multiple"], vec![]), "key [key ...]"),
+        (argument("condition", "oneof", None, &["optional"], vec![token("NX"), token("XX")]), "[NX | XX]"),
+        (argument("direction", "oneof", None, &[], vec![token("LEFT"), token("RIGHT")]), "<LEFT | RIGHT>"),
+        (
+            argument("data", "block", None, &["multiple"], vec![argument("field", "string", None, &[], vec![]), argument("value", "string", None, &[], vec![])]),
+            "field value [field value ...]",
+        ),
+    ];
+    for (argument, expected) in cases {
+        assert_eq!(argument_syntax(&argument), expected, "{}", expected);
+    }
+}
+
+#[test]
+fn test_parse_docs() {
+    let docs = map(vec![
+        ("set", map(vec![
+            ("summary", string("Sets the string value of a key")),
+            ("arguments", Value::Array(vec![
+                argument("key", "key", None, &[], vec![]),
+                argument("value", "string", None, &[], vec![]),
+            ])),
+        ])),
+        ("config", map(vec![
+            ("summary", string("A container for server configuration commands")),
+            ("subcommands", map(vec![
+                ("config|get", map(vec![
+                    ("summary", string("Returns the effective values of configuration parameters")),
+                    ("arguments", Value::Array(vec![argument("parameter", "string", None, &["multiple"], vec![])])),
+                ])),
+            ])),
+        ])),
+    ]);
+    let doc = |name: &str, summary: &str, arguments: &str| CommandDoc { name: name.to_string(), summary: summary.to_string(), arguments: arguments.to_string() };
+    let expected = vec![
+        doc("SET", "Sets the string value of a key", "key value"),
+        doc("CONFIG", "A container for server configuration commands", ""),
+        doc("CONFIG GET", "Returns the effective values of configuration parameters", "parameter [parameter ...]"),
+    ];
+    assert_eq!(parse_docs(&docs, None), expected);
+
+    // RESP2 sends the same maps as flat arrays
+    let flatten = |value: Value| match value {
+        Value::Map(entries) => Value::Array(entries.into_iter().flat_map(|(key, value)| [key, value]).collect()),
+        value => value,
+    };
+    let resp2 = match docs {
+        Value::Map(entries) => Value::Array(entries.into_iter().flat_map(|(key, value)| [key, flatten(value)]).collect()),
+        _ => unreachable!(),
+    };
+    assert_eq!(parse_docs(&resp2, None), expected);
+}
@@ -0,0 +1,192 @@
+use std::{collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap}, hash::{Hash, Hasher}};
+
+use redis::{Connection, FromRedisValue};
+
+use crate::dbclient::fetcher::{FetchResult, FetcherError};
+
+use super::{connect, scan_keys, RedisConfig, POLL_TIMEOUT};
+
+// Keys checked per pipeline round trip
+const BATCH_SIZE: usize = 100;
+// DUMP payload ends with a 2 byte RDB version and a 8 byte CRC64
+const DUMP_FOOTER_LEN: usize = 10;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum KeyDifference {
+    OnlyLeft,
+    OnlyRight,
+    // left type, right type
+    Type(String, String),
+    // whether left and right keys have a TTL
+    Ttl(bool, bool),
+    // left and right value digests
+    Value(String, String),
+}
+
+impl KeyDifference {
+    pub fn status(&self) -> &'static str {
+        match self {
+            KeyDifference::OnlyLeft => "only in left",
+            KeyDifference::OnlyRight => "only in right",
+            KeyDifference::Type(..) => "type differs",
+            KeyDifference::Ttl(..) => "ttl differs",
+            KeyDifference::Value(..) => "value differs",
+        }
+    }
+
+    fn details(&self) -> (String, String) {
+        let ttl = |has_ttl: &bool| if *has_ttl { "ttl" } else { "no ttl" }.to_string();
+        match self {
+            KeyDifference::OnlyLeft => ("present".to_string(), String::new()),
+            KeyDifference::OnlyRight => (String::new(), "present".to_string()),
+            KeyDifference::Type(left, right) => (left.clone(), right.clone()),
+            KeyDifference::Ttl(left, right) => (ttl(left), ttl(right)),
+            KeyDifference::Value(left, right) => (left.clone(), right.clone()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct KeyspaceDiff {
+    pub left_keys: usize,
+    pub right_keys: usize,
+    pub differences: Vec<(String, KeyDifference)>,
+}
+
+impl KeyspaceDiff {
+    pub fn count(&self, status: &str) -> usize {
+        self.differences.iter().filter(|(_, difference)| difference.status() == status).count()
+    }
+
+    pub fn summary(&self) -> String {
+        let only_left = self.count(KeyDifference::OnlyLeft.status());
+        let only_right = self.count(KeyDifference::OnlyRight.status());
+        format!(
+            "{} keys in left, {} in right: {} only in left, {} only in right, {} differ",
+            self.left_keys, self.right_keys, only_left, only_right, self.differences.len() - only_left - only_right,
+        )
+    }
+
+    pub fn to_fetch_result(&self) -> FetchResult {
+        let mut table: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, difference) in &self.differences {
+            let (left, right) = difference.details();
+            table.entry("key".to_string()).or_default().push(key.clone());
+            table.entry("status".to_string()).or_default().push(difference.status().to_string());
+            table.entry("left".to_string()).or_default().push(left);
+            table.entry("right".to_string()).or_default().push(right);
+        }
+        if table.is_empty() {
+            return FetchResult::none();
+        }
+        FetchResult { table: Some((vec!["key".to_string()], table)), reply: None }
+    }
+}
+
+struct KeyState {
+    key_type: String,
+    has_ttl: bool,
+    dump: Option<Vec<u8>>,
+}
+
+// Scans both keyspaces with `pattern` and compares the keys present on both sides
+// by type, TTL presence and value (DUMP payload first, type-specific read when the payloads differ).
+pub fn diff_keyspace(left: &RedisConfig, right: &RedisConfig, pattern: &str) -> Result<KeyspaceDiff, FetcherError> {
+    // runs on the UI thread, an unreachable server fails instead of hanging it
+    let mut left_connection = connect(&left.uri, Some(POLL_TIMEOUT))?;
+    let mut right_connection = connect(&right.uri, Some(POLL_TIMEOUT))?;
+
+    let left_keys = scan_keys(&mut left_connection, pattern)?;
+    let right_keys = scan_keys(&mut right_connection, pattern)?;
+
+    let mut differences: Vec<(String, KeyDifference)> = vec![];
+    differences.extend(left_keys.difference(&right_keys).map(|key| (key.clone(), KeyDifference::OnlyLeft)));
+    differences.extend(right_keys.difference(&left_keys).map(|key| (key.clone(), KeyDifference::OnlyRight)));
+
+    let common: Vec<&String> = left_keys.intersection(&right_keys).collect();
+    for batch in common.chunks(BATCH_SIZE) {
+        let left_states = get_key_states(&mut left_connection, batch)?;
+        let right_states = get_key_states(&mut right_connection, batch)?;
+        for ((key, left_state), right_state) in batch.iter().zip(left_states).zip(right_states) {
+            if let Some(difference) = compare_key(key, &left_state, &right_state, &mut left_connection, &mut right_connection)? {
+                differences.push(((*key).clone(), difference));
+            }
+        }
+    }
+    differences.sort_by(|left, right| left.0.cmp(&right.0));
+
+    Ok(KeyspaceDiff {
+        left_keys: left_keys.len(),
+        right_keys: right_keys.len(),
+        differences,
+    })
+}
+
+fn get_key_states(connection: &mut Connection, keys: &[&String]) -> Result<Vec<KeyState>, FetcherError> {
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.cmd("TYPE").arg(*key).cmd("PTTL").arg(*key).cmd("DUMP").arg(*key);
+    }
+    let values: Vec<redis::Value> = pipe.query(connection)?;
+    values.chunks(3).map(|state| {
+        Ok(KeyState {
+            key_type: String::from_redis_value(&state[0])?,
+            has_ttl: i64::from_redis_value(&state[1])? >= 0,
+            dump: Option::<Vec<u8>>::from_redis_value(&state[2])?,
+        })
+    }).collect()
+}
+
+fn compare_key(key: &str, left: &KeyState, right: &KeyState, left_connection: &mut Connection, right_connection: &mut Connection) -> Result<Option<KeyDifference>, FetcherError> {
+    if left.key_type != right.key_type {
+        return Ok(Some(KeyDifference::Type(left.key_type.clone(), right.key_type.clone())));
+    }
+    if left.has_ttl != right.has_ttl {
+        return Ok(Some(KeyDifference::Ttl(left.has_ttl, right.has_ttl)));
+    }
+
+    let left_payload = left.dump.as_deref().map(strip_dump_footer);
+    let right_payload = right.dump.as_deref().map(strip_dump_footer);
+    if left_payload == right_payload {
+        return Ok(None);
+    }
+
+    // Equal values can still be dumped differently (listpack vs hashtable encoding, RDB version)
+    let left_value = read_value(left_connection, key, &left.key_type)?;
+    let right_value = read_value(right_connection, key, &right.key_type)?;
+    if left_value == right_value {
+        return Ok(None);
+    }
+    Ok(Some(KeyDifference::Value(digest(&left_value), digest(&right_value))))
+}
+
+fn strip_dump_footer(dump: &[u8]) -> &[u8] {
+    &dump[..dump.len().saturating_sub(DUMP_FOOTER_LEN)]
+}
+
+// Value in a form that doesn't depend on the server side ordering
+fn read_value(connection: &mut Connection, key: &str, key_type: &str) -> Result<Vec<Vec<u8>>, FetcherError> {
+    let value = match key_type {
+        "string" => vec![redis::cmd("GET").arg(key).query::<Vec<u8>>(connection)?],
+        "list" => redis::cmd("LRANGE").arg(key).arg(0).arg(-1).query(connection)?,
+        "set" => redis::cmd("SMEMBERS").arg(key).query::<BTreeSet<Vec<u8>>>(connection)?.into_iter().collect(),
+        "zset" => redis::cmd("ZRANGE").arg(key).arg(0).arg(-1).arg("WITHSCORES").query(connection)?,
+        "hash" => redis::cmd("HGETALL").arg(key).query::<BTreeMap<Vec<u8>, Vec<u8>>>(connection)?
+            .into_iter()
+            .flat_map(|(field, value)| [field, value])
+            .collect(),
+        "stream" => {
+            let entries: redis::Value = redis::cmd("XRANGE").arg(key).arg("-").arg("+").query(connection)?;
+            vec![format!("{:?}", entries).into_bytes()]
+        },
+        // Module types can only be compared by their payload
+        _ => vec![redis::cmd("DUMP").arg(key).query::<Vec<u8>>(connection)?],
+    };
+    Ok(value)
+}
+
+fn digest(value: &[Vec<u8>]) -> String {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
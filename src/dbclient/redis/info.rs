@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use redis::Connection;
+
+use crate::dbclient::fetcher::FetcherError;
+
+// Parsed `INFO` reply, keys are unique across sections
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Info {
+    pub sections: Vec<(String, Vec<String>)>,
+    pub values: HashMap<String, String>,
+}
+
+impl Info {
+    pub fn parse(info: &str) -> Self {
+        let mut parsed = Info::default();
+        for line in info.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(section) = line.strip_prefix('#') {
+                parsed.sections.push((section.trim().to_lowercase(), vec![]));
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                if let Some((_, keys)) = parsed.sections.last_mut() {
+                    keys.push(key.to_string());
+                }
+                parsed.values.insert(key.to_string(), value.to_string());
+            }
+        }
+        parsed
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(|value| value.parse().ok())
+    }
+
+    // Lifetime keyspace hit ratio in percents
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let hits = self.get_u64("keyspace_hits")?;
+        let misses = self.get_u64("keyspace_misses")?;
+        if hits + misses == 0 {
+            return None;
+        }
+        Some(hits as f64 * 100.0 / (hits + misses) as f64)
+    }
+
+    // `db0:keys=1,expires=0,avg_ttl=0` lines of the keyspace section
+    pub fn keyspace(&self) -> Vec<(String, String)> {
+        self.sections.iter()
+            .filter(|(section, _)| section == "keyspace")
+            .flat_map(|(_, keys)| keys.iter())
+            .filter_map(|key| self.values.get(key).map(|value| (key.clone(), value.clone())))
+            .collect()
+    }
+}
+
+pub fn fetch_info(connection: &mut Connection) -> Result<Info, FetcherError> {
+    let info: String = redis::cmd("INFO").arg("all").query(connection)?;
+    Ok(Info::parse(&info))
+}
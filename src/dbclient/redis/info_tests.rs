@@ -0,0 +1,45 @@
+use crate::dbclient::redis::info::Info;
+
+const INFO: &str = "# Server\r
+redis_version:7.2.4\r
+uptime_in_seconds:3600\r
+\r
+# Stats\r
+keyspace_hits:75\r
+keyspace_misses:25\r
+\r
+# Keyspace\r
+db0:keys=10,expires=2,avg_ttl=1000\r
+db3:keys=1,expires=0,avg_ttl=0\r
+";
+
+#[test]
+fn test_parse_info() {
+    let info = Info::parse(INFO);
+    assert_eq!(
+        info.sections,
+        vec![
+            ("server".to_string(), vec!["redis_version".to_string(), "uptime_in_seconds".to_string()]),
+            ("stats".to_string(), vec!["keyspace_hits".to_string(), "keyspace_misses".to_string()]),
+            ("keyspace".to_string(), vec!["db0".to_string(), "db3".to_string()]),
+        ],
+    );
+    assert_eq!(info.get("redis_version"), Some("7.2.4"));
+    assert_eq!(info.get_u64("uptime_in_seconds"), Some(3600));
+    assert_eq!(info.get_u64("redis_version"), None);
+    assert_eq!(info.get("missing"), None);
+    assert_eq!(info.hit_ratio(), Some(75.0));
+    assert_eq!(info.keyspace(), vec![
+        ("db0".to_string(), "keys=10,expires=2,avg_ttl=1000".to_string()),
+        ("db3".to_string(), "keys=1,expires=0,avg_ttl=0".to_string()),
+    ]);
+}
+
+#[test]
+fn test_parse_info_without_lookups() {
+    let info = Info::parse("# Stats\nkeyspace_hits:0\nkeyspace_misses:0\nexecutable:/usr/bin/redis-server\n");
+    assert_eq!(info.hit_ratio(), None);
+    // values are split on the first colon only
+    assert_eq!(info.get("executable"), Some("/usr/bin/redis-server"));
+    assert!(info.keyspace().is_empty());
+}
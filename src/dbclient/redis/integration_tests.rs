@@ -1,16 +1,15 @@
-use std::{collections::HashMap, usize};
+use std::collections::{BTreeSet, HashMap};
 
-use crate::dbclient::{fetcher::{FetchRequest, FetchResult, Fetcher}, query_builder::QueryElement, redis::{RedisConfig, RedisFetcher}};
+use crate::dbclient::{fetcher::{FetchRequest, FetchResult, Fetcher, FetcherError}, query_builder::QueryElement, redis::{cluster::{discover_nodes, key_slot, RedisClusterFetcher}, diff::diff_keyspace, migrate::{copy_keys, CopyAction}, scripting::{delete_function, fetch_functions, load_function}, connect, RedisConfig, RedisFetcher}};
 
 
 #[ignore = "integration tests are disabled for automated runners"]
 #[test]
 fn test_int() {
-    let mut redis = RedisFetcher {
-        config: RedisConfig {
-            uri: String::from("redis://127.0.0.1/")
-        },
-    };
+    let mut redis = RedisFetcher::new(RedisConfig {
+        uri: String::from("redis://127.0.0.1/"),
+        read_only: false,
+    });
     let result = redis.fetch(&FetchRequest{
         query: vec![
             QueryElement::RawQuery("GET test_int".to_string()),
@@ -21,17 +20,16 @@ fn test_int() {
     let mut expected = HashMap::new();
     let index_key = "result".to_string();
     expected.insert(index_key.clone(), vec!["49".to_string()]);
-    assert_eq!(result, Ok(FetchResult{ table: Some((vec![index_key], expected)) }))
+    assert_eq!(result.map(|result| result.table), Ok(Some((vec![index_key], expected))))
 }
 
 #[ignore = "integration tests are disabled for automated runners"]
 #[test]
 fn test_string() {
-    let mut redis = RedisFetcher {
-        config: RedisConfig {
-            uri: String::from("redis://127.0.0.1/")
-        },
-    };
+    let mut redis = RedisFetcher::new(RedisConfig {
+        uri: String::from("redis://127.0.0.1/"),
+        read_only: false,
+    });
     let result = redis.fetch(&FetchRequest{
         query: vec![
             QueryElement::ListAllItemsFrom("tags".to_string()),
@@ -42,17 +40,16 @@ fn test_string() {
     let mut expected = HashMap::new();
     let index_key = "result".to_string();
     expected.insert(index_key.clone(), vec!["nosql".to_string(), "redis".to_string(), "python".to_string()]);
-    assert_eq!(result, Ok(FetchResult{ table: Some((vec![index_key], expected)) }))
+    assert_eq!(result, Ok(FetchResult{ table: Some((vec![index_key], expected)), reply: None }))
 }
 
 #[ignore = "integration tests are disabled for automated runners"]
 #[test]
 fn test_hash() {
-    let mut redis = RedisFetcher {
-        config: RedisConfig {
-            uri: String::from("redis://127.0.0.1/")
-        },
-    };
+    let mut redis = RedisFetcher::new(RedisConfig {
+        uri: String::from("redis://127.0.0.1/"),
+        read_only: false,
+    });
     let result = redis.fetch(&FetchRequest{
         query: vec![
             QueryElement::RawQuery("HGETALL user:1001".to_string())
@@ -63,5 +60,256 @@ fn test_hash() {
     let mut expected = HashMap::new();
     let index_key = "result".to_string();
     expected.insert(index_key.clone(), vec!["nosql".to_string(), "redis".to_string(), "python".to_string()]);
-    assert_eq!(result, Ok(FetchResult{ table: Some((vec![index_key], expected)) }))
+    assert_eq!(result.map(|result| result.table), Ok(Some((vec![index_key], expected))))
+}
+
+#[ignore = "integration tests are disabled for automated runners"]
+#[test]
+fn test_rename_and_delete() {
+    let mut redis = RedisFetcher::new(RedisConfig {
+        uri: String::from("redis://127.0.0.1/"),
+        read_only: false,
+    });
+    let _ = redis.fetch(&FetchRequest{
+        query: vec![
+            QueryElement::AddDatabaseObject("".to_string(), "string".to_string(), "test_rename".to_string()),
+        ],
+        limit: usize::MAX,
+    });
+    let renamed = redis.fetch(&FetchRequest{
+        query: vec![
+            QueryElement::RenameDatabaseObject("test_rename".to_string(), "test_renamed".to_string(), true),
+        ],
+        limit: usize::MAX,
+    });
+    assert_eq!(renamed, Ok(FetchResult::single(&true)));
+
+    let deleted = redis.fetch(&FetchRequest{
+        query: vec![
+            QueryElement::DeleteDatabaseObject("test_renamed".to_string(), false),
+        ],
+        limit: usize::MAX,
+    });
+    assert_eq!(deleted, Ok(FetchResult::single(&1)));
+}
+
+#[ignore = "integration tests are disabled for automated runners"]
+#[test]
+fn test_read_only() {
+    let mut redis = RedisFetcher::new(RedisConfig {
+        uri: String::from("redis://127.0.0.1/"),
+        read_only: true,
+    });
+    let rejected = redis.fetch(&FetchRequest{
+        query: vec![
+            QueryElement::RawQuery("FLUSHALL".to_string()),
+        ],
+        limit: usize::MAX,
+    });
+    assert_eq!(rejected, Err(FetcherError::ReadOnly("FLUSHALL".to_string())));
+
+    let allowed = redis.fetch(&FetchRequest{
+        query: vec![
+            QueryElement::RawQuery("GET test_int".to_string()),
+        ],
+        limit: usize::MAX,
+    });
+    assert!(allowed.is_ok());
+}
+
+#[ignore = "integration tests are disabled for automated runners"]
+#[test]
+fn test_diff_keyspace() {
+    let left = RedisConfig {
+        uri: String::from("redis://127.0.0.1/1"),
+        read_only: false,
+    };
+    let right = RedisConfig {
+        uri: String::from("redis://127.0.0.1/2"),
+        read_only: false,
+    };
+    for (config, commands) in [
+        (&left, vec!["SET diff:same 1", "SET diff:value 1", "SET diff:type 1", "SET diff:left 1"]),
+        (&right, vec!["SET diff:same 1", "SET diff:value 2", "SADD diff:type 1", "SET diff:right 1"]),
+    ] {
+        let mut redis = RedisFetcher::new(RedisConfig { uri: config.uri.clone(), read_only: false });
+        for command in commands {
+            let _ = redis.fetch(&FetchRequest{
+                query: vec![QueryElement::RawQuery(command.to_string())],
+                limit: usize::MAX,
+            });
+        }
+    }
+
+    let diff = diff_keyspace(&left, &right, "diff:*").unwrap();
+    let statuses: Vec<(&str, &str)> = diff.differences.iter().map(|(key, difference)| (key.as_str(), difference.status())).collect();
+    assert_eq!(statuses, vec![
+        ("diff:left", "only in left"),
+        ("diff:right", "only in right"),
+        ("diff:type", "type differs"),
+        ("diff:value", "value differs"),
+    ]);
+}
+
+#[ignore = "integration tests are disabled for automated runners"]
+#[test]
+fn test_copy_keys() {
+    let source = RedisConfig {
+        uri: String::from("redis://127.0.0.1/3"),
+        read_only: false,
+    };
+    let destination = RedisConfig {
+        uri: String::from("redis://127.0.0.1/4"),
+        read_only: false,
+    };
+    let mut redis = RedisFetcher::new(RedisConfig { uri: source.uri.clone(), read_only: false });
+    for command in ["SET copy:new 1", "SET copy:existing 1", "EXPIRE copy:new 100"] {
+        let _ = redis.fetch(&FetchRequest{
+            query: vec![QueryElement::RawQuery(command.to_string())],
+            limit: usize::MAX,
+        });
+    }
+    // leftovers of a previous run would turn the new key into a skipped one
+    let mut redis = RedisFetcher::new(RedisConfig { uri: destination.uri.clone(), read_only: false });
+    for command in ["DEL copy:new copy:existing", "SET copy:existing 2"] {
+        let _ = redis.fetch(&FetchRequest{
+            query: vec![QueryElement::RawQuery(command.to_string())],
+            limit: usize::MAX,
+        });
+    }
+
+    let dry_run = copy_keys(&source, &destination, "copy:*", false, true, &mut |_, _| ()).unwrap();
+    assert_eq!(dry_run.keys, vec![
+        ("copy:existing".to_string(), CopyAction::Skip),
+        ("copy:new".to_string(), CopyAction::Copy),
+    ]);
+
+    let copied = copy_keys(&source, &destination, "copy:*", true, false, &mut |_, _| ()).unwrap();
+    assert_eq!(copied.count(&CopyAction::Replace), 1);
+    assert_eq!(copied.count(&CopyAction::Copy), 1);
+}
+
+#[ignore = "integration tests are disabled for automated runners"]
+#[test]
+fn test_eval_script() {
+    let mut redis = RedisFetcher::new(RedisConfig {
+        uri: String::from("redis://127.0.0.1/"),
+        read_only: false,
+    });
+    // the first run loads the flushed script
+    for command in ["SCRIPT FLUSH", "GET test_int"] {
+        let _ = redis.fetch(&FetchRequest{
+            query: vec![QueryElement::RawQuery(command.to_string())],
+            limit: usize::MAX,
+        });
+        let result = redis.fetch(&FetchRequest{
+            query: vec![QueryElement::EvalScript(
+                "return {KEYS[1], ARGV[1]}".to_string(),
+                vec!["script:key".to_string()],
+                vec!["arg".to_string()],
+            )],
+            limit: usize::MAX,
+        });
+        assert_eq!(result.unwrap().table, FetchResult::multiple(&["script:key", "arg"]).table);
+    }
+}
+
+#[ignore = "integration tests are disabled for automated runners"]
+#[test]
+fn test_functions() {
+    let config = RedisConfig {
+        uri: String::from("redis://127.0.0.1/"),
+        read_only: false,
+    };
+    let code = "#!lua name=testlib\nredis.register_function{function_name='testfn', callback=function(keys, args) return 1 end, flags={'no-writes'}}";
+    assert_eq!(load_function(&config, code, true), Ok("testlib".to_string()));
+
+    let mut connection = connect(&config.uri, None).unwrap();
+    let libraries = fetch_functions(&mut connection).unwrap();
+    let library = libraries.iter().find(|library| library.name == "testlib").unwrap();
+    assert_eq!(library.engine, "LUA");
+    assert_eq!(library.code, code);
+    assert_eq!(library.functions[0].name, "testfn");
+    assert_eq!(library.functions[0].flags, vec!["no-writes".to_string()]);
+
+    assert_eq!(delete_function(&config, "testlib"), Ok(()));
+    assert!(fetch_functions(&mut connection).unwrap().iter().all(|library| library.name != "testlib"));
+}
+
+#[ignore = "integration tests are disabled for automated runners"]
+#[test]
+fn test_transaction() {
+    let config = || RedisConfig {
+        uri: String::from("redis://127.0.0.1/"),
+        read_only: false,
+    };
+    let mut redis = RedisFetcher::new(config());
+    redis.begin(&["tx:counter".to_string()]).unwrap();
+    let results = redis.commit(&["SET tx:counter 1".to_string(), "INCR tx:counter".to_string(), "HGET tx:counter field".to_string()]).unwrap().unwrap();
+    assert_eq!(results[0].table, FetchResult::single(&"OK").table);
+    assert_eq!(results[1].table, FetchResult::single(&2).table);
+    // the type error of one command does not stop the others
+    assert!(results[2].table.as_ref().is_some_and(|table| table.1.contains_key("error")));
+    assert_eq!(redis.commit(&[]), Err(FetcherError::NoTransaction));
+
+    // a watched key changed by another connection aborts the transaction
+    redis.begin(&["tx:counter".to_string()]).unwrap();
+    let _ = RedisFetcher::new(config()).fetch(&FetchRequest{
+        query: vec![QueryElement::RawQuery("INCR tx:counter".to_string())],
+        limit: usize::MAX,
+    });
+    assert_eq!(redis.commit(&["SET tx:counter 10".to_string()]), Ok(None));
+
+    redis.begin(&[]).unwrap();
+    assert_eq!(redis.rollback(), Ok(()));
+    assert_eq!(redis.rollback(), Err(FetcherError::NoTransaction));
+}
+
+#[ignore = "integration tests are disabled for automated runners"]
+#[test]
+fn test_cluster() {
+    let config = RedisConfig {
+        uri: String::from("redis://127.0.0.1:7000/,redis://127.0.0.1:7001/"),
+        read_only: false,
+    };
+    let nodes = discover_nodes(&config.uri).unwrap();
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(nodes.iter().flat_map(|node| node.slots.iter()).map(|(start, end)| end - start + 1).sum::<u16>(), 16384);
+
+    let mut connection = redis::Client::open("redis://127.0.0.1:7000/").unwrap().get_connection().unwrap();
+    for key in ["a", "b", "cluster:key", "{user1000}.following", "foo{}{bar}"] {
+        let slot: u16 = redis::cmd("CLUSTER").arg("KEYSLOT").arg(key).query(&mut connection).unwrap();
+        assert_eq!(key_slot(key), slot);
+    }
+
+    // keys on every master are routed by slot and listed with their owner
+    let mut redis = RedisClusterFetcher::new(config);
+    let keys = ["a", "b", "c", "d", "e", "f"];
+    for key in keys {
+        let result = redis.fetch(&FetchRequest {
+            query: vec![QueryElement::RawQuery(format!("SET {} value", key))],
+            limit: usize::MAX,
+        });
+        assert_eq!(result.unwrap().table, FetchResult::single(&"OK").table);
+    }
+    let result = redis.fetch(&FetchRequest {
+        query: vec![QueryElement::RawQuery("GET e".to_string())],
+        limit: usize::MAX,
+    });
+    assert_eq!(result.unwrap().table, FetchResult::single(&"value").table);
+
+    let objects = redis.fetch_db_objects().unwrap().table.unwrap();
+    let owners: Vec<&String> = objects.1["node"].iter().collect();
+    for key in keys {
+        let index = objects.1["result"].iter().position(|object| object == key).unwrap();
+        let node = nodes.iter().find(|node| node.owns(key_slot(key))).unwrap();
+        assert_eq!(owners[index], &format!("slot {} @ {}", key_slot(key), node.address));
+    }
+    assert_eq!(owners.iter().map(|owner| owner.split(" @ ").last()).collect::<BTreeSet<_>>().len(), 3);
+
+    redis.begin(&["{tx}:a".to_string()]).unwrap();
+    let results = redis.commit(&["SET {tx}:a 1".to_string(), "INCR {tx}:a".to_string()]).unwrap().unwrap();
+    assert_eq!(results[1].table, FetchResult::single(&2).table);
+    redis.begin(&[]).unwrap();
+    assert_eq!(redis.commit(&["SET a 1".to_string(), "SET b 1".to_string()]), Err(FetcherError::CrossSlot));
 }
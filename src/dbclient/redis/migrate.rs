@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use redis::{Connection, FromRedisValue};
+
+use crate::dbclient::fetcher::{FetchResult, FetcherError};
+
+use super::{connect, scan_keys, RedisConfig, POLL_TIMEOUT};
+
+// Keys transferred per pipeline round trip, progress is reported after each batch
+const BATCH_SIZE: usize = 100;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CopyAction {
+    Copy,
+    Replace,
+    // exists on the destination and overwriting is not allowed
+    Skip,
+    // deleted on the source during the copy
+    Missing,
+    Failed(String),
+}
+
+impl CopyAction {
+    pub fn status(&self, dry_run: bool) -> String {
+        match (self, dry_run) {
+            (CopyAction::Copy, true) => "will copy".to_string(),
+            (CopyAction::Copy, false) => "copied".to_string(),
+            (CopyAction::Replace, true) => "will replace".to_string(),
+            (CopyAction::Replace, false) => "replaced".to_string(),
+            (CopyAction::Skip, _) => "skipped, exists".to_string(),
+            (CopyAction::Missing, _) => "skipped, missing".to_string(),
+            (CopyAction::Failed(err), _) => format!("failed: {}", err),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CopySummary {
+    pub dry_run: bool,
+    pub keys: Vec<(String, CopyAction)>,
+}
+
+impl CopySummary {
+    pub fn count(&self, action: &CopyAction) -> usize {
+        self.keys.iter().filter(|(_, key_action)| key_action == action).count()
+    }
+
+    pub fn summary(&self) -> String {
+        let failed = self.keys.iter().filter(|(_, action)| matches!(action, CopyAction::Failed(_))).count();
+        let skipped = self.count(&CopyAction::Skip) + self.count(&CopyAction::Missing);
+        let (copied, replaced) = if self.dry_run { ("to copy", "to replace") } else { ("copied", "replaced") };
+        format!(
+            "{}{} keys: {} {}, {} {}, {} skipped, {} failed",
+            if self.dry_run { "Dry run, " } else { "" },
+            self.keys.len(), self.count(&CopyAction::Copy), copied, self.count(&CopyAction::Replace), replaced, skipped, failed,
+        )
+    }
+
+    pub fn to_fetch_result(&self) -> FetchResult {
+        let mut table: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, action) in &self.keys {
+            table.entry("key".to_string()).or_default().push(key.clone());
+            table.entry("status".to_string()).or_default().push(action.status(self.dry_run));
+        }
+        if table.is_empty() {
+            return FetchResult::none();
+        }
+        FetchResult { table: Some((vec!["key".to_string()], table)), reply: None }
+    }
+}
+
+// Copies the keys matching `pattern` with DUMP/RESTORE keeping their TTL.
+// `progress` is called with the number of processed keys and the total after every batch.
+pub fn copy_keys(
+    source: &RedisConfig,
+    destination: &RedisConfig,
+    pattern: &str,
+    replace: bool,
+    dry_run: bool,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<CopySummary, FetcherError> {
+    if destination.read_only && !dry_run {
+        return Err(FetcherError::ReadOnly("RESTORE".to_string()));
+    }
+    // runs on the UI thread, an unreachable server fails instead of hanging it
+    let mut source_connection = connect(&source.uri, Some(POLL_TIMEOUT))?;
+    let mut destination_connection = connect(&destination.uri, Some(POLL_TIMEOUT))?;
+
+    let keys: Vec<String> = scan_keys(&mut source_connection, pattern)?.into_iter().collect();
+    let mut summary = CopySummary { dry_run, keys: vec![] };
+    progress(0, keys.len());
+
+    for batch in keys.chunks(BATCH_SIZE) {
+        let dumps = get_dumps(&mut source_connection, batch)?;
+        let exists = get_exists(&mut destination_connection, batch)?;
+
+        for ((key, dump), exists) in batch.iter().zip(dumps).zip(exists) {
+            let action = match (dump, exists) {
+                (None, _) => CopyAction::Missing,
+                (Some(_), true) if !replace => CopyAction::Skip,
+                (Some(_), _) if dry_run => if exists { CopyAction::Replace } else { CopyAction::Copy },
+                (Some((ttl, payload)), _) => {
+                    let mut cmd = redis::cmd("RESTORE");
+                    cmd.arg(key).arg(ttl).arg(payload);
+                    if replace {
+                        cmd.arg("REPLACE");
+                    }
+                    match cmd.query::<()>(&mut destination_connection) {
+                        Ok(()) if exists => CopyAction::Replace,
+                        Ok(()) => CopyAction::Copy,
+                        Err(err) => CopyAction::Failed(err.to_string()),
+                    }
+                },
+            };
+            summary.keys.push((key.clone(), action));
+        }
+        progress(summary.keys.len(), keys.len());
+    }
+
+    Ok(summary)
+}
+
+// TTL in milliseconds (0 for no TTL, as RESTORE expects) and DUMP payload
+type Dump = (i64, Vec<u8>);
+
+// None when the key is gone
+fn get_dumps(connection: &mut Connection, keys: &[String]) -> Result<Vec<Option<Dump>>, FetcherError> {
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.cmd("PTTL").arg(key).cmd("DUMP").arg(key);
+    }
+    let values: Vec<redis::Value> = pipe.query(connection)?;
+    values.chunks(2).map(|dump| {
+        let ttl = i64::from_redis_value(&dump[0])?;
+        let payload = Option::<Vec<u8>>::from_redis_value(&dump[1])?;
+        Ok(payload.map(|payload| (ttl.max(0), payload)))
+    }).collect()
+}
+
+fn get_exists(connection: &mut Connection, keys: &[String]) -> Result<Vec<bool>, FetcherError> {
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.cmd("EXISTS").arg(key);
+    }
+    let values: Vec<i64> = pipe.query(connection)?;
+    Ok(values.into_iter().map(|count| count > 0).collect())
+}
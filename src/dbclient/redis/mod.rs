@@ -1,15 +1,75 @@
-use std::collections::HashMap;
+use std::{collections::BTreeSet, time::{Duration, Instant}};
 
-use redis::{Cmd, Commands, Connection, FromRedisValue, RedisError};
+use redis::{Cmd, Commands, Connection, ConnectionLike, FromRedisValue, RedisError};
 
-use super::{fetcher::{FetchResult, Fetcher, FetcherError}, query_builder::QueryElement};
+use super::{fetcher::{FetchRequest, FetchResult, Fetcher, FetcherError, Reply, ServerInfo}, query_builder::QueryElement};
+use read_only::{check_read_only, CommandCache};
+
+pub mod cluster;
+pub mod commands;
+pub mod diff;
+pub mod info;
+pub mod migrate;
+pub mod monitor;
+pub mod pubsub;
+pub mod read_only;
+pub mod scripting;
+pub mod slowlog;
 
 pub struct RedisConfig {
-    pub uri: String
+    pub uri: String,
+    pub read_only: bool,
 }
 
 pub struct RedisFetcher {
-    pub config: RedisConfig
+    pub config: RedisConfig,
+    // connection of the transaction in progress, WATCH only holds on the connection that sent it
+    transaction: Option<Connection>,
+    commands: CommandCache,
+}
+
+impl RedisFetcher {
+    pub fn new(config: RedisConfig) -> Self {
+        Self { config, transaction: None, commands: CommandCache::default() }
+    }
+}
+
+const SCAN_COUNT: usize = 1000;
+
+// An unreachable host fails after this long instead of hanging the UI
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// Reply timeout of the connections the UI polls (health check, dashboard, slowlog, ...)
+pub const POLL_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Waits for replies for at most `reply_timeout` when set, user queries may block longer (BLPOP)
+pub fn connect(uri: &str, reply_timeout: Option<Duration>) -> Result<Connection, FetcherError> {
+    let connection = redis::Client::open(uri)?.get_connection_with_timeout(CONNECT_TIMEOUT)?;
+    connection.set_read_timeout(reply_timeout)?;
+    connection.set_write_timeout(reply_timeout)?;
+    Ok(connection)
+}
+
+// Connection of a page that polls the server, opened with POLL_TIMEOUT on first use and
+// dropped after an error so that the next call reconnects
+pub struct PollConnection {
+    uri: String,
+    connection: Option<Connection>,
+}
+
+impl PollConnection {
+    pub fn new(uri: &str) -> Self {
+        Self { uri: uri.to_string(), connection: None }
+    }
+
+    pub fn run<T>(&mut self, query: impl FnOnce(&mut Connection) -> Result<T, FetcherError>) -> Result<T, FetcherError> {
+        let mut connection = match self.connection.take() {
+            Some(connection) => connection,
+            None => connect(&self.uri, Some(POLL_TIMEOUT))?,
+        };
+        let result = query(&mut connection)?;
+        self.connection = Some(connection);
+        Ok(result)
+    }
 }
 
 pub enum RedisType {
@@ -41,89 +101,12 @@ impl<'a> TryFrom<&'a str> for RedisType {
 
 impl Fetcher for RedisFetcher {
     fn fetch(&mut self, request: &super::fetcher::FetchRequest) -> Result<super::fetcher::FetchResult, super::fetcher::FetcherError> {
-        let client = redis::Client::open(self.config.uri.clone())?;
-        let mut connection = client.get_connection()?;
-
-        match request.query.first() {
-            Some(query) => match query {
-                QueryElement::RawQuery(query) => {
-                                let mut cmd = Cmd::new();
-                                let args: Vec<&str> = query.split(' ').collect();
-                                cmd.arg(args);
-
-                                let res = cmd.query(&mut connection)?;
-
-                                Ok(FetchResult::from_redis_value(&res)?)
-                            },
-                QueryElement::ListAllItemsFrom(index) => {
-                                let index_type = get_index_type(index, &mut connection)?;
-                                let res = match index_type {
-                                    RedisType::String => {
-                                        let res: String = connection.get(index)?;
-                                        FetchResult::single(&res)
-                                    },
-                                    RedisType::List => {
-                                        let res: Vec<String> = connection.lrange(index, 0, -1)?;
-                                        FetchResult::multiple(&res)
-                                    },
-                                    RedisType::Set => {
-                                        let res: Vec<String> = connection.smembers(index)?;
-                                        FetchResult::multiple(&res)
-                                    },
-                                    RedisType::Zset => {
-                                        let res: Vec<String> = connection.zrange(index, 0, -1)?;
-                                        FetchResult::multiple(&res)
-                                    },
-                                    RedisType::Hash => {
-                                        let res: HashMap<String, String> = connection.hgetall(index)?;
-                                        FetchResult::key_value(res)
-                                    },
-                                    RedisType::Stream => {
-                                        FetchResult::none()
-                                    },
-                                    RedisType::None => {
-                                        FetchResult::none()
-                                    },
-                                };
-                    
-                                Ok(res)
-                            },
-                QueryElement::AddDatabaseObject(_, obj_type, name) => {
-                    let index_type = obj_type.as_str().try_into()?;
-                    let res = match index_type {
-                        RedisType::String => {
-                            let res: String = connection.set(name, "initial")?;
-                            FetchResult::single(&res)
-                        },
-                        RedisType::List => {
-                            let res: String = connection.lpush(name, "initial")?;
-                            FetchResult::single(&res)
-                        },
-                        RedisType::Set => {
-                            let res: String = connection.sadd(name, "initial")?;
-                            FetchResult::single(&res)
-                        },
-                        RedisType::Zset => {
-                            let res: String = connection.zadd(name, "initial", 0)?;
-                            FetchResult::single(&res)
-                        },
-                        RedisType::Hash => {
-                            let res: String = connection.hset(name, "init_key", "init_val")?;
-                            FetchResult::single(&res)
-                        },
-                        RedisType::Stream => unimplemented!(),
-                        RedisType::None => FetchResult::none(),
-                    };
-                    Ok(res)
-                },
-            },
-            None => Err(FetcherError::InvalidQuery),
-        }
+        let mut connection = connect(&self.config.uri, None)?;
+        fetch_with(&mut connection, self.config.read_only, &mut self.commands, request)
     }
 
     fn fetch_db_objects(&mut self) -> Result<FetchResult, FetcherError> {
-        let client = redis::Client::open(self.config.uri.clone())?;
-        let mut connection = client.get_connection()?;
+        let mut connection = connect(&self.config.uri, None)?;
 
         let mut cursor = 0;
         let mut res = FetchResult::none();
@@ -146,12 +129,225 @@ impl Fetcher for RedisFetcher {
 
         Ok(res)
     }
+
+    fn server_info(&mut self) -> Result<ServerInfo, FetcherError> {
+        let mut connection = connect(&self.config.uri, Some(POLL_TIMEOUT))?;
+
+        let started = Instant::now();
+        let _: String = redis::cmd("PING").query(&mut connection)?;
+        let latency = started.elapsed();
+
+        let info: String = redis::cmd("INFO").arg("server").query(&mut connection)?;
+        let version = info.lines()
+            .find_map(|line| line.strip_prefix("redis_version:"))
+            .map(|version| version.trim().to_string());
+
+        // ACL WHOAMI is not available before Redis 6
+        let user: Option<String> = redis::cmd("ACL").arg("WHOAMI").query(&mut connection).ok();
+
+        Ok(ServerInfo { latency, version, user })
+    }
+
+    fn begin(&mut self, watch: &[String]) -> Result<(), FetcherError> {
+        let mut connection = connect(&self.config.uri, None)?;
+        if !watch.is_empty() {
+            redis::cmd("WATCH").arg(watch).query::<()>(&mut connection)?;
+        }
+        self.transaction = Some(connection);
+        Ok(())
+    }
+
+    fn commit(&mut self, queries: &[String]) -> Result<Option<Vec<FetchResult>>, FetcherError> {
+        // the connection is closed afterwards whatever happens, which also drops the watches
+        let mut connection = self.transaction.take().ok_or(FetcherError::NoTransaction)?;
+        exec_queued(&mut connection, self.config.read_only, &mut self.commands, queries)
+    }
+
+    fn rollback(&mut self) -> Result<(), FetcherError> {
+        // nothing is queued on the server before commit, closing the connection drops the watches
+        self.transaction.take().map(|_| ()).ok_or(FetcherError::NoTransaction)
+    }
 }
 
-fn get_index_type(index: &String, mut connection: &mut Connection) -> Result<RedisType, FetcherError> {
+// Runs the request on one server, or on a cluster connection that routes it by slot
+fn fetch_with<C: ConnectionLike>(connection: &mut C, read_only: bool, commands: &mut CommandCache, request: &FetchRequest) -> Result<FetchResult, FetcherError> {
+    if read_only {
+        if let Some(query) = request.query.first() {
+            check_read_only(query, connection, commands)?;
+        }
+    }
+
+    match request.query.first() {
+        Some(query) => match query {
+            QueryElement::RawQuery(query) => {
+                            let res = raw_command(query).query(connection)?;
+
+                            Ok(FetchResult::from_redis_value(&res)?)
+                        },
+            QueryElement::ListAllItemsFrom(index) => {
+                            let index_type = get_index_type(index, connection)?;
+                            let res = match index_type {
+                                RedisType::String => {
+                                    let res: String = connection.get(index)?;
+                                    FetchResult::single(&res)
+                                },
+                                RedisType::List => {
+                                    let res: Vec<String> = connection.lrange(index, 0, -1)?;
+                                    FetchResult::multiple(&res)
+                                },
+                                // typed as set and map replies so that the split view compares them regardless of order
+                                RedisType::Set => {
+                                    let res: Vec<String> = connection.smembers(index)?;
+                                    FetchResult::from_reply(Reply::List("set".to_string(), res.into_iter().map(string_reply).collect()))
+                                },
+                                RedisType::Zset => {
+                                    let res: Vec<String> = connection.zrange(index, 0, -1)?;
+                                    FetchResult::multiple(&res)
+                                },
+                                RedisType::Hash => {
+                                    let res: Vec<(String, String)> = connection.hgetall(index)?;
+                                    FetchResult::from_reply(Reply::Map(res.into_iter().map(|(field, value)| (string_reply(field), string_reply(value))).collect()))
+                                },
+                                RedisType::Stream => {
+                                    FetchResult::none()
+                                },
+                                RedisType::None => {
+                                    FetchResult::none()
+                                },
+                            };
+                
+                            Ok(res)
+                        },
+            QueryElement::AddDatabaseObject(_, obj_type, name) => {
+                let index_type = obj_type.as_str().try_into()?;
+                let res = match index_type {
+                    RedisType::String => {
+                        let res: String = connection.set(name, "initial")?;
+                        FetchResult::single(&res)
+                    },
+                    RedisType::List => {
+                        let res: String = connection.lpush(name, "initial")?;
+                        FetchResult::single(&res)
+                    },
+                    RedisType::Set => {
+                        let res: String = connection.sadd(name, "initial")?;
+                        FetchResult::single(&res)
+                    },
+                    RedisType::Zset => {
+                        let res: String = connection.zadd(name, "initial", 0)?;
+                        FetchResult::single(&res)
+                    },
+                    RedisType::Hash => {
+                        let res: String = connection.hset(name, "init_key", "init_val")?;
+                        FetchResult::single(&res)
+                    },
+                    RedisType::Stream => unimplemented!(),
+                    RedisType::None => FetchResult::none(),
+                };
+                Ok(res)
+            },
+            QueryElement::DeleteDatabaseObject(name, lazy) => {
+                let res: usize = if *lazy {
+                    connection.unlink(name)?
+                } else {
+                    connection.del(name)?
+                };
+                Ok(FetchResult::single(&res))
+            },
+            QueryElement::RenameDatabaseObject(name, new_name, overwrite) => {
+                if *overwrite {
+                    let _: () = connection.rename(name, new_name)?;
+                    Ok(FetchResult::single(&true))
+                } else {
+                    let res: bool = connection.rename_nx(name, new_name)?;
+                    Ok(FetchResult::single(&res))
+                }
+            },
+            QueryElement::CopyDatabaseObject(name, destination, database, overwrite) => {
+                let mut cmd = redis::cmd("COPY");
+                cmd.arg(name).arg(destination);
+                if let Some(database) = database {
+                    cmd.arg("DB").arg(database);
+                }
+                if *overwrite {
+                    cmd.arg("REPLACE");
+                }
+                let res: bool = cmd.query(connection)?;
+                Ok(FetchResult::single(&res))
+            },
+            QueryElement::ExpireDatabaseObject(name, ttl) => {
+                let res: bool = match ttl {
+                    Some(seconds) => connection.expire(name, *seconds as i64)?,
+                    None => connection.persist(name)?,
+                };
+                Ok(FetchResult::single(&res))
+            },
+            QueryElement::EvalScript(body, keys, args) => scripting::eval_script(connection, read_only, body, keys, args),
+        },
+        None => Err(FetcherError::InvalidQuery),
+    }
+}
+
+// MULTI, the queries and EXEC on the connection that WATCHed, None when a watched key changed
+fn exec_queued(connection: &mut Connection, read_only: bool, commands: &mut CommandCache, queries: &[String]) -> Result<Option<Vec<FetchResult>>, FetcherError> {
+    if read_only {
+        for query in queries {
+            check_read_only(&QueryElement::RawQuery(query.clone()), connection, commands)?;
+        }
+    }
+    redis::cmd("MULTI").query::<()>(connection)?;
+    for query in queries {
+        // a command rejected while queueing (unknown command, wrong arity) aborts the transaction
+        if let Err(err) = raw_command(query).query::<redis::Value>(connection) {
+            let _ = redis::cmd("DISCARD").query::<()>(connection);
+            return Err(err.into());
+        }
+    }
+    match redis::cmd("EXEC").query::<redis::Value>(connection)? {
+        redis::Value::Nil => Ok(None),
+        // a command failing while running does not roll back the others
+        redis::Value::Array(replies) => Ok(Some(replies.iter()
+            .map(|reply| FetchResult::from_redis_value(reply).unwrap_or_else(|err| FetchResult::error(&err.into())))
+            .collect())),
+        _ => Err(FetcherError::InvalidQuery),
+    }
+}
+
+// Arguments of a raw query, what is sent and what the read-only check looks at must be the same
+pub fn query_args(query: &str) -> Vec<&str> {
+    query.split_whitespace().collect()
+}
+
+fn raw_command(query: &str) -> Cmd {
+    let mut cmd = Cmd::new();
+    cmd.arg(query_args(query));
+    cmd
+}
+
+fn scan_keys(connection: &mut Connection, pattern: &str) -> Result<BTreeSet<String>, FetcherError> {
+    let mut cursor = 0;
+    let mut keys = BTreeSet::new();
+    loop {
+        let (next_cursor, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query(connection)?;
+        keys.extend(batch);
+        cursor = next_cursor;
+        if cursor == 0 {
+            break;
+        }
+    }
+    Ok(keys)
+}
+
+fn get_index_type<C: ConnectionLike>(index: &String, connection: &mut C) -> Result<RedisType, FetcherError> {
     let mut type_cmd = redis::cmd("TYPE");
     let type_cmd = type_cmd.arg(index);
-    let type_res = type_cmd.query::<FetchResult>(&mut connection)?;
+    let type_res = type_cmd.query::<FetchResult>(connection)?;
     match type_res.table {
         Some(table) => {
             match table.1.iter().last() {
@@ -168,51 +364,65 @@ fn get_index_type(index: &String, mut connection: &mut Connection) -> Result<Red
 
 impl FromRedisValue for FetchResult {
     fn from_redis_value(v: &redis::Value) -> redis::RedisResult<Self> {
-        match v {
-            redis::Value::Nil => Ok(FetchResult::none()),
-            redis::Value::Int(x) => Ok(FetchResult::single(x)),
-            redis::Value::BulkString(items) => {
-                let string_result = String::from_utf8(items.clone()).map_err(|_| RedisError::from((redis::ErrorKind::TypeError, "Invalid UTF-8 in BulkString")))?;
-                Ok(FetchResult::single(&string_result))
-            },
-            redis::Value::Array(values) => {
-                let mut res = FetchResult::none();
-                for value in values {
-                    let pre_res = FetchResult::from_redis_value(value)?;
-                    res = FetchResult::merge(&res, &pre_res);
-                }
-                Ok(res)
-            },
-            redis::Value::SimpleString(item) => Ok(FetchResult::single(item)),
-            redis::Value::Okay => Ok(FetchResult::none()),
-            redis::Value::Map(items) => {
-                let mut res = FetchResult::none();
-                for item in items {
-                    let pre_res1 = FetchResult::from_redis_value(&item.0)?;
-                    let pre_res2 = FetchResult::from_redis_value(&item.1)?;
-                    res = FetchResult::join(&res, &pre_res1);
-                    res = FetchResult::join(&res, &pre_res2);
-                }
-                Ok(res)
-            },
-            redis::Value::Attribute { data: _, attributes: _ } => unimplemented!(),
-            redis::Value::Set(values) => {
-                let mut res = FetchResult::none();
-                for value in values {
-                    let pre_res = FetchResult::from_redis_value(value)?;
-                    res = FetchResult::merge(&res, &pre_res);
-                }
-                Ok(res)
-            },
-            redis::Value::Double(x) => Ok(FetchResult::single(x)),
-            redis::Value::Boolean(x) => Ok(FetchResult::single(x)),
-            redis::Value::VerbatimString { format: _, text } => Ok(FetchResult::single(text)),
-            redis::Value::BigNumber(big_int) => Ok(FetchResult::single(big_int)),
-            redis::Value::Push { kind: _, data: _ } => unimplemented!(),
-            redis::Value::ServerError(server_error) => Err(RedisError::from(server_error.clone())),
-        }
+        Ok(FetchResult::from_reply(to_reply(v)?))
+    }
+}
+
+fn string_reply(value: String) -> Reply {
+    Reply::Scalar("string".to_string(), value)
+}
+
+fn to_reply(value: &redis::Value) -> redis::RedisResult<Reply> {
+    let scalar = |type_name: &str, value: String| Reply::Scalar(type_name.to_string(), value);
+    let list = |type_name: &str, values: &[redis::Value]| -> redis::RedisResult<Reply> {
+        Ok(Reply::List(type_name.to_string(), values.iter().map(to_reply).collect::<redis::RedisResult<_>>()?))
+    };
+    let map = |entries: &[(redis::Value, redis::Value)]| -> redis::RedisResult<Vec<(Reply, Reply)>> {
+        entries.iter().map(|(key, value)| Ok((to_reply(key)?, to_reply(value)?))).collect()
+    };
+    match value {
+        redis::Value::Nil => Ok(Reply::Nil),
+        redis::Value::Int(x) => Ok(scalar("integer", x.to_string())),
+        redis::Value::BulkString(items) => {
+            let string_result = String::from_utf8(items.clone()).map_err(|_| RedisError::from((redis::ErrorKind::TypeError, "Invalid UTF-8 in BulkString")))?;
+            Ok(scalar("string", string_result))
+        },
+        redis::Value::Array(values) => list("array", values),
+        redis::Value::SimpleString(item) => Ok(scalar("simple string", item.clone())),
+        redis::Value::Okay => Ok(scalar("simple string", "OK".to_string())),
+        redis::Value::Map(items) => Ok(Reply::Map(map(items)?)),
+        redis::Value::Attribute { data, attributes } => Ok(Reply::Attribute(Box::new(to_reply(data)?), map(attributes)?)),
+        redis::Value::Set(values) => list("set", values),
+        redis::Value::Double(x) => Ok(scalar("double", x.to_string())),
+        redis::Value::Boolean(x) => Ok(scalar("boolean", x.to_string())),
+        redis::Value::VerbatimString { format: _, text } => Ok(scalar("verbatim string", text.clone())),
+        redis::Value::BigNumber(big_int) => Ok(scalar("big number", big_int.to_string())),
+        // out-of-band message (pub/sub, client tracking invalidation)
+        redis::Value::Push { kind: _, data } => list("push", data),
+        redis::Value::ServerError(server_error) => Err(RedisError::from(server_error.clone())),
+    }
+}
+
+// Adds `protocol=resp3` to the connection URI unless it already chooses a protocol
+pub fn resp3_uri(uri: &str) -> String {
+    if uri.contains("protocol=") {
+        return uri.to_string();
     }
+    let separator = if uri.contains('?') { '&' } else { '?' };
+    format!("{}{}protocol=resp3", uri, separator)
 }
 
+#[cfg(test)]
+mod cluster_tests;
+#[cfg(test)]
+mod commands_tests;
+#[cfg(test)]
+mod info_tests;
 #[cfg(test)]
 mod integration_tests;
+#[cfg(test)]
+mod monitor_tests;
+#[cfg(test)]
+mod read_only_tests;
+#[cfg(test)]
+mod slowlog_tests;
@@ -0,0 +1,137 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, mpsc::{sync_channel, Receiver, TryRecvError}, Arc}, thread::{self, JoinHandle}, time::Duration};
+
+use redis::Value;
+
+use crate::dbclient::fetcher::FetcherError;
+
+use super::{connect, RedisConfig, POLL_TIMEOUT};
+
+// How often the reader thread checks whether it has to stop
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+// Lines not taken by the UI yet, newer lines are dropped when it is full
+const CHANNEL_SIZE: usize = 10000;
+
+// One `MONITOR` line: `1339518083.107412 [0 127.0.0.1:60866] "keys" "*"`
+#[derive(Debug, PartialEq, Clone)]
+pub struct MonitorLine {
+    pub timestamp: String,
+    pub db: String,
+    // address, `lua` or `unix:<path>`
+    pub client: String,
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl MonitorLine {
+    pub fn parse(line: &str) -> Option<Self> {
+        let (timestamp, rest) = line.split_once(' ')?;
+        let rest = rest.strip_prefix('[')?;
+        let (source, rest) = rest.split_once("] ")?;
+        let (db, client) = source.split_once(' ')?;
+        let mut words = parse_quoted(rest).into_iter();
+        Some(Self {
+            timestamp: timestamp.to_string(),
+            db: db.to_string(),
+            client: client.to_string(),
+            command: words.next()?.to_uppercase(),
+            args: words.collect(),
+        })
+    }
+}
+
+// `"a" "b\"c" "\x00"` as written by the server, unescaped
+pub fn parse_quoted(text: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        if char != '"' {
+            continue;
+        }
+        let mut word: Vec<u8> = vec![];
+        while let Some(char) = chars.next() {
+            match char {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => word.push(b'\n'),
+                    Some('r') => word.push(b'\r'),
+                    Some('t') => word.push(b'\t'),
+                    Some('a') => word.push(0x07),
+                    Some('b') => word.push(0x08),
+                    Some('x') => {
+                        let hex: String = chars.by_ref().take(2).collect();
+                        word.push(u8::from_str_radix(&hex, 16).unwrap_or(b'?'));
+                    },
+                    Some(char) => word.extend(char.to_string().as_bytes()),
+                    None => break,
+                },
+                char => word.extend(char.to_string().as_bytes()),
+            }
+        }
+        words.push(String::from_utf8_lossy(&word).to_string());
+    }
+    words
+}
+
+// `MONITOR` running on its own connection in a background thread, stopped when dropped
+pub struct Monitor {
+    receiver: Receiver<Result<MonitorLine, String>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Monitor {
+    pub fn start(config: &RedisConfig) -> Result<Self, FetcherError> {
+        let mut connection = connect(&config.uri, Some(POLL_TIMEOUT))?;
+        connection.send_packed_command(&redis::cmd("MONITOR").get_packed_command())?;
+        // +OK, or the error when MONITOR is not allowed
+        connection.recv_response()?.extract_error()?;
+        connection.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        let (sender, receiver) = sync_channel(CHANNEL_SIZE);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let handle = thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                match connection.recv_response() {
+                    Ok(Value::SimpleString(line)) => {
+                        if let Some(line) = MonitorLine::parse(&line) {
+                            let _ = sender.try_send(Ok(line));
+                        }
+                    },
+                    Ok(_) => {},
+                    Err(err) if err.is_timeout() => {},
+                    Err(err) => {
+                        let _ = sender.try_send(Err(err.to_string()));
+                        break;
+                    },
+                }
+            }
+        });
+
+        Ok(Self { receiver, stop, handle: Some(handle) })
+    }
+
+    // Lines received since the previous call, Err when the connection is lost
+    pub fn poll(&self) -> Result<Vec<MonitorLine>, String> {
+        let mut lines = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Ok(line)) => lines.push(line),
+                Ok(Err(err)) => return Err(err),
+                Err(TryRecvError::Empty) => return Ok(lines),
+                Err(TryRecvError::Disconnected) if lines.is_empty() => return Err("Monitor connection closed".to_string()),
+                Err(TryRecvError::Disconnected) => return Ok(lines),
+            }
+        }
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // the connection is closed together with the thread
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
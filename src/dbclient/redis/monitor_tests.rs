@@ -0,0 +1,44 @@
+use crate::dbclient::redis::monitor::{parse_quoted, MonitorLine};
+
+#[test]
+fn test_parse_quoted() {
+    let cases: [(&str, Vec<&str>); 8] = [
+        (r#""get" "key""#, vec!["get", "key"]),
+        (r#""set" "key" "hello world""#, vec!["set", "key", "hello world"]),
+        (r#""set" "key" "say \"hi\"""#, vec!["set", "key", "say \"hi\""]),
+        (r#""set" "path" "C:\\tmp""#, vec!["set", "path", "C:\\tmp"]),
+        (r#""set" "bin" "\x00\x7f""#, vec!["set", "bin", "\u{0}\u{7f}"]),
+        (r#""set" "utf8" "caf\xc3\xa9""#, vec!["set", "utf8", "café"]),
+        (r#""lpush" "list" "a\r\nb\tc""#, vec!["lpush", "list", "a\r\nb\tc"]),
+        (r#""set" "key" """#, vec!["set", "key", ""]),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(parse_quoted(text), expected, "{}", text);
+    }
+}
+
+#[test]
+fn test_parse_monitor_line() {
+    assert_eq!(
+        MonitorLine::parse(r#"1339518083.107412 [0 127.0.0.1:60866] "set" "greeting" "hello \"world\"""#),
+        Some(MonitorLine {
+            timestamp: "1339518083.107412".to_string(),
+            db: "0".to_string(),
+            client: "127.0.0.1:60866".to_string(),
+            command: "SET".to_string(),
+            args: vec!["greeting".to_string(), "hello \"world\"".to_string()],
+        }),
+    );
+
+    let cases = [
+        (r#"1339518087.877697 [0 lua] "get" "key""#, "lua"),
+        (r#"1339518087.877697 [3 unix:/tmp/redis.sock] "ping""#, "unix:/tmp/redis.sock"),
+    ];
+    for (line, client) in cases {
+        assert_eq!(MonitorLine::parse(line).map(|line| line.client), Some(client.to_string()), "{}", line);
+    }
+
+    for line in ["OK", "1339518083.107412 0 127.0.0.1:60866 \"get\"", "1339518083.107412 [0 127.0.0.1:60866] "] {
+        assert_eq!(MonitorLine::parse(line), None, "{}", line);
+    }
+}
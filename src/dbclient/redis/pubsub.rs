@@ -0,0 +1,160 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, mpsc::{channel, sync_channel, Receiver, Sender, TryRecvError}, Arc}, thread::{self, JoinHandle}, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use redis::Connection;
+
+use crate::dbclient::fetcher::FetcherError;
+
+use super::{connect, RedisConfig, POLL_TIMEOUT};
+
+// How often the reader thread picks up new subscriptions and checks whether it has to stop
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+// Messages not taken by the UI yet, newer messages are dropped when it is full
+const CHANNEL_SIZE: usize = 10000;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PubSubMessage {
+    // milliseconds since the epoch
+    pub received_at: u64,
+    pub channel: String,
+    // set for messages delivered through PSUBSCRIBE
+    pub pattern: Option<String>,
+    pub payload: String,
+}
+
+enum Subscription {
+    Subscribe(String),
+    PSubscribe(String),
+    UnsubscribeAll,
+}
+
+// SUBSCRIBE/PSUBSCRIBE connection running in a background thread, closed when dropped
+pub struct Subscriber {
+    receiver: Receiver<Result<PubSubMessage, String>>,
+    subscriptions: Sender<Subscription>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Subscriber {
+    pub fn start(config: &RedisConfig) -> Result<Self, FetcherError> {
+        let mut connection = connect(&config.uri, None)?;
+        let (sender, receiver) = sync_channel(CHANNEL_SIZE);
+        let (subscriptions, requests) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let handle = thread::spawn(move || {
+            let mut pubsub = connection.as_pubsub();
+            if let Err(err) = pubsub.set_read_timeout(Some(READ_TIMEOUT)) {
+                let _ = sender.try_send(Err(err.to_string()));
+                return;
+            }
+            let (mut channels, mut patterns): (Vec<String>, Vec<String>) = (vec![], vec![]);
+            while !thread_stop.load(Ordering::Relaxed) {
+                for request in requests.try_iter() {
+                    let result = match request {
+                        Subscription::Subscribe(channel) => pubsub.subscribe(&channel).map(|_| channels.push(channel)),
+                        Subscription::PSubscribe(pattern) => pubsub.psubscribe(&pattern).map(|_| patterns.push(pattern)),
+                        Subscription::UnsubscribeAll => {
+                            let mut result = Ok(());
+                            if !channels.is_empty() {
+                                result = pubsub.unsubscribe(&channels);
+                            }
+                            if result.is_ok() && !patterns.is_empty() {
+                                result = pubsub.punsubscribe(&patterns);
+                            }
+                            channels.clear();
+                            patterns.clear();
+                            result
+                        },
+                    };
+                    if let Err(err) = result {
+                        let _ = sender.try_send(Err(err.to_string()));
+                        return;
+                    }
+                }
+                if channels.is_empty() && patterns.is_empty() {
+                    // nothing to read until the first subscription
+                    thread::sleep(READ_TIMEOUT);
+                    continue;
+                }
+                match pubsub.get_message() {
+                    Ok(message) => {
+                        let _ = sender.try_send(Ok(PubSubMessage {
+                            received_at: now_millis(),
+                            channel: message.get_channel_name().to_string(),
+                            pattern: if message.from_pattern() { message.get_pattern().ok() } else { None },
+                            payload: String::from_utf8_lossy(message.get_payload_bytes()).to_string(),
+                        }));
+                    },
+                    Err(err) if err.is_timeout() => {},
+                    Err(err) => {
+                        let _ = sender.try_send(Err(err.to_string()));
+                        return;
+                    },
+                }
+            }
+        });
+
+        Ok(Self { receiver, subscriptions, stop, handle: Some(handle) })
+    }
+
+    pub fn subscribe(&self, channel: String) {
+        let _ = self.subscriptions.send(Subscription::Subscribe(channel));
+    }
+
+    pub fn psubscribe(&self, pattern: String) {
+        let _ = self.subscriptions.send(Subscription::PSubscribe(pattern));
+    }
+
+    pub fn unsubscribe_all(&self) {
+        let _ = self.subscriptions.send(Subscription::UnsubscribeAll);
+    }
+
+    // Messages received since the previous call, Err when the connection is lost
+    pub fn poll(&self) -> Result<Vec<PubSubMessage>, String> {
+        let mut messages = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Ok(message)) => messages.push(message),
+                Ok(Err(err)) => return Err(err),
+                Err(TryRecvError::Empty) => return Ok(messages),
+                Err(TryRecvError::Disconnected) if messages.is_empty() => return Err("Pub/Sub connection closed".to_string()),
+                Err(TryRecvError::Disconnected) => return Ok(messages),
+            }
+        }
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // the connection is closed together with the thread
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_millis() as u64).unwrap_or(0)
+}
+
+// Active channels matching `pattern` with their number of subscribers (PUBSUB CHANNELS + PUBSUB NUMSUB)
+pub fn fetch_channels(connection: &mut Connection, pattern: &str) -> Result<Vec<(String, u64)>, FetcherError> {
+    let mut channels: Vec<String> = redis::cmd("PUBSUB").arg("CHANNELS").arg(pattern).query(connection)?;
+    if channels.is_empty() {
+        return Ok(vec![]);
+    }
+    channels.sort();
+    Ok(redis::cmd("PUBSUB").arg("NUMSUB").arg(&channels).query(connection)?)
+}
+
+// Returns the number of clients that received the message
+pub fn publish(config: &RedisConfig, channel: &str, message: &str) -> Result<u64, FetcherError> {
+    if config.read_only {
+        return Err(FetcherError::ReadOnly("PUBLISH".to_string()));
+    }
+    let mut connection = connect(&config.uri, Some(POLL_TIMEOUT))?;
+    Ok(redis::cmd("PUBLISH").arg(channel).arg(message).query(&mut connection)?)
+}
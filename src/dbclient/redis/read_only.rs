@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use redis::{ConnectionLike, FromRedisValue};
+
+use crate::dbclient::{fetcher::FetcherError, query_builder::QueryElement};
+
+use super::query_args;
+
+// Rejected without asking the server, COMMAND INFO does not flag all of them (SCRIPT FLUSH)
+// and is not answered when COMMAND is renamed. Container commands are listed as `COMMAND SUBCOMMAND`
+const WRITE_COMMANDS: &[&str] = &[
+    "SET", "SETNX", "SETEX", "PSETEX", "MSET", "MSETNX", "APPEND", "SETRANGE", "GETSET", "GETDEL", "GETEX",
+    "INCR", "INCRBY", "INCRBYFLOAT", "DECR", "DECRBY",
+    "DEL", "UNLINK", "RENAME", "RENAMENX", "COPY", "MOVE", "MIGRATE", "RESTORE", "SWAPDB", "FLUSHDB", "FLUSHALL",
+    "EXPIRE", "EXPIREAT", "PEXPIRE", "PEXPIREAT", "PERSIST",
+    "LPUSH", "LPUSHX", "RPUSH", "RPUSHX", "LPOP", "RPOP", "BLPOP", "BRPOP", "LMPOP", "BLMPOP", "LSET", "LREM", "LTRIM", "LINSERT", "LMOVE", "BLMOVE", "RPOPLPUSH", "BRPOPLPUSH",
+    "SADD", "SREM", "SPOP", "SMOVE", "SINTERSTORE", "SUNIONSTORE", "SDIFFSTORE",
+    "ZADD", "ZREM", "ZINCRBY", "ZPOPMIN", "ZPOPMAX", "BZPOPMIN", "BZPOPMAX", "ZMPOP", "BZMPOP",
+    "ZREMRANGEBYSCORE", "ZREMRANGEBYRANK", "ZREMRANGEBYLEX", "ZUNIONSTORE", "ZINTERSTORE", "ZDIFFSTORE", "ZRANGESTORE",
+    "HSET", "HSETNX", "HMSET", "HDEL", "HINCRBY", "HINCRBYFLOAT", "HGETDEL", "HGETEX", "HSETEX", "HEXPIRE", "HPEXPIRE", "HEXPIREAT", "HPEXPIREAT", "HPERSIST",
+    "XADD", "XDEL", "XTRIM", "XGROUP", "XACK", "XCLAIM", "XAUTOCLAIM", "XREADGROUP", "XSETID",
+    "PFADD", "PFMERGE", "SETBIT", "BITOP", "BITFIELD", "GEOADD", "GEOSEARCHSTORE",
+    "EVAL", "EVALSHA", "FCALL", "PUBLISH", "SPUBLISH",
+    // admin and dangerous commands
+    "CONFIG SET", "CONFIG REWRITE", "CONFIG RESETSTAT", "SHUTDOWN", "DEBUG", "MONITOR", "SAVE", "BGSAVE", "BGREWRITEAOF",
+    "REPLICAOF", "SLAVEOF", "FAILOVER", "KEYS",
+    "SCRIPT FLUSH", "SCRIPT KILL", "SCRIPT LOAD", "FUNCTION FLUSH", "FUNCTION KILL", "FUNCTION LOAD", "FUNCTION DELETE", "FUNCTION RESTORE",
+    "CLIENT KILL", "CLIENT PAUSE", "CLIENT UNPAUSE", "CLIENT NO-EVICT", "CLIENT NO-TOUCH",
+    "ACL SETUSER", "ACL DELUSER", "ACL LOAD", "ACL SAVE", "ACL LOG",
+    "SLOWLOG RESET", "LATENCY RESET", "MEMORY PURGE", "MODULE LOAD", "MODULE LOADEX", "MODULE UNLOAD",
+];
+
+// Commands that write only when given one of these options, e.g. `SORT key STORE dest`
+const STORE_OPTIONS: &[(&str, &[&str])] = &[
+    ("SORT", &["STORE"]),
+    ("GEORADIUS", &["STORE", "STOREDIST"]),
+    ("GEORADIUSBYMEMBER", &["STORE", "STOREDIST"]),
+];
+
+// @dangerous commands that only read, the others are also flagged write or admin
+const READING_DANGEROUS_COMMANDS: &[&str] = &["info", "role", "lastsave"];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CommandInfo {
+    pub blocked: bool,
+    // CONFIG, SCRIPT, CLIENT, ... carry their flags on the subcommands (Redis 7)
+    pub container: bool,
+}
+
+// COMMAND INFO answers by command name, kept for the lifetime of the fetcher so that
+// a command costs a round trip only the first time it is used. None when the server does not know it,
+// failed requests are not kept so that the next use asks again
+#[derive(Default)]
+pub struct CommandCache(HashMap<String, Option<CommandInfo>>);
+
+impl CommandCache {
+    fn get(&mut self, name: &str, connection: &mut dyn ConnectionLike) -> Option<CommandInfo> {
+        if let Some(info) = self.0.get(name) {
+            return *info;
+        }
+        let info = redis::cmd("COMMAND").arg("INFO").arg(name).query::<redis::Value>(connection).ok()?;
+        let info = parse_command_info(name, &info);
+        self.0.insert(name.to_string(), info);
+        info
+    }
+}
+
+pub fn check_read_only(query: &QueryElement, connection: &mut dyn ConnectionLike, commands: &mut CommandCache) -> Result<(), FetcherError> {
+    match query {
+        QueryElement::ListAllItemsFrom(_) => Ok(()),
+        QueryElement::RawQuery(query) => {
+            let args = query_args(query);
+            if is_write_command(&args, connection, commands) {
+                Err(FetcherError::ReadOnly(args.first().unwrap_or(&"").to_uppercase()))
+            } else {
+                Ok(())
+            }
+        },
+        QueryElement::AddDatabaseObject(..) => Err(FetcherError::ReadOnly("SET".to_string())),
+        QueryElement::DeleteDatabaseObject(_, lazy) => Err(FetcherError::ReadOnly(if *lazy { "UNLINK" } else { "DEL" }.to_string())),
+        QueryElement::RenameDatabaseObject(..) => Err(FetcherError::ReadOnly("RENAME".to_string())),
+        QueryElement::CopyDatabaseObject(..) => Err(FetcherError::ReadOnly("COPY".to_string())),
+        QueryElement::ExpireDatabaseObject(..) => Err(FetcherError::ReadOnly("EXPIRE".to_string())),
+        // runs with EVALSHA_RO, the server refuses scripts that write
+        QueryElement::EvalScript(..) => Ok(()),
+    }
+}
+
+// Commands COMMAND INFO can't tell about (renamed COMMAND, module commands, ...) are rejected too
+fn is_write_command(args: &[&str], connection: &mut dyn ConnectionLike, commands: &mut CommandCache) -> bool {
+    let command = match args.first() {
+        Some(command) => command.to_lowercase(),
+        None => return false,
+    };
+    if is_listed_write_command(args) {
+        return true;
+    }
+    match commands.get(&command, connection) {
+        Some(CommandInfo { container: true, .. }) if args.len() > 1 => {
+            let subcommand = format!("{}|{}", command, args[1].to_lowercase());
+            commands.get(&subcommand, connection).is_none_or(|info| info.blocked)
+        },
+        Some(info) => info.blocked,
+        None => true,
+    }
+}
+
+pub fn is_listed_write_command(args: &[&str]) -> bool {
+    let command = args.first().map(|command| command.to_uppercase()).unwrap_or_default();
+    let subcommand = args.get(1).map(|subcommand| format!("{} {}", command, subcommand.to_uppercase()));
+    WRITE_COMMANDS.contains(&command.as_str())
+        || subcommand.is_some_and(|subcommand| WRITE_COMMANDS.contains(&subcommand.as_str()))
+        // SORT ... STORE writes while plain SORT only reads
+        || has_store_option(args)
+}
+
+fn has_store_option(args: &[&str]) -> bool {
+    let command = args.first().map(|command| command.to_uppercase()).unwrap_or_default();
+    STORE_OPTIONS.iter()
+        .find(|(name, _)| *name == command)
+        .is_some_and(|(_, options)| args[1..].iter().any(|arg| options.contains(&arg.to_uppercase().as_str())))
+}
+
+// Flags are the 3rd field of the COMMAND INFO entry, ACL categories the 7th and subcommands the 10th
+pub fn parse_command_info(name: &str, info: &redis::Value) -> Option<CommandInfo> {
+    let fields = match info {
+        redis::Value::Array(entries) => match entries.first() {
+            Some(redis::Value::Array(fields)) => fields,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let strings = |index: usize| -> Vec<String> {
+        match fields.get(index) {
+            Some(redis::Value::Array(values)) | Some(redis::Value::Set(values)) => values.iter()
+                .filter_map(|value| String::from_redis_value(value).ok())
+                .map(|value| value.to_lowercase())
+                .collect(),
+            _ => vec![],
+        }
+    };
+    let flags = strings(2);
+    let categories = strings(6);
+    let container = matches!(fields.get(9), Some(redis::Value::Array(subcommands)) if !subcommands.is_empty());
+
+    let writes = flags.iter().any(|flag| flag == "write" || flag == "may_replicate" || flag == "admin");
+    let dangerous = categories.iter().any(|category| category == "@dangerous") && !READING_DANGEROUS_COMMANDS.contains(&name);
+    Some(CommandInfo { blocked: writes || dangerous, container })
+}
@@ -0,0 +1,142 @@
+use redis::{ConnectionLike, RedisResult, Value};
+
+use crate::dbclient::{fetcher::FetcherError, query_builder::QueryElement, redis::read_only::{check_read_only, is_listed_write_command, parse_command_info, CommandCache, CommandInfo}};
+
+// Answers every command with `reply` and counts the round trips
+struct FakeConnection {
+    reply: Value,
+    requests: usize,
+}
+
+impl ConnectionLike for FakeConnection {
+    fn req_packed_command(&mut self, _cmd: &[u8]) -> RedisResult<Value> {
+        self.requests += 1;
+        Ok(self.reply.clone())
+    }
+
+    fn req_packed_commands(&mut self, _cmd: &[u8], _offset: usize, _count: usize) -> RedisResult<Vec<Value>> {
+        unimplemented!()
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+
+    fn check_connection(&mut self) -> bool {
+        true
+    }
+
+    fn is_open(&self) -> bool {
+        true
+    }
+}
+
+fn strings(values: &[&str]) -> Value {
+    Value::Array(values.iter().map(|value| Value::SimpleString(value.to_string())).collect())
+}
+
+// COMMAND INFO reply of a single command
+fn command_info(name: &str, flags: &[&str], categories: &[&str], subcommands: usize) -> Value {
+    Value::Array(vec![Value::Array(vec![
+        Value::BulkString(name.as_bytes().to_vec()),
+        Value::Int(-2),
+        strings(flags),
+        Value::Int(1),
+        Value::Int(1),
+        Value::Int(1),
+        strings(categories),
+        Value::Array(vec![]),
+        Value::Array(vec![]),
+        Value::Array((0..subcommands).map(|_| Value::Array(vec![])).collect()),
+    ])])
+}
+
+#[test]
+fn test_parse_command_info() {
+    let cases = [
+        ("get", command_info("get", &["readonly", "fast"], &["@read", "@string", "@fast"], 0), false, false),
+        ("set", command_info("set", &["write", "denyoom"], &["@write", "@string", "@slow"], 0), true, false),
+        ("publish", command_info("publish", &["pubsub", "loading", "stale", "fast", "may_replicate"], &["@pubsub", "@fast"], 0), true, false),
+        ("config|set", command_info("config|set", &["admin", "noscript", "loading", "stale"], &["@admin", "@slow", "@dangerous"], 0), true, false),
+        ("keys", command_info("keys", &["readonly"], &["@keyspace", "@read", "@slow", "@dangerous"], 0), true, false),
+        ("info", command_info("info", &["loading", "stale"], &["@slow", "@dangerous"], 0), false, false),
+        ("config", command_info("config", &[], &["@slow"], 4), false, true),
+    ];
+    for (name, info, blocked, container) in cases {
+        assert_eq!(parse_command_info(name, &info), Some(CommandInfo { blocked, container }), "{}", name);
+    }
+    assert_eq!(parse_command_info("nope", &Value::Array(vec![Value::Nil])), None);
+}
+
+#[test]
+fn test_listed_write_commands() {
+    let cases = [
+        ("GET key", false),
+        ("set key value", true),
+        ("MIGRATE host 6379 key 0 5000", true),
+        ("LMPOP 1 list LEFT", true),
+        ("ZMPOP 1 zset MIN", true),
+        ("BZPOPMIN zset 0", true),
+        ("GEOSEARCHSTORE dest src FROMMEMBER m BYRADIUS 1 km", true),
+        ("SORT list", false),
+        ("SORT list LIMIT 0 10 STORE dest", true),
+        ("GEORADIUS key 0 0 1 km STOREDIST dest", true),
+        ("CONFIG GET maxmemory", false),
+        ("config set maxmemory 1", true),
+        ("SHUTDOWN NOSAVE", true),
+        ("SCRIPT EXISTS sha", false),
+        ("SCRIPT FLUSH", true),
+        ("FUNCTION FLUSH", true),
+        ("DEBUG SLEEP 1", true),
+        ("CLIENT LIST", false),
+        ("CLIENT KILL ID 1", true),
+        ("ACL SETUSER alice on", true),
+    ];
+    for (query, write) in cases {
+        let args: Vec<&str> = query.split_whitespace().collect();
+        assert_eq!(is_listed_write_command(&args), write, "{}", query);
+    }
+}
+
+#[test]
+fn test_check_read_only_caches_command_info() {
+    let mut connection = FakeConnection { reply: command_info("get", &["readonly", "fast"], &["@read"], 0), requests: 0 };
+    let mut commands = CommandCache::default();
+    for key in ["a", "b", "c"] {
+        let query = QueryElement::RawQuery(format!("GET {}", key));
+        assert_eq!(check_read_only(&query, &mut connection, &mut commands), Ok(()));
+    }
+    assert_eq!(connection.requests, 1);
+
+    // listed commands are rejected without asking the server
+    let query = QueryElement::RawQuery("SCRIPT FLUSH".to_string());
+    assert_eq!(check_read_only(&query, &mut connection, &mut commands), Err(FetcherError::ReadOnly("SCRIPT".to_string())));
+    assert_eq!(connection.requests, 1);
+}
+
+#[test]
+fn test_check_read_only_uses_subcommand_flags() {
+    let mut connection = FakeConnection { reply: command_info("client", &[], &["@slow"], 2), requests: 0 };
+    let mut commands = CommandCache::default();
+    // the fake answers `client|info` with the container entry, which is not blocked
+    let query = QueryElement::RawQuery("CLIENT INFO".to_string());
+    assert_eq!(check_read_only(&query, &mut connection, &mut commands), Ok(()));
+    assert_eq!(connection.requests, 2);
+
+    connection.reply = command_info("client|unblock", &["admin", "noscript", "loading", "stale"], &["@slow"], 0);
+    let query = QueryElement::RawQuery("CLIENT UNBLOCK 42".to_string());
+    assert_eq!(check_read_only(&query, &mut connection, &mut commands), Err(FetcherError::ReadOnly("CLIENT".to_string())));
+}
+
+#[test]
+fn test_check_read_only_rejects_unknown_commands() {
+    // COMMAND INFO answers nil for commands the server does not know, e.g. a module command
+    let mut connection = FakeConnection { reply: Value::Array(vec![Value::Nil]), requests: 0 };
+    let mut commands = CommandCache::default();
+    let query = QueryElement::RawQuery("MYMODULE.DO  key".to_string());
+    assert_eq!(check_read_only(&query, &mut connection, &mut commands), Err(FetcherError::ReadOnly("MYMODULE.DO".to_string())));
+
+    connection.reply = Value::Nil;
+    let query = QueryElement::RawQuery("OTHER key".to_string());
+    assert_eq!(check_read_only(&query, &mut connection, &mut commands), Err(FetcherError::ReadOnly("OTHER".to_string())));
+}
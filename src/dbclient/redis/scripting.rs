@@ -0,0 +1,92 @@
+use redis::{Connection, ConnectionLike, ErrorKind, FromRedisValue, Value};
+
+use crate::dbclient::fetcher::{FetchResult, FetcherError};
+
+use super::{commands::{field, string_field}, connect, RedisConfig, POLL_TIMEOUT};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionLibrary {
+    pub name: String,
+    pub engine: String,
+    pub functions: Vec<Function>,
+    pub code: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Function {
+    pub name: String,
+    pub description: String,
+    // e.g. no-writes, allow-stale
+    pub flags: Vec<String>,
+}
+
+// Runs the script by its SHA1 and loads it with SCRIPT LOAD when the server does not know it yet.
+// Read-only connections use EVALSHA_RO, the server then refuses scripts that write
+pub fn eval_script<C: ConnectionLike>(connection: &mut C, read_only: bool, body: &str, keys: &[String], args: &[String]) -> Result<FetchResult, FetcherError> {
+    let sha = redis::Script::new(body).get_hash().to_string();
+    let mut evalsha = redis::cmd(if read_only { "EVALSHA_RO" } else { "EVALSHA" });
+    evalsha.arg(&sha).arg(keys.len()).arg(keys).arg(args);
+    let value = match evalsha.query::<Value>(connection) {
+        Err(err) if err.kind() == ErrorKind::NoScriptError => {
+            redis::cmd("SCRIPT").arg("LOAD").arg(body).query::<String>(connection)?;
+            evalsha.query::<Value>(connection)?
+        },
+        result => result?,
+    };
+    Ok(FetchResult::from_redis_value(&value)?)
+}
+
+// FUNCTION LIST WITHCODE (Redis 7), libraries sorted by name
+pub fn fetch_functions(connection: &mut Connection) -> Result<Vec<FunctionLibrary>, FetcherError> {
+    let libraries: Vec<Value> = redis::cmd("FUNCTION").arg("LIST").arg("WITHCODE").query(connection)?;
+    let mut libraries: Vec<FunctionLibrary> = libraries.iter()
+        .map(|library| FunctionLibrary {
+            name: string_field(library, "library_name").unwrap_or_default(),
+            engine: string_field(library, "engine").unwrap_or_default(),
+            functions: match field(library, "functions") {
+                Some(Value::Array(functions)) => functions.iter().map(parse_function).collect(),
+                _ => vec![],
+            },
+            code: string_field(library, "library_code").unwrap_or_default(),
+        })
+        .collect();
+    libraries.sort_by(|left, right| left.name.cmp(&right.name));
+    Ok(libraries)
+}
+
+fn parse_function(function: &Value) -> Function {
+    Function {
+        name: string_field(function, "name").unwrap_or_default(),
+        // nil when the function has no description
+        description: string_field(function, "description").unwrap_or_default(),
+        flags: match field(function, "flags") {
+            Some(Value::Array(flags)) | Some(Value::Set(flags)) => flags.iter()
+                .filter_map(|flag| String::from_redis_value(flag).ok())
+                .collect(),
+            _ => vec![],
+        },
+    }
+}
+
+// FUNCTION LOAD, returns the name of the loaded library
+pub fn load_function(config: &RedisConfig, code: &str, replace: bool) -> Result<String, FetcherError> {
+    if config.read_only {
+        return Err(FetcherError::ReadOnly("FUNCTION LOAD".to_string()));
+    }
+    let mut connection = connect(&config.uri, Some(POLL_TIMEOUT))?;
+    let mut cmd = redis::cmd("FUNCTION");
+    cmd.arg("LOAD");
+    if replace {
+        cmd.arg("REPLACE");
+    }
+    Ok(cmd.arg(code).query(&mut connection)?)
+}
+
+pub fn delete_function(config: &RedisConfig, library: &str) -> Result<(), FetcherError> {
+    if config.read_only {
+        return Err(FetcherError::ReadOnly("FUNCTION DELETE".to_string()));
+    }
+    let mut connection = connect(&config.uri, Some(POLL_TIMEOUT))?;
+    redis::cmd("FUNCTION").arg("DELETE").arg(library).query::<()>(&mut connection)?;
+    Ok(())
+}
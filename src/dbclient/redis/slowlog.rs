@@ -0,0 +1,93 @@
+use redis::{Connection, FromRedisValue, Value};
+
+use crate::dbclient::fetcher::FetcherError;
+
+use super::{connect, RedisConfig, POLL_TIMEOUT};
+
+// How many SLOWLOG entries are requested, the server keeps `slowlog-max-len` of them
+const SLOWLOG_COUNT: usize = 128;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SlowlogEntry {
+    pub id: u64,
+    pub timestamp: u64,
+    // microseconds
+    pub duration: u64,
+    pub command: String,
+    // address and name, empty before Redis 4
+    pub client: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct LatencyEvent {
+    pub name: String,
+    pub timestamp: u64,
+    // milliseconds
+    pub latest: u64,
+    pub max: u64,
+}
+
+pub fn fetch_slowlog(connection: &mut Connection) -> Result<Vec<SlowlogEntry>, FetcherError> {
+    let entries: Vec<Vec<Value>> = redis::cmd("SLOWLOG").arg("GET").arg(SLOWLOG_COUNT).query(connection)?;
+    entries.iter().map(|entry| {
+        let field = |index: usize| entry.get(index).ok_or(FetcherError::InvalidQuery);
+        let args: Vec<Vec<u8>> = Vec::from_redis_value(field(3)?)?;
+        let args: Vec<String> = args.iter().map(|arg| String::from_utf8_lossy(arg).to_string()).collect();
+        let address = entry.get(4).and_then(|value| String::from_redis_value(value).ok()).unwrap_or_default();
+        let name = entry.get(5).and_then(|value| String::from_redis_value(value).ok()).unwrap_or_default();
+        Ok(SlowlogEntry {
+            id: u64::from_redis_value(field(0)?)?,
+            timestamp: u64::from_redis_value(field(1)?)?,
+            duration: u64::from_redis_value(field(2)?)?,
+            command: args.join(" "),
+            client: if name.is_empty() { address } else { format!("{} ({})", address, name) },
+        })
+    }).collect()
+}
+
+pub fn fetch_latency_latest(connection: &mut Connection) -> Result<Vec<LatencyEvent>, FetcherError> {
+    let events: Vec<Vec<Value>> = redis::cmd("LATENCY").arg("LATEST").query(connection)?;
+    events.iter().map(|event| {
+        let field = |index: usize| event.get(index).ok_or(FetcherError::InvalidQuery);
+        Ok(LatencyEvent {
+            name: String::from_redis_value(field(0)?)?,
+            timestamp: u64::from_redis_value(field(1)?)?,
+            latest: u64::from_redis_value(field(2)?)?,
+            max: u64::from_redis_value(field(3)?)?,
+        })
+    }).collect()
+}
+
+// (timestamp, latency in milliseconds) samples of one event
+pub fn fetch_latency_history(connection: &mut Connection, event: &str) -> Result<Vec<(u64, u64)>, FetcherError> {
+    Ok(redis::cmd("LATENCY").arg("HISTORY").arg(event).query(connection)?)
+}
+
+pub fn reset(config: &RedisConfig) -> Result<(), FetcherError> {
+    if config.read_only {
+        return Err(FetcherError::ReadOnly("SLOWLOG RESET".to_string()));
+    }
+    let mut connection = connect(&config.uri, Some(POLL_TIMEOUT))?;
+    redis::cmd("SLOWLOG").arg("RESET").query::<()>(&mut connection)?;
+    redis::cmd("LATENCY").arg("RESET").query::<()>(&mut connection)?;
+    Ok(())
+}
+
+// Unix timestamp as `YYYY-MM-DD HH:MM:SS` UTC
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // Days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
@@ -0,0 +1,16 @@
+use crate::dbclient::redis::slowlog::format_timestamp;
+
+#[test]
+fn test_format_timestamp() {
+    let cases = [
+        (0, "1970-01-01 00:00:00"),
+        (951782400, "2000-02-29 00:00:00"),
+        (1234567890, "2009-02-13 23:31:30"),
+        (1700000000, "2023-11-14 22:13:20"),
+        (1709251199, "2024-02-29 23:59:59"),
+        (4102444799, "2099-12-31 23:59:59"),
+    ];
+    for (timestamp, expected) in cases {
+        assert_eq!(format_timestamp(timestamp), expected, "{}", timestamp);
+    }
+}
@@ -11,11 +11,13 @@ mod ui3;
 fn main() {
     let args = Cli::parse();
 
-    let config_content = std::fs::read_to_string(&args.config_path)
-        .expect("Failed to read config file");
-
-    let config: Config = toml::from_str(&config_content)
-        .expect("Failed to parse config file");
+    let config = match Config::discover(args.config_path).and_then(|path| Config::load(&path)) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        },
+    };
 
     // ui2::draw(config);
     Model::new(&config).main_loop();
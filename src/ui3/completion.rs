@@ -0,0 +1,80 @@
+use crate::dbclient::redis::commands::CommandDoc;
+
+// Candidates shown in the completion popup at most
+const MAX_CANDIDATES: usize = 8;
+
+// Command docs of the connection and key names of the DbObjects list
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Completions {
+    pub commands: Vec<CommandDoc>,
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Candidate {
+    pub text: String,
+    // summary of commands, empty for keys
+    pub detail: String,
+}
+
+impl Completions {
+    // Column of the word under completion and the candidates for it.
+    // The first word completes commands, the second one subcommands, the rest keys.
+    pub fn candidates(&self, before_cursor: &str) -> (usize, Vec<Candidate>) {
+        let words: Vec<&str> = before_cursor.split_whitespace().collect();
+        let partial = if before_cursor.ends_with(char::is_whitespace) { "" } else { words.last().copied().unwrap_or("") };
+        let start = before_cursor.chars().count() - partial.chars().count();
+        let previous = &words[..words.len() - usize::from(!partial.is_empty())];
+        let prefix = partial.to_uppercase();
+
+        let candidates: Vec<Candidate> = match previous {
+            [] => self.commands.iter()
+                .filter(|command| !command.name.contains(' ') && command.name.starts_with(&prefix))
+                .map(|command| Candidate { text: command.name.clone(), detail: command.summary.clone() })
+                .collect(),
+            [command] if self.has_subcommands(command) => {
+                let parent = format!("{} ", command.to_uppercase());
+                self.commands.iter()
+                    .filter_map(|doc| doc.name.strip_prefix(&parent).map(|name| (name, doc)))
+                    .filter(|(name, _)| name.starts_with(&prefix))
+                    .map(|(name, doc)| Candidate { text: name.to_string(), detail: doc.summary.clone() })
+                    .collect()
+            },
+            _ => self.keys.iter()
+                .filter(|key| key.starts_with(partial))
+                .map(|key| Candidate { text: key.clone(), detail: String::new() })
+                .collect(),
+        };
+        (start, candidates.into_iter().take(MAX_CANDIDATES).collect())
+    }
+
+    fn has_subcommands(&self, command: &str) -> bool {
+        let parent = format!("{} ", command.to_uppercase());
+        self.commands.iter().any(|doc| doc.name.starts_with(&parent))
+    }
+
+    // Argument syntax of the command typed on the line, subcommands preferred
+    pub fn hint(&self, line: &str) -> Option<String> {
+        let words: Vec<String> = line.split_whitespace().take(2).map(|word| word.to_uppercase()).collect();
+        let find = |name: String| self.commands.iter().find(|doc| doc.name == name);
+        let doc = match words.as_slice() {
+            [command, subcommand] => find(format!("{} {}", command, subcommand)).or_else(|| find(command.clone())),
+            [command] => find(command.clone()),
+            _ => None,
+        };
+        doc.map(CommandDoc::hint)
+    }
+}
+
+// Longest prefix shared by all candidates
+pub fn common_prefix(candidates: &[Candidate]) -> String {
+    let mut prefix: String = match candidates.first() {
+        Some(candidate) => candidate.text.clone(),
+        None => return String::new(),
+    };
+    for candidate in &candidates[1..] {
+        let len = prefix.chars().zip(candidate.text.chars()).take_while(|(left, right)| left == right).count();
+        prefix = prefix.chars().take(len).collect();
+    }
+    prefix
+}
@@ -0,0 +1,67 @@
+use crate::{dbclient::redis::commands::CommandDoc, ui3::completion::{common_prefix, Candidate, Completions}};
+
+fn completions() -> Completions {
+    let doc = |name: &str, summary: &str| CommandDoc { name: name.to_string(), summary: summary.to_string(), arguments: String::new() };
+    Completions {
+        commands: vec![
+            doc("CONFIG", "Server configuration"),
+            doc("CONFIG GET", "Returns configuration parameters"),
+            doc("CONFIG SET", "Sets configuration parameters"),
+            doc("GET", "Returns the string value of a key"),
+            doc("GETDEL", "Returns and deletes a key"),
+            doc("SET", "Sets the string value of a key"),
+        ],
+        keys: vec!["user:1".to_string(), "user:2".to_string(), "session:1".to_string()],
+    }
+}
+
+fn texts(candidates: &[Candidate]) -> Vec<&str> {
+    candidates.iter().map(|candidate| candidate.text.as_str()).collect()
+}
+
+#[test]
+fn test_candidates() {
+    let cases: [(&str, usize, Vec<&str>); 9] = [
+        ("", 0, vec!["CONFIG", "GET", "GETDEL", "SET"]),
+        ("ge", 0, vec!["GET", "GETDEL"]),
+        ("  get", 2, vec!["GET", "GETDEL"]),
+        ("config ", 7, vec!["GET", "SET"]),
+        ("CONFIG g", 7, vec!["GET"]),
+        // commands without subcommands complete keys from the second word on
+        ("get us", 4, vec!["user:1", "user:2"]),
+        ("get ", 4, vec!["user:1", "user:2", "session:1"]),
+        ("config get ", 11, vec!["user:1", "user:2", "session:1"]),
+        // keys are case sensitive
+        ("get USER", 4, vec![]),
+    ];
+    let completions = completions();
+    for (before_cursor, start, expected) in cases {
+        let (column, candidates) = completions.candidates(before_cursor);
+        assert_eq!((column, texts(&candidates)), (start, expected), "{:?}", before_cursor);
+    }
+    let (_, candidates) = completions.candidates("se");
+    assert_eq!(candidates[0].detail, "Sets the string value of a key");
+}
+
+#[test]
+fn test_hint() {
+    let completions = completions();
+    assert_eq!(completions.hint("config set maxmemory"), Some("CONFIG SET - Sets configuration parameters".to_string()));
+    assert_eq!(completions.hint("get key"), Some("GET - Returns the string value of a key".to_string()));
+    assert_eq!(completions.hint("unknown"), None);
+}
+
+#[test]
+fn test_common_prefix() {
+    let candidates = |texts: &[&str]| texts.iter().map(|text| Candidate { text: text.to_string(), detail: String::new() }).collect::<Vec<_>>();
+    let cases: [(&[&str], &str); 5] = [
+        (&[], ""),
+        (&["GET"], "GET"),
+        (&["GET", "GETDEL", "GETEX"], "GET"),
+        (&["user:1", "user:2"], "user:"),
+        (&["key", "value"], ""),
+    ];
+    for (texts, expected) in cases {
+        assert_eq!(common_prefix(&candidates(texts)), expected, "{:?}", texts);
+    }
+}
@@ -1,9 +1,11 @@
+use std::collections::{HashMap, HashSet};
+
 use ratatui::{layout::Alignment, style::Color};
-use tuirealm::{event::{Key, KeyEvent}, props::{BorderType, Borders, Table, TableBuilder, TextSpan}, Component, Event, MockComponent};
+use tuirealm::{event::{Key, KeyEvent}, props::{BorderType, Borders, PropPayload, PropValue, Table, TableBuilder, TextSpan}, AttrValue, Attribute, Component, Event, MockComponent};
 
 use crate::config::Connection;
 
-use super::{AppEvent, Msg};
+use super::{environment_color, fuzzy::fuzzy_score_fields, AppEvent, EditorType, Msg, APP_CONNECTIONS_ROWS, APP_SEARCH_PATTERN};
 
 #[derive(MockComponent)]
 pub struct ConnectionsListComponent {
@@ -28,6 +30,12 @@ impl Default for ConnectionsListComponent {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionsRow {
+    Group(String),
+    Connection(usize),
+}
+
 impl Component<Msg, AppEvent> for ConnectionsListComponent {
     fn on(&mut self, ev: tuirealm::Event<AppEvent>) -> Option<Msg> {
         match ev {
@@ -40,9 +48,27 @@ impl Component<Msg, AppEvent> for ConnectionsListComponent {
                 self.component.states.decr_list_index(true);
                 Some(Msg::None)
             },
-            Event::Keyboard(KeyEvent { code: Key::Enter, .. }) => {
-                let selected_connection = self.component.states.list_index;
-                Some(Msg::ToQueryPage(selected_connection))
+            Event::Keyboard(KeyEvent { code: Key::Char('/'), .. }) => {
+                let filter = match self.query(Attribute::Custom(APP_SEARCH_PATTERN)) {
+                    Some(AttrValue::String(filter)) => filter,
+                    _ => String::new(),
+                };
+                Some(Msg::ActivateEditor(EditorType::FuzzyFind(filter)))
+            },
+            Event::Keyboard(KeyEvent { code: Key::Enter | Key::Char(' '), .. }) => match self.get_selected_row() {
+                Some(ConnectionsRow::Connection(selected_connection)) => Some(Msg::ToQueryPage(selected_connection)),
+                Some(ConnectionsRow::Group(group)) => Some(Msg::ToggleConnectionsGroup(group)),
+                None => Some(Msg::None),
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('a'), .. }) => Some(Msg::AddConnection),
+            Event::Keyboard(KeyEvent { code: Key::Char(key @ ('t' | 'e' | 'c' | 'd')), .. }) => match self.get_selected_row() {
+                Some(ConnectionsRow::Connection(selected_connection)) => match key {
+                    't' => Some(Msg::TestConnection(selected_connection)),
+                    'e' => Some(Msg::EditConnection(selected_connection)),
+                    'c' => Some(Msg::DuplicateConnection(selected_connection)),
+                    _ => Some(Msg::DeleteConnection(selected_connection)),
+                },
+                _ => Some(Msg::None),
             },
             _ => Some(Msg::None),
         }
@@ -50,20 +76,99 @@ impl Component<Msg, AppEvent> for ConnectionsListComponent {
 }
 
 impl ConnectionsListComponent {
-    pub fn build_connections_table(connections: &Vec<Connection>) -> Table {
-        if connections.is_empty() {
+    // Connections matching the filter sorted by score, or all connections grouped when there is no filter
+    pub fn build_connections_rows(connections: &[Connection], filter: &str, collapsed_groups: &HashSet<String>) -> Vec<ConnectionsRow> {
+        if !filter.trim().is_empty() {
+            let mut scored: Vec<(i64, usize)> = connections.iter().enumerate().filter_map(|(index, conn)| {
+                let connection_type = conn.connection_type.to_string();
+                let mut fields = vec![conn.name.as_str(), connection_type.as_str(), conn.group.as_deref().unwrap_or(""), conn.host()];
+                fields.extend(conn.tags.iter().map(|tag| tag.as_str()));
+                fuzzy_score_fields(filter, &fields).map(|score| (score, index))
+            }).collect();
+            scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            return scored.into_iter().map(|(_, index)| ConnectionsRow::Connection(index)).collect();
+        }
+
+        let mut rows: Vec<ConnectionsRow> = connections.iter().enumerate()
+            .filter(|(_, conn)| conn.group.is_none())
+            .map(|(index, _)| ConnectionsRow::Connection(index))
+            .collect();
+
+        let mut groups: Vec<&String> = vec![];
+        for group in connections.iter().filter_map(|conn| conn.group.as_ref()) {
+            if !groups.contains(&group) {
+                groups.push(group);
+            }
+        }
+        for group in groups {
+            rows.push(ConnectionsRow::Group(group.clone()));
+            if !collapsed_groups.contains(group) {
+                rows.extend(connections.iter().enumerate()
+                    .filter(|(_, conn)| conn.group.as_ref() == Some(group))
+                    .map(|(index, _)| ConnectionsRow::Connection(index)));
+            }
+        }
+        rows
+    }
+
+    pub fn build_connections_rows_payload(rows: &[ConnectionsRow]) -> AttrValue {
+        AttrValue::Payload(PropPayload::Vec(rows.iter().map(|row| match row {
+            ConnectionsRow::Group(group) => PropValue::Str(group.clone()),
+            ConnectionsRow::Connection(index) => PropValue::Usize(*index),
+        }).collect()))
+    }
+
+    pub fn build_connections_table(connections: &[Connection], rows: &[ConnectionsRow], collapsed_groups: &HashSet<String>, health: &HashMap<String, bool>) -> Table {
+        if rows.is_empty() {
             return vec![];
         }
         let mut table = TableBuilder::default();
-        connections.iter().enumerate().for_each(|(index, conn)| {
-            let row = table
-                .add_col(TextSpan::from(conn.name.clone()).fg(Color::Blue))
-                .add_col(TextSpan::from(conn.connection_type.to_string()));
-            if index < connections.len() - 1 {
-                row.add_row();
+        rows.iter().enumerate().for_each(|(index, row)| {
+            let row_builder = match row {
+                ConnectionsRow::Group(group) => {
+                    let count = connections.iter().filter(|conn| conn.group.as_ref() == Some(group)).count();
+                    table
+                        .add_col(TextSpan::from(if collapsed_groups.contains(group) { "▸" } else { "▾" }).fg(Color::Yellow))
+                        .add_col(TextSpan::from(format!("{} ({})", group, count)).fg(Color::Yellow).bold())
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                },
+                ConnectionsRow::Connection(connection_index) => {
+                    let conn = &connections[*connection_index];
+                    let name = if conn.group.is_some() { format!("  {}", conn.name) } else { conn.name.clone() };
+                    table
+                        .add_col(match health.get(&conn.name) {
+                            Some(true) => TextSpan::from("●").fg(Color::Green),
+                            Some(false) => TextSpan::from("●").fg(Color::Red),
+                            None => TextSpan::from("○").fg(Color::Gray),
+                        })
+                        .add_col(TextSpan::from(name).fg(Color::Blue))
+                        .add_col(TextSpan::from(conn.connection_type.to_string()))
+                        .add_col(if conn.read_only { TextSpan::from("🔒").fg(Color::Red) } else { TextSpan::from("") })
+                        .add_col(match &conn.environment {
+                            Some(environment) => TextSpan::from(environment.to_string()).fg(environment_color(&conn.environment)),
+                            None => TextSpan::from(""),
+                        })
+                        .add_col(TextSpan::from(conn.tags.join(", ")).fg(Color::Gray))
+                },
+            };
+            if index < rows.len() - 1 {
+                row_builder.add_row();
             }
         });
         table.build()
     }
-}
 
+    fn get_selected_row(&self) -> Option<ConnectionsRow> {
+        match self.query(Attribute::Custom(APP_CONNECTIONS_ROWS)) {
+            Some(AttrValue::Payload(PropPayload::Vec(rows))) => match rows.get(self.component.states.list_index) {
+                Some(PropValue::Usize(index)) => Some(ConnectionsRow::Connection(*index)),
+                Some(PropValue::Str(group)) => Some(ConnectionsRow::Group(group.clone())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
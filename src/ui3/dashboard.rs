@@ -0,0 +1,231 @@
+use std::{collections::VecDeque, time::{Duration, Instant}};
+
+use ratatui::{layout::{Constraint, Direction, Layout, Rect}, style::{Color, Style}, text::Line, widgets::{Block, Borders, Paragraph, Sparkline}, Frame};
+use tuirealm::{command::{Cmd, CmdResult}, event::{Key, KeyEvent}, props::{BorderType, Props}, AttrValue, Attribute, Component, Event, MockComponent, State};
+
+use crate::dbclient::redis::{info::{fetch_info, Info}, PollConnection, RedisConfig};
+
+use super::{AppEvent, Msg};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+const SAMPLES: usize = 60;
+
+#[derive(Default)]
+struct Samples {
+    memory: VecDeque<u64>,
+    clients: VecDeque<u64>,
+    ops: VecDeque<u64>,
+    hit_ratio: VecDeque<u64>,
+}
+
+impl Samples {
+    fn push(samples: &mut VecDeque<u64>, value: u64) {
+        samples.push_back(value);
+        if samples.len() > SAMPLES {
+            samples.pop_front();
+        }
+    }
+}
+
+// Server health page built from `INFO`, refreshed on the application tick
+pub struct Dashboard {
+    props: Props,
+    connection: PollConnection,
+    connection_name: String,
+    info: Result<Info, String>,
+    samples: Samples,
+    refreshed_at: Option<Instant>,
+}
+
+impl Dashboard {
+    pub fn new(config: RedisConfig, connection_name: String) -> Self {
+        let mut dashboard = Self {
+            props: Props::default(),
+            connection: PollConnection::new(&config.uri),
+            connection_name,
+            info: Err("Loading...".to_string()),
+            samples: Samples::default(),
+            refreshed_at: None,
+        };
+        dashboard.refresh();
+        dashboard
+    }
+
+    fn refresh(&mut self) {
+        self.refreshed_at = Some(Instant::now());
+        let info = match self.connection.run(fetch_info) {
+            Ok(info) => info,
+            Err(err) => {
+                self.info = Err(err.to_string());
+                return;
+            },
+        };
+
+        Samples::push(&mut self.samples.memory, info.get_u64("used_memory").unwrap_or(0));
+        Samples::push(&mut self.samples.clients, info.get_u64("connected_clients").unwrap_or(0));
+        Samples::push(&mut self.samples.ops, info.get_u64("instantaneous_ops_per_sec").unwrap_or(0));
+
+        // Hit ratio of the lookups made since the previous sample
+        let lookups = |info: &Info| (info.get_u64("keyspace_hits").unwrap_or(0), info.get_u64("keyspace_misses").unwrap_or(0));
+        if let Ok(previous) = &self.info {
+            let (hits, misses) = lookups(&info);
+            let (previous_hits, previous_misses) = lookups(previous);
+            let hits = hits.saturating_sub(previous_hits);
+            let total = hits + misses.saturating_sub(previous_misses);
+            Samples::push(&mut self.samples.hit_ratio, (hits * 100).checked_div(total).unwrap_or(100));
+        }
+
+        self.info = Ok(info);
+    }
+
+    fn panel<'a>(title: &'a str) -> Block<'a> {
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Yellow))
+    }
+
+    fn render_panel(frame: &mut Frame, area: Rect, title: &str, lines: Vec<Line>, samples: Option<&VecDeque<u64>>) {
+        let block = Self::panel(title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(if samples.is_some() { 3 } else { 0 })])
+            .split(inner);
+        frame.render_widget(Paragraph::new(lines), chunks[0]);
+        if let Some(samples) = samples {
+            let samples: Vec<u64> = samples.iter().copied().collect();
+            frame.render_widget(Sparkline::default().data(samples.as_slice()).style(Style::default().fg(Color::Cyan)), chunks[1]);
+        }
+    }
+
+    fn line(info: &Info, label: &str, key: &str) -> Line<'static> {
+        Line::from(format!("{}: {}", label, info.get(key).unwrap_or("-")))
+    }
+}
+
+impl MockComponent for Dashboard {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        let info = match &self.info {
+            Ok(info) => info,
+            Err(err) => {
+                let block = Self::panel(&self.connection_name);
+                frame.render_widget(Paragraph::new(err.as_str()).style(Style::default().fg(Color::Red)).block(block), area);
+                return;
+            },
+        };
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Fill(1), Constraint::Fill(1)])
+            .split(area);
+        let header = format!(
+            " {} | redis {} | {} mode | up {} days",
+            self.connection_name,
+            info.get("redis_version").unwrap_or("-"),
+            info.get("redis_mode").unwrap_or("-"),
+            info.get("uptime_in_days").unwrap_or("-"),
+        );
+        frame.render_widget(Paragraph::new(header).style(Style::default().fg(Color::Yellow)), rows[0]);
+
+        let top = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Fill(1), Constraint::Fill(1), Constraint::Fill(1)])
+            .split(rows[1]);
+        let bottom = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Fill(1), Constraint::Fill(1), Constraint::Fill(1)])
+            .split(rows[2]);
+
+        Self::render_panel(frame, top[0], "Memory", vec![
+            Self::line(info, "used", "used_memory_human"),
+            Self::line(info, "peak", "used_memory_peak_human"),
+            Self::line(info, "rss", "used_memory_rss_human"),
+            Self::line(info, "fragmentation", "mem_fragmentation_ratio"),
+            Self::line(info, "maxmemory", "maxmemory_human"),
+            Self::line(info, "policy", "maxmemory_policy"),
+        ], Some(&self.samples.memory));
+
+        Self::render_panel(frame, top[1], "Clients", vec![
+            Self::line(info, "connected", "connected_clients"),
+            Self::line(info, "blocked", "blocked_clients"),
+            Self::line(info, "max", "maxclients"),
+            Self::line(info, "rejected", "rejected_connections"),
+        ], Some(&self.samples.clients));
+
+        Self::render_panel(frame, top[2], "Ops/sec", vec![
+            Self::line(info, "ops/sec", "instantaneous_ops_per_sec"),
+            Self::line(info, "commands", "total_commands_processed"),
+            Self::line(info, "input kbps", "instantaneous_input_kbps"),
+            Self::line(info, "output kbps", "instantaneous_output_kbps"),
+        ], Some(&self.samples.ops));
+
+        let mut keyspace = vec![Line::from(format!(
+            "hit ratio: {}",
+            info.hit_ratio().map(|ratio| format!("{:.2}%", ratio)).unwrap_or_else(|| "-".to_string()),
+        ))];
+        keyspace.push(Self::line(info, "expired", "expired_keys"));
+        keyspace.push(Self::line(info, "evicted", "evicted_keys"));
+        keyspace.extend(info.keyspace().into_iter().map(|(db, value)| Line::from(format!("{}: {}", db, value))));
+        Self::render_panel(frame, bottom[0], "Keyspace", keyspace, Some(&self.samples.hit_ratio));
+
+        let mut replication = vec![
+            Self::line(info, "role", "role"),
+            Self::line(info, "replicas", "connected_slaves"),
+            Self::line(info, "offset", "master_repl_offset"),
+        ];
+        if info.get("role") == Some("slave") {
+            replication.push(Line::from(format!("master: {}:{}", info.get("master_host").unwrap_or("-"), info.get("master_port").unwrap_or("-"))));
+            replication.push(Self::line(info, "link", "master_link_status"));
+        }
+        Self::render_panel(frame, bottom[1], "Replication", replication, None);
+
+        Self::render_panel(frame, bottom[2], "Persistence", vec![
+            Self::line(info, "loading", "loading"),
+            Self::line(info, "rdb changes", "rdb_changes_since_last_save"),
+            Self::line(info, "rdb last save", "rdb_last_save_time"),
+            Self::line(info, "rdb last bgsave", "rdb_last_bgsave_status"),
+            Self::line(info, "aof enabled", "aof_enabled"),
+            Self::line(info, "aof last write", "aof_last_write_status"),
+        ], None);
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.props.get(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        self.props.set(attr, value);
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, AppEvent> for Dashboard {
+    fn on(&mut self, ev: Event<AppEvent>) -> Option<Msg> {
+        match ev {
+            Event::Tick => {
+                if self.refreshed_at.is_none_or(|refreshed_at| refreshed_at.elapsed() >= REFRESH_INTERVAL) {
+                    self.refresh();
+                    return Some(Msg::None);
+                }
+                None
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('r'), .. }) => {
+                self.refresh();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Esc | Key::Char('q'), .. }) => Some(Msg::CloseDashboard),
+            _ => None,
+        }
+    }
+}
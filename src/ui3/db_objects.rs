@@ -7,6 +7,7 @@ use super::{AppEvent, EditorType, Msg, APP_SEARCH_PATTERN};
 #[derive(MockComponent)]
 pub struct DbObjects {
     component: tui_realm_stdlib::List,
+    pending_g: bool,
 }
 
 impl Default for DbObjects {
@@ -24,59 +25,98 @@ impl Default for DbObjects {
             );
 
         Self {
-            component: list
+            component: list,
+            pending_g: false,
         }
     }
 }
 
 impl Component<Msg, AppEvent> for DbObjects {
     fn on(&mut self, ev: tuirealm::Event<AppEvent>) -> Option<Msg> {
+        let pending_g = std::mem::take(&mut self.pending_g);
         match ev {
+            Event::Keyboard(KeyEvent { code: Key::Char('g'), .. }) if !pending_g => {
+                self.pending_g = true;
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('g'), .. }) => {
+                self.component.states.list_index_at_first();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('t'), .. }) if pending_g => Some(Msg::NextSession),
+            Event::Keyboard(KeyEvent { code: Key::Char('T'), .. }) if pending_g => Some(Msg::PrevSession),
+            Event::Keyboard(KeyEvent { code: Key::Char(number @ '1'..='9'), .. }) => Some(Msg::ToSession(number as usize - '1' as usize)),
+            Event::Keyboard(KeyEvent { code: Key::Char('x'), .. }) => Some(Msg::CloseSession),
+            Event::Keyboard(KeyEvent { code: Key::Char('I'), .. }) => Some(Msg::OpenDashboard),
+            Event::Keyboard(KeyEvent { code: Key::Char('O'), .. }) => Some(Msg::OpenSlowlog),
+            Event::Keyboard(KeyEvent { code: Key::Char('W'), .. }) => Some(Msg::OpenMonitor),
+            Event::Keyboard(KeyEvent { code: Key::Char('P'), .. }) => Some(Msg::OpenPubSub),
+            Event::Keyboard(KeyEvent { code: Key::Char('F'), .. }) => Some(Msg::OpenFunctions),
+            Event::Keyboard(KeyEvent { code: Key::Char('E'), .. }) => Some(Msg::ActivateEditor(EditorType::Script)),
+            Event::Keyboard(KeyEvent { code: Key::Char('B'), .. }) => Some(Msg::ActivateEditor(EditorType::BeginTransaction)),
+            Event::Keyboard(KeyEvent { code: Key::Char('X'), .. }) => Some(Msg::ExecTransaction),
+            Event::Keyboard(KeyEvent { code: Key::Char('R'), .. }) => Some(Msg::DiscardTransaction),
             Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => Some(Msg::ToConnectionsPage),
             Event::Keyboard(KeyEvent { code: Key::Char('r'), .. }) => Some(Msg::FetchDbObjects),
             Event::Keyboard(KeyEvent { code: Key::Char('a'), ..}) => Some(Msg::ActivateEditor(EditorType::AddDbObject)),
+            Event::Keyboard(KeyEvent { code: Key::Char('d'), .. }) => self.get_current_object()
+                .map(|object| Msg::ActivateEditor(EditorType::DeleteDbObject(object, false)))
+                .or(Some(Msg::None)),
+            Event::Keyboard(KeyEvent { code: Key::Char('D'), .. }) => self.get_current_object()
+                .map(|object| Msg::ActivateEditor(EditorType::DeleteDbObject(object, true)))
+                .or(Some(Msg::None)),
+            Event::Keyboard(KeyEvent { code: Key::Char('m'), .. }) => self.get_current_object()
+                .map(|object| Msg::ActivateEditor(EditorType::RenameDbObject(object)))
+                .or(Some(Msg::None)),
+            Event::Keyboard(KeyEvent { code: Key::Char('c'), .. }) => self.get_current_object()
+                .map(|object| Msg::ActivateEditor(EditorType::CopyDbObject(object)))
+                .or(Some(Msg::None)),
+            Event::Keyboard(KeyEvent { code: Key::Char('M'), .. }) => {
+                let pattern = self.get_current_object().map(|object| Self::escape_pattern(&object)).unwrap_or_else(|| "*".to_string());
+                Some(Msg::ActivateEditor(EditorType::CopyKeys(pattern)))
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('e'), .. }) => self.get_current_object()
+                .map(|object| Msg::ActivateEditor(EditorType::ExpireDbObject(object)))
+                .or(Some(Msg::None)),
+            Event::Keyboard(KeyEvent { code: Key::Char('p'), .. }) => self.get_current_object()
+                .map(|object| Msg::ExpireDbObject(object, None))
+                .or(Some(Msg::None)),
             Event::Keyboard(KeyEvent { code: Key::Char('/'), .. }) => Some(Msg::ActivateEditor(EditorType::Search)),
             Event::Keyboard(KeyEvent { code: Key::Char('n'), .. }) => {
                 let attr_val = self.query(Attribute::Custom(APP_SEARCH_PATTERN));
                 match attr_val {
-                    Some(val) => match val {
-                        AttrValue::String(pattern) => {
-                            let current_list = self.get_current_list();
-                            let start_index = self.component.states.list_index;
-                            self.component.states.incr_list_index(true);
-                            while self.component.states.list_index != start_index {
-                                if current_list.get(self.component.states.list_index).unwrap().contains(&pattern) {
-                                    break
-                                }
-                                self.component.states.incr_list_index(true);
+                    Some(AttrValue::String(pattern)) => {
+                        let current_list = self.get_current_list();
+                        let start_index = self.component.states.list_index;
+                        self.component.states.incr_list_index(true);
+                        while self.component.states.list_index != start_index {
+                            if current_list.get(self.component.states.list_index).unwrap().contains(&pattern) {
+                                break
                             }
-                            return None;
-                        },
-                        _ => return Some(Msg::None)
+                            self.component.states.incr_list_index(true);
+                        }
+                        None
                     },
-                    None => return Some(Msg::None),
-                };
+                    _ => Some(Msg::None),
+                }
             },
             Event::Keyboard(KeyEvent { code: Key::Char('N'), .. }) => {
                 let attr_val = self.query(Attribute::Custom(APP_SEARCH_PATTERN));
                 match attr_val {
-                    Some(val) => match val {
-                        AttrValue::String(pattern) => {
-                            let current_list = self.get_current_list();
-                            let start_index = self.component.states.list_index;
-                            self.component.states.decr_list_index(true);
-                            while self.component.states.list_index != start_index {
-                                if current_list.get(self.component.states.list_index).unwrap().contains(&pattern) {
-                                    break
-                                }
-                                self.component.states.decr_list_index(true);
+                    Some(AttrValue::String(pattern)) => {
+                        let current_list = self.get_current_list();
+                        let start_index = self.component.states.list_index;
+                        self.component.states.decr_list_index(true);
+                        while self.component.states.list_index != start_index {
+                            if current_list.get(self.component.states.list_index).unwrap().contains(&pattern) {
+                                break
                             }
-                            return None;
-                        },
-                        _ => return Some(Msg::None)
+                            self.component.states.decr_list_index(true);
+                        }
+                        None
                     },
-                    None => return Some(Msg::None),
-                };
+                    _ => Some(Msg::None),
+                }
             },
             Event::Keyboard(KeyEvent { code: Key::Char('j') | Key::Down, .. }) => {
                 self.component.states.incr_list_index(true);
@@ -87,25 +127,17 @@ impl Component<Msg, AppEvent> for DbObjects {
                 Some(Msg::None)
             },
             Event::Keyboard(KeyEvent { code: Key::Char('L') | Key::Right, ..}) => Some(Msg::ToQueryResultWidget),
-            Event::Keyboard(KeyEvent { code: Key::Enter, .. }) => {
-                match self.component.query(Attribute::Content) {
-                    Some(val) => match val {
-                        AttrValue::Table(list) => {
-                            let current_object = list.get(self.component.states.list_index).unwrap().get(0).unwrap();
-                            Some(Msg::FetchDbObject(current_object.content.clone()))
-                        },
-                        _ => Some(Msg::None)
-                    },
-                    None => Some(Msg::None),
-                }
-            },
+            Event::Keyboard(KeyEvent { code: Key::Enter, .. }) => self.get_current_object()
+                .map(Msg::FetchDbObject)
+                .or(Some(Msg::None)),
             _ => Some(Msg::None)
         }
     }
 }
 
 impl DbObjects {
-    pub fn build_objects_list(connections: &Vec<String>) -> Table {
+    // `owners` is the slot and node of every key on a cluster, the key stays the first column
+    pub fn build_objects_list(connections: &[String], owners: Option<&Vec<String>>) -> Table {
         if connections.is_empty() {
             return vec![];
         }
@@ -113,6 +145,9 @@ impl DbObjects {
         connections.iter().enumerate().for_each(|(index, obj)| {
             let row = table
                 .add_col(TextSpan::from(obj).fg(Color::Blue));
+            if let Some(owner) = owners.and_then(|owners| owners.get(index)) {
+                row.add_col(TextSpan::from(format!("  {}", owner)).fg(Color::Gray));
+            }
             if index < connections.len() - 1 {
                 row.add_row();
             }
@@ -120,16 +155,25 @@ impl DbObjects {
         table.build()
     }
 
+    // Makes a key usable as a MATCH pattern matching only itself
+    fn escape_pattern(key: &str) -> String {
+        key.chars().fold(String::new(), |mut pattern, ch| {
+            if matches!(ch, '*' | '?' | '[' | ']' | '\\') {
+                pattern.push('\\');
+            }
+            pattern.push(ch);
+            pattern
+        })
+    }
+
+    pub fn get_current_object(&self) -> Option<String> {
+        self.get_current_list().get(self.component.states.list_index).cloned()
+    }
+
     pub fn get_current_list(&self) -> Vec<String> {
         match self.component.query(Attribute::Content) {
-            Some(val) => match val {
-                AttrValue::Table(list) => {
-                    let result = list.iter().map(|row| row.first().unwrap().content.clone()).collect();
-                    result
-                },
-                _ => vec![]
-            },
-            None => vec![],
+            Some(AttrValue::Table(list)) => list.iter().map(|row| row.first().unwrap().content.clone()).collect(),
+            _ => vec![],
         }
     }
 }
@@ -1,16 +1,19 @@
-use std::collections::HashMap;
+use std::{cmp::min, collections::HashMap};
 
 use ratatui::{layout::{Constraint, Direction as RatatuiDirection, Layout}, widgets::{Block, Borders, Clear}, style::Color};
-use tuirealm::{command::{Cmd, CmdResult}, event::KeyEvent, props::BorderType, AttrValue, Attribute, Component, Event, MockComponent};
+use tuirealm::{command::{Cmd, CmdResult}, event::KeyEvent, props::{BorderType, PropPayload, PropValue}, AttrValue, Attribute, Component, Event, MockComponent};
 
 use crate::ui3::editor_simple_input::EditorSimpleInput;
 use crate::ui3::query_input::EditorInput;
 
-use super::{AppEvent, Msg};
+use super::{completion::Completions, highlight::Syntax, AppEvent, Msg, APP_EDITOR_CONTENT};
 
 pub trait EditorPopupWidget :Component<Msg, AppEvent> {
     fn get_content(&self) -> Vec<String>;
     fn get_editor_type(&self) -> &'static str;
+    fn set_content(&mut self, content: Vec<String>);
+    fn set_completions(&mut self, _completions: Completions) {}
+    fn set_syntax(&mut self, _syntax: Syntax) {}
 }
 
 pub enum EditorType {
@@ -22,6 +25,9 @@ pub struct EditorPopup {
     editor_type: crate::ui3::EditorType,
     components: Vec<(Box<dyn EditorPopupWidget>, EditorType)>,
     selected_component_index: usize,
+    // previous queries, browsed with Ctrl-p/Ctrl-n
+    history: Vec<String>,
+    history_index: Option<usize>,
 }
 
 impl EditorPopup {
@@ -38,18 +44,158 @@ impl EditorPopup {
                 (Box::new(EditorSimpleInput::new("Type", "type")), EditorType::Oneline),
                 (Box::new(EditorSimpleInput::new("Name", "name")), EditorType::Oneline),
             ],
+            super::EditorType::DeleteDbObject(ref name, _) => vec![
+                (Box::new(EditorSimpleInput::new(format!("Delete '{}'? (y/n)", name), "confirm")), EditorType::Oneline),
+            ],
+            super::EditorType::RenameDbObject(_) => vec![
+                (Box::new(EditorSimpleInput::new("New name", "name")), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Overwrite existing (y/n)", "overwrite")), EditorType::Oneline),
+            ],
+            super::EditorType::CopyDbObject(_) => vec![
+                (Box::new(EditorSimpleInput::new("Destination name", "name")), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Destination database", "database")), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Overwrite existing (y/n)", "overwrite")), EditorType::Oneline),
+            ],
+            super::EditorType::ExpireDbObject(_) => vec![
+                (Box::new(EditorSimpleInput::new("TTL in seconds (empty to persist)", "ttl")), EditorType::Oneline),
+            ],
+            super::EditorType::Secret(_, ref secret, _) => vec![
+                (Box::new(EditorSimpleInput::password(format!("Enter {}", secret), "secret")), EditorType::Oneline),
+            ],
+            super::EditorType::ConnectionForm(_, ref connection) => vec![
+                (Box::new(EditorSimpleInput::new("Name", "name").with_value(connection.name.clone())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Type (Redis, RedisCluster, Postgres, MySql)", "type").with_value(connection.connection_type.to_string())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Connection string", "connection_string").with_value(connection.connection_string.clone())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Password file (optional)", "password_file")
+                    .with_value(connection.password_file.as_ref().map(|path| path.display().to_string()).unwrap_or_default())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Password command (optional)", "password_command")
+                    .with_value(connection.password_command.clone().unwrap_or_default())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Environment (dev, staging, prod or empty)", "environment")
+                    .with_value(connection.environment.as_ref().map(|environment| environment.to_string()).unwrap_or_default())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Read-only (y/n)", "read_only").with_value(if connection.read_only { "y" } else { "n" })), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("RESP3, Redis only (y/n)", "resp3").with_value(if connection.resp3 { "y" } else { "n" })), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Group", "group").with_value(connection.group.clone().unwrap_or_default())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Tags (comma separated)", "tags").with_value(connection.tags.join(", "))), EditorType::Oneline),
+            ],
+            super::EditorType::FuzzyFind(ref filter) => vec![
+                (Box::new(EditorSimpleInput::new("Find connection (name, type, group, host, tags)", "search").with_value(filter.clone())), EditorType::Oneline),
+            ],
+            super::EditorType::DeleteConnection(_, ref name) => vec![
+                (Box::new(EditorSimpleInput::new(format!("Delete connection '{}'? (y/n)", name), "confirm")), EditorType::Oneline),
+            ],
+            super::EditorType::SplitView => vec![
+                (Box::new(EditorSimpleInput::new("Compare with connection (empty to close split view)", "connection")), EditorType::Oneline),
+            ],
+            super::EditorType::KeyspaceDiff(ref left, ref right) => vec![
+                (Box::new(EditorSimpleInput::new("Left connection", "left").with_value(left.clone())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Right connection", "right").with_value(right.clone())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("MATCH pattern", "pattern").with_value("*")), EditorType::Oneline),
+            ],
+            super::EditorType::CopyKeys(ref pattern) => vec![
+                (Box::new(EditorSimpleInput::new("MATCH pattern", "pattern").with_value(pattern.clone())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Destination connection", "connection")), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Replace existing keys (y/n)", "overwrite").with_value("n")), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Dry run (y/n)", "dry_run").with_value("y")), EditorType::Oneline),
+            ],
+            super::EditorType::ExportResult => vec![
+                (Box::new(EditorSimpleInput::new("Export to CSV file", "path")), EditorType::Oneline),
+            ],
+            super::EditorType::ResetSlowlog => vec![
+                (Box::new(EditorSimpleInput::new("Reset slow log and latency events? (y/n)", "confirm")), EditorType::Oneline),
+            ],
+            super::EditorType::Subscribe => vec![
+                (Box::new(EditorSimpleInput::new("Channels (space separated, SUBSCRIBE)", "channels")), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Patterns (space separated, PSUBSCRIBE)", "patterns")), EditorType::Oneline),
+            ],
+            super::EditorType::Publish(ref channel) => vec![
+                (Box::new(EditorSimpleInput::new("Channel", "channel").with_value(channel.clone())), EditorType::Oneline),
+                (Box::new(EditorSimpleInput::new("Message", "message")), EditorType::Oneline),
+            ],
+            super::EditorType::Script => vec![
+                (Box::new(EditorInput::new("Lua script", "script")), EditorType::Multiline),
+                (Box::new(EditorInput::new("KEYS (one per line)", "keys")), EditorType::Multiline),
+                (Box::new(EditorInput::new("ARGV (one per line)", "args")), EditorType::Multiline),
+            ],
+            super::EditorType::LoadFunction(ref code, replace) => {
+                let mut editor = EditorInput::new("Library code", "code");
+                editor.set_content(code.lines().map(str::to_string).collect());
+                vec![
+                    (Box::new(editor), EditorType::Multiline),
+                    (Box::new(EditorSimpleInput::new("Replace existing library (y/n)", "replace").with_value(if replace { "y" } else { "n" })), EditorType::Oneline),
+                ]
+            },
+            super::EditorType::DeleteFunction(ref library) => vec![
+                (Box::new(EditorSimpleInput::new(format!("Delete library '{}'? (y/n)", library), "confirm")), EditorType::Oneline),
+            ],
+            super::EditorType::BeginTransaction => vec![
+                (Box::new(EditorSimpleInput::new("WATCH keys (space separated, Redis only)", "watch")), EditorType::Oneline),
+            ],
+            super::EditorType::MonitorFilter(ref filter) => vec![
+                (Box::new(EditorSimpleInput::new("Filter by client, db, command or args (empty shows all)", "filter").with_value(filter.clone())), EditorType::Oneline),
+            ],
+            super::EditorType::ConfirmQuery(ref connection_name, _) => vec![
+                (Box::new(EditorSimpleInput::new(format!("Destructive action on prod, type '{}' to confirm", connection_name), "confirm")), EditorType::Oneline),
+            ],
         };
 
         let mut popup = Self {
             editor_type,
             components,
-            selected_component_index: 0
+            selected_component_index: 0,
+            history: vec![],
+            history_index: None,
         };
         
         popup.update_focus();
         popup
     }
 
+    pub fn with_content(mut self, editor_type: &'static str, content: Vec<String>) -> Self {
+        if let Some((component, _)) = self.components.iter_mut().find(|(component, _)| component.get_editor_type() == editor_type) {
+            component.set_content(content);
+        }
+        self.update_focus();
+        self
+    }
+
+    pub fn with_completions(mut self, editor_type: &'static str, completions: Completions) -> Self {
+        if let Some((component, _)) = self.components.iter_mut().find(|(component, _)| component.get_editor_type() == editor_type) {
+            component.set_completions(completions);
+        }
+        self
+    }
+
+    pub fn with_syntax(mut self, editor_type: &'static str, syntax: Syntax) -> Self {
+        if let Some((component, _)) = self.components.iter_mut().find(|(component, _)| component.get_editor_type() == editor_type) {
+            component.set_syntax(syntax);
+        }
+        self
+    }
+
+    pub fn with_history(mut self, history: Vec<String>) -> Self {
+        self.history = history;
+        self
+    }
+
+    fn browse_history(&mut self, older: bool) {
+        let last = match self.history.len().checked_sub(1) {
+            Some(last) => last,
+            None => return,
+        };
+        let index = match (self.history_index, older) {
+            (None, true) => last,
+            (None, false) => return,
+            (Some(index), true) => index.saturating_sub(1),
+            (Some(index), false) => min(index + 1, last),
+        };
+        self.history_index = Some(index);
+        let content = self.history[index].lines().map(|line| line.to_string()).collect();
+        if let Some((component, _)) = self.components.get_mut(self.selected_component_index) {
+            component.set_content(content);
+        }
+        self.update_focus();
+    }
+
     fn update_focus(&mut self) {
         for (component, _) in &mut self.components {
             component.attr(Attribute::Focus, AttrValue::Flag(false));
@@ -79,6 +225,28 @@ impl EditorPopup {
             super::EditorType::Search => "Search",
             super::EditorType::Query => "Query Editor",
             super::EditorType::AddDbObject => "Add Database Object",
+            super::EditorType::DeleteDbObject(..) => "Delete Database Object",
+            super::EditorType::RenameDbObject(_) => "Rename Database Object",
+            super::EditorType::CopyDbObject(_) => "Copy Database Object",
+            super::EditorType::ExpireDbObject(_) => "Expire Database Object",
+            super::EditorType::ConfirmQuery(..) => "Confirm",
+            super::EditorType::Secret(..) => "Enter Secret",
+            super::EditorType::ConnectionForm(Some(_), _) => "Edit Connection",
+            super::EditorType::ConnectionForm(None, _) => "New Connection",
+            super::EditorType::DeleteConnection(..) => "Delete Connection",
+            super::EditorType::FuzzyFind(_) => "Find Connection",
+            super::EditorType::SplitView => "Split View",
+            super::EditorType::KeyspaceDiff(..) => "Compare Keyspaces",
+            super::EditorType::ExportResult => "Export Result",
+            super::EditorType::CopyKeys(_) => "Copy Keys To Connection",
+            super::EditorType::ResetSlowlog => "Reset Slowlog",
+            super::EditorType::MonitorFilter(_) => "Filter Monitor",
+            super::EditorType::Subscribe => "Subscribe",
+            super::EditorType::Publish(_) => "Publish Message",
+            super::EditorType::Script => "Run Script",
+            super::EditorType::LoadFunction(..) => "Load Function Library",
+            super::EditorType::DeleteFunction(_) => "Delete Function Library",
+            super::EditorType::BeginTransaction => "Begin Transaction",
         }
     }
 }
@@ -87,7 +255,8 @@ impl Component<Msg, AppEvent> for EditorPopup {
     fn on(&mut self, ev: Event<AppEvent>) -> Option<Msg> {
         if let Event::Keyboard(KeyEvent { code, modifiers, .. }) = &ev {
             match code {
-                tuirealm::event::Key::Tab => {
+                // a single editor handles Tab itself, e.g. for completion
+                tuirealm::event::Key::Tab if self.components.len() > 1 => {
                     if modifiers.contains(tuirealm::event::KeyModifiers::SHIFT) {
                         self.prev_component();
                     } else {
@@ -95,6 +264,16 @@ impl Component<Msg, AppEvent> for EditorPopup {
                     }
                     return Some(Msg::None);
                 },
+                tuirealm::event::Key::Char('e') if modifiers.contains(tuirealm::event::KeyModifiers::CONTROL) => {
+                    if let Some((component, EditorType::Multiline)) = self.components.get(self.selected_component_index) {
+                        return Some(Msg::EditExternally(component.get_content()));
+                    }
+                    return Some(Msg::None);
+                },
+                tuirealm::event::Key::Char(ch @ ('p' | 'n')) if modifiers.contains(tuirealm::event::KeyModifiers::CONTROL) && !self.history.is_empty() => {
+                    self.browse_history(*ch == 'p');
+                    return Some(Msg::None);
+                },
                 _ => {}
             }
         }
@@ -112,6 +291,10 @@ impl Component<Msg, AppEvent> for EditorPopup {
                 self.next_component();
                 Some(Msg::None)
             }
+            Some(Msg::None) if matches!(self.editor_type, super::EditorType::FuzzyFind(_)) => {
+                let filter = component.get_content().join("");
+                Some(Msg::FilterConnections(filter))
+            }
             m => m
         }
     }
@@ -187,6 +370,18 @@ impl MockComponent for EditorPopup {
             (Attribute::Focus, AttrValue::Flag(_)) => {
                 self.components.iter_mut().for_each(|c| c.0.attr(Attribute::Focus, AttrValue::Flag(false)));
             },
+            (Attribute::Custom(APP_EDITOR_CONTENT), AttrValue::Payload(PropPayload::Vec(lines))) => {
+                let content = lines.iter()
+                    .filter_map(|line| match line {
+                        PropValue::Str(line) => Some(line.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                if let Some((component, _)) = self.components.get_mut(self.selected_component_index) {
+                    component.set_content(content);
+                }
+                return;
+            },
             _ => ()
         };
         let (component, _) = self.components.get_mut(self.selected_component_index).unwrap();
@@ -1,43 +1,55 @@
-use ratatui::{layout::Alignment, style::Color};
-use tui_realm_stdlib::Input;
-use tuirealm::{command::Cmd, event::{Key, KeyEvent}, props::BorderType, AttrValue, Attribute, Component, Event, MockComponent};
+use ratatui::{layout::Alignment, style::{Color, Style}, text::{Line, Span}, widgets::{Block, BorderType, Borders, Paragraph}};
+use tuirealm::{command::{Cmd, CmdResult}, event::Key, props::Props, AttrValue, Attribute, Component, Event, MockComponent, State, StateValue};
 
-use crate::ui3::{editor_popup::EditorPopupWidget, AppEvent, Msg};
+use crate::ui3::{editor_popup::EditorPopupWidget, vim::{Mode, Vim}, AppEvent, Msg};
 
 pub struct EditorSimpleInput {
-    component: Input,
+    props: Props,
+    vim: Vim,
+    title: String,
+    // chars are shown as this char, e.g. for passwords
+    mask: Option<char>,
+    // first visible column
+    scroll: usize,
     pub editor_type: &'static str,
 }
 
 
 impl EditorSimpleInput {
-    pub fn new(title: &'static str, editor_type: &'static str) -> Self {
+    pub fn new<S: Into<String>>(title: S, editor_type: &'static str) -> Self {
         Self {
-            component: Input::default()
-                .title(title, Alignment::Left)
-                .borders(tuirealm::props::Borders::default().modifiers(BorderType::Rounded).color(Color::Yellow)),
+            props: Props::default(),
+            vim: Vim::new(vec![], true),
+            title: title.into(),
+            mask: None,
+            scroll: 0,
             editor_type,
         }
     }
+
+    pub fn with_value<S: Into<String>>(mut self, value: S) -> Self {
+        self.vim.set_content(vec![value.into()]);
+        self
+    }
+
+    pub fn password<S: Into<String>>(title: S, editor_type: &'static str) -> Self {
+        let mut editor = Self::new(title, editor_type);
+        editor.mask = Some('*');
+        editor
+    }
 }
 
 impl Component<Msg, AppEvent> for EditorSimpleInput {
     fn on(&mut self, ev: tuirealm::Event<AppEvent>) -> Option<Msg> {
-        match ev {
-            Event::Keyboard(KeyEvent { code, .. }) => match code {
-                Key::Esc => Some(Msg::DiactivateEditor),
-                Key::Tab => Some(Msg::EditorPopupNext),
-                Key::Enter => Some(Msg::EditorAccept),
-                Key::Backspace => {
-                    self.component.perform(Cmd::Delete);
-                    Some(Msg::None)
-                },
-                Key::Char(c) => {
-                    self.component.perform(Cmd::Type(c));
-                    Some(Msg::None)
-                },
-                _ => None,
-            },
+        let key = match ev {
+            Event::Keyboard(key) => key,
+            _ => return None,
+        };
+        match key.code {
+            Key::Esc if self.vim.is_idle() || self.vim.is_untouched() => Some(Msg::DiactivateEditor),
+            Key::Tab => Some(Msg::EditorPopupNext),
+            Key::Enter if matches!(self.vim.mode, Mode::Insert | Mode::Normal) => Some(Msg::EditorAccept),
+            _ if self.vim.handle_key(key) => Some(Msg::None),
             _ => None,
         }
     }
@@ -45,39 +57,52 @@ impl Component<Msg, AppEvent> for EditorSimpleInput {
 
 impl EditorPopupWidget for EditorSimpleInput {
     fn get_content(&self) -> Vec<String> {
-        vec![self.component.states.get_value()]
+        vec![self.vim.buffer.lines().join(" ")]
     }
-    
+
     fn get_editor_type(&self) -> &'static str {
         self.editor_type
     }
+
+    fn set_content(&mut self, content: Vec<String>) {
+        self.vim.set_content(vec![content.join(" ")]);
+    }
 }
 
 impl MockComponent for EditorSimpleInput {
     fn view(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect) {
-        self.component.view(frame, area);
+        let focused = self.props.get_or(Attribute::Focus, AttrValue::Flag(false)).unwrap_flag();
+        let mut block = Block::default()
+            .title(self.title.as_str())
+            .title_alignment(Alignment::Left)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(if focused { Color::Yellow } else { Color::Gray }));
+        if focused {
+            block = block.title_bottom(Line::from(Span::styled(format!(" {} ", self.vim.mode.name()), self.vim.mode.style())));
+        }
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let (_, col) = self.vim.buffer.cursor();
+        self.scroll = Vim::scroll_to(self.scroll, col, inner.width as usize);
+        let line = self.vim.styled_line(0, self.scroll, vec![], focused, self.mask);
+        frame.render_widget(Paragraph::new(line), inner);
     }
 
     fn query(&self, attr: Attribute) -> Option<AttrValue> {
-        self.component.query(attr)
+        self.props.get(attr)
     }
 
     fn attr(&mut self, attr: Attribute, value: AttrValue) {
-        if let (Attribute::Focus, AttrValue::Flag(focused)) = (&attr, &value) {
-            let border_color = if *focused { Color::Yellow } else { Color::Gray };
-            self.component.attr(
-                Attribute::Borders,
-                AttrValue::Borders(tuirealm::props::Borders::default().color(border_color))
-            );
-        }
-        self.component.attr(attr, value);
+        self.props.set(attr, value);
     }
 
-    fn state(&self) -> tuirealm::State {
-        self.component.state()
+    fn state(&self) -> State {
+        State::One(StateValue::String(self.get_content().join("")))
     }
 
-    fn perform(&mut self, cmd: tuirealm::command::Cmd) -> tuirealm::command::CmdResult {
-        self.component.perform(cmd)
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
     }
 }
@@ -0,0 +1,206 @@
+use ratatui::{layout::{Alignment, Constraint, Direction, Layout, Rect}, style::{Color, Style}, widgets::{Block, Borders as RatatuiBorders, Paragraph}, Frame};
+use tuirealm::{command::{Cmd, CmdResult}, event::{Key, KeyEvent}, props::{BorderType, Borders, Props, TableBuilder, TextSpan}, AttrValue, Attribute, Component, Event, MockComponent, State};
+
+use crate::dbclient::redis::{scripting::{fetch_functions, FunctionLibrary}, PollConnection, RedisConfig};
+
+use super::{AppEvent, EditorType, Msg};
+
+// Written into the load editor by `l`
+const LIBRARY_TEMPLATE: &str = "#!lua name=mylib\n\nredis.register_function('myfunc', function(keys, args)\n  return args[1]\nend)";
+
+#[derive(PartialEq)]
+enum Pane {
+    Functions,
+    Code,
+}
+
+// FUNCTION LIST libraries with their functions on top, the code of the selected library below
+pub struct FunctionsView {
+    props: Props,
+    connection: PollConnection,
+    functions: tui_realm_stdlib::Table,
+    libraries: Vec<FunctionLibrary>,
+    // library of every table row after the header
+    rows: Vec<usize>,
+    code_scroll: u16,
+    focus: Pane,
+}
+
+impl FunctionsView {
+    pub fn new(config: RedisConfig) -> Self {
+        let mut view = Self {
+            props: Props::default(),
+            connection: PollConnection::new(&config.uri),
+            functions: tui_realm_stdlib::Table::default()
+                .title("Functions", Alignment::Left)
+                .highlighted_color(Color::Yellow)
+                .highlighted_str("> ")
+                .scroll(true)
+                .rewind(true)
+                .widths(&[20, 10, 20, 20, 30]),
+            libraries: vec![],
+            rows: vec![],
+            code_scroll: 0,
+            focus: Pane::Functions,
+        };
+        view.refresh();
+        view
+    }
+
+    fn refresh(&mut self) {
+        match self.connection.run(fetch_functions) {
+            Ok(libraries) => {
+                self.libraries = libraries;
+                self.reload();
+            },
+            Err(err) => {
+                let mut table = TableBuilder::default();
+                table.add_col(TextSpan::from(err.to_string()).fg(Color::Red));
+                self.libraries = vec![];
+                self.rows = vec![];
+                self.functions.attr(Attribute::Content, AttrValue::Table(table.build()));
+            },
+        }
+        self.code_scroll = 0;
+        self.update_focus();
+    }
+
+    fn reload(&mut self) {
+        let mut table = TableBuilder::default();
+        for column in ["library", "engine", "function", "flags", "description"] {
+            table.add_col(TextSpan::from(column).bold());
+        }
+        self.rows = vec![];
+        for (index, library) in self.libraries.iter().enumerate() {
+            if library.functions.is_empty() {
+                table.add_row()
+                    .add_col(TextSpan::from(library.name.clone()).fg(Color::Blue))
+                    .add_col(TextSpan::from(library.engine.clone()))
+                    .add_col(TextSpan::from("(no functions)").fg(Color::Gray))
+                    .add_col(TextSpan::from(""))
+                    .add_col(TextSpan::from(""));
+                self.rows.push(index);
+            }
+            // the library name is only written on its first row
+            for (position, function) in library.functions.iter().enumerate() {
+                let (name, engine) = if position == 0 { (library.name.clone(), library.engine.clone()) } else { (String::new(), String::new()) };
+                table.add_row()
+                    .add_col(TextSpan::from(name).fg(Color::Blue))
+                    .add_col(TextSpan::from(engine))
+                    .add_col(TextSpan::from(function.name.clone()).fg(Color::Yellow))
+                    .add_col(TextSpan::from(function.flags.join(", ")).fg(Color::Magenta))
+                    .add_col(TextSpan::from(function.description.clone()).fg(Color::Gray));
+                self.rows.push(index);
+            }
+        }
+        self.functions.attr(Attribute::Content, AttrValue::Table(table.build()));
+    }
+
+    fn selected_library(&self) -> Option<&FunctionLibrary> {
+        // the first row is the header
+        let index = self.functions.states.list_index.checked_sub(1)?;
+        self.rows.get(index).and_then(|library| self.libraries.get(*library))
+    }
+
+    fn update_focus(&mut self) {
+        let color = if self.focus == Pane::Functions { Color::Yellow } else { Color::Gray };
+        self.functions.attr(Attribute::Borders, AttrValue::Borders(Borders::default().modifiers(BorderType::Rounded).color(color)));
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let table = &mut self.functions;
+        let previous = table.states.list_index;
+        if down {
+            table.states.incr_list_index(true);
+        } else {
+            table.states.decr_list_index(true);
+        }
+        if table.states.list_index == 0 {
+            if down {
+                table.states.incr_list_index(true);
+            } else {
+                table.states.decr_list_index(true);
+            }
+        }
+        if table.states.list_index != previous {
+            self.code_scroll = 0;
+        }
+    }
+}
+
+impl MockComponent for FunctionsView {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Fill(1)])
+            .split(area);
+        self.functions.view(frame, chunks[0]);
+
+        let (title, code) = match self.selected_library() {
+            Some(library) => (format!("Code: {}", library.name), library.code.clone()),
+            None => ("Code".to_string(), String::new()),
+        };
+        let color = if self.focus == Pane::Code { Color::Yellow } else { Color::Gray };
+        let block = Block::default()
+            .title(title)
+            .borders(RatatuiBorders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(color));
+        frame.render_widget(Paragraph::new(code).block(block).scroll((self.code_scroll, 0)), chunks[1]);
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.props.get(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        self.props.set(attr, value);
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, AppEvent> for FunctionsView {
+    fn on(&mut self, ev: Event<AppEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc | Key::Char('q'), .. }) => Some(Msg::CloseFunctions),
+            Event::Keyboard(KeyEvent { code: Key::Char('j') | Key::Down, .. }) => {
+                match self.focus {
+                    Pane::Functions => self.move_selection(true),
+                    Pane::Code => self.code_scroll = self.code_scroll.saturating_add(1),
+                }
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('k') | Key::Up, .. }) => {
+                match self.focus {
+                    Pane::Functions => self.move_selection(false),
+                    Pane::Code => self.code_scroll = self.code_scroll.saturating_sub(1),
+                }
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                self.focus = if self.focus == Pane::Functions { Pane::Code } else { Pane::Functions };
+                self.update_focus();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('r'), .. }) => {
+                self.refresh();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('l'), .. }) => Some(Msg::ActivateEditor(EditorType::LoadFunction(LIBRARY_TEMPLATE.to_string(), false))),
+            Event::Keyboard(KeyEvent { code: Key::Char('e'), .. }) => self.selected_library()
+                .map(|library| Msg::ActivateEditor(EditorType::LoadFunction(library.code.clone(), true)))
+                .or(Some(Msg::None)),
+            Event::Keyboard(KeyEvent { code: Key::Char('d'), .. }) => self.selected_library()
+                .map(|library| Msg::ActivateEditor(EditorType::DeleteFunction(library.name.clone())))
+                .or(Some(Msg::None)),
+            _ => None,
+        }
+    }
+}
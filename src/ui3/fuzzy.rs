@@ -0,0 +1,60 @@
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 8;
+const BOUNDARY_BONUS: i64 = 8;
+const GAP_PENALTY: i64 = 1;
+
+// fzf-like subsequence match. Case-insensitive unless the pattern has an uppercase letter.
+// Returns None when `text` does not contain all the pattern characters in order.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    let case_sensitive = pattern.chars().any(|ch| ch.is_uppercase());
+    let normalize = |ch: char| if case_sensitive { ch } else { ch.to_lowercase().next().unwrap_or(ch) };
+
+    let text: Vec<char> = text.chars().collect();
+    let mut pattern = pattern.chars().map(normalize).peekable();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (index, ch) in text.iter().enumerate() {
+        let expected = match pattern.peek() {
+            Some(expected) => *expected,
+            None => break,
+        };
+        if normalize(*ch) != expected {
+            continue;
+        }
+        pattern.next();
+
+        score += MATCH_SCORE;
+        match last_match {
+            Some(last) if last + 1 == index => score += CONSECUTIVE_BONUS,
+            Some(last) => score -= (index - last - 1) as i64 * GAP_PENALTY,
+            None => (),
+        }
+        let at_boundary = match index.checked_sub(1).map(|prev| text[prev]) {
+            None => true,
+            Some(prev) => !prev.is_alphanumeric() || (prev.is_lowercase() && ch.is_uppercase()),
+        };
+        if at_boundary {
+            score += BOUNDARY_BONUS;
+        }
+        last_match = Some(index);
+    }
+
+    match pattern.peek() {
+        Some(_) => None,
+        None => Some(score),
+    }
+}
+
+// Every space separated term has to match at least one of the fields
+pub fn fuzzy_score_fields(pattern: &str, fields: &[&str]) -> Option<i64> {
+    pattern.split_whitespace().try_fold(0, |total, term| {
+        fields.iter()
+            .filter_map(|field| fuzzy_score(term, field))
+            .max()
+            .map(|score| total + score)
+    })
+}
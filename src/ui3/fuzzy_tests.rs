@@ -0,0 +1,50 @@
+use crate::ui3::fuzzy::{fuzzy_score, fuzzy_score_fields};
+
+#[test]
+fn test_fuzzy_score_matches_subsequences() {
+    assert_eq!(fuzzy_score("", "anything"), Some(0));
+    assert_eq!(fuzzy_score("abc", "abc"), Some(72));
+    assert!(fuzzy_score("prd", "production").is_some());
+    assert!(fuzzy_score("cache", "cache-eu-1").is_some());
+    assert_eq!(fuzzy_score("cba", "abc"), None);
+    assert_eq!(fuzzy_score("abcd", "abc"), None);
+    assert_eq!(fuzzy_score("x", ""), None);
+}
+
+#[test]
+fn test_fuzzy_score_case() {
+    // lowercase patterns ignore case, a pattern with an uppercase letter is case-sensitive
+    assert!(fuzzy_score("redis", "RedisCluster").is_some());
+    assert!(fuzzy_score("RC", "RedisCluster").is_some());
+    assert_eq!(fuzzy_score("RC", "rediscluster"), None);
+}
+
+#[test]
+fn test_fuzzy_score_ranking() {
+    let score = |pattern: &str, text: &str| fuzzy_score(pattern, text).unwrap();
+    // consecutive characters beat scattered ones
+    assert!(score("abc", "abc") > score("abc", "a_b_c"));
+    assert!(score("cache", "cache-prod") > score("cache", "c-a-c-h-e"));
+    // word boundaries and camelCase humps beat matches inside words
+    assert!(score("fb", "fooBar") > score("fb", "foobar"));
+    assert!(score("eu", "prod-eu") > score("eu", "queue"));
+    // smaller gaps beat bigger ones
+    assert!(score("ac", "abc") > score("ac", "abbbbc"));
+}
+
+#[test]
+fn test_fuzzy_score_fields() {
+    let fields = ["cache", "Redis", "work", "10.0.0.1:6379", "eu", "hot"];
+    assert_eq!(fuzzy_score_fields("", &fields), Some(0));
+    assert!(fuzzy_score_fields("cache", &fields).is_some());
+    // every term has to match some field, not necessarily the same one
+    assert!(fuzzy_score_fields("cache eu", &fields).is_some());
+    assert!(fuzzy_score_fields("work 6379", &fields).is_some());
+    assert_eq!(fuzzy_score_fields("cache us", &fields), None);
+    // the best field counts for each term
+    assert_eq!(fuzzy_score_fields("hot", &fields), fuzzy_score("hot", "hot"));
+    assert_eq!(
+        fuzzy_score_fields("cache hot", &fields),
+        Some(fuzzy_score("cache", "cache").unwrap() + fuzzy_score("hot", "hot").unwrap()),
+    );
+}
@@ -0,0 +1,189 @@
+use ratatui::style::{Color, Modifier, Style};
+
+const SQL_KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "AND", "OR", "NOT", "IN", "IS", "NULL", "LIKE", "ILIKE", "BETWEEN", "EXISTS",
+    "INSERT", "INTO", "VALUES", "UPDATE", "SET", "DELETE", "RETURNING",
+    "CREATE", "ALTER", "DROP", "TRUNCATE", "TABLE", "VIEW", "INDEX", "SCHEMA", "DATABASE", "SEQUENCE", "IF",
+    "PRIMARY", "FOREIGN", "KEY", "REFERENCES", "UNIQUE", "DEFAULT", "CONSTRAINT", "CHECK",
+    "JOIN", "INNER", "LEFT", "RIGHT", "FULL", "OUTER", "CROSS", "ON", "USING", "AS", "DISTINCT",
+    "GROUP", "BY", "ORDER", "ASC", "DESC", "HAVING", "LIMIT", "OFFSET", "UNION", "ALL", "INTERSECT", "EXCEPT",
+    "CASE", "WHEN", "THEN", "ELSE", "END", "WITH", "RECURSIVE", "TRUE", "FALSE",
+    "BEGIN", "COMMIT", "ROLLBACK", "TRANSACTION", "SAVEPOINT", "EXPLAIN", "ANALYZE", "SHOW", "DESCRIBE", "USE",
+    "GRANT", "REVOKE", "CAST",
+];
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Syntax {
+    #[default]
+    Plain,
+    Redis,
+    Sql,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TokenKind {
+    Command,
+    Keyword,
+    Identifier,
+    String,
+    Number,
+    Comment,
+    // quote without its closing quote, parenthesis without its pair
+    Unbalanced,
+}
+
+impl TokenKind {
+    pub fn style(&self) -> Style {
+        match self {
+            TokenKind::Command => Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            TokenKind::Keyword => Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+            TokenKind::Identifier => Style::default().fg(Color::Cyan),
+            TokenKind::String => Style::default().fg(Color::Green),
+            TokenKind::Number => Style::default().fg(Color::Yellow),
+            TokenKind::Comment => Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+            TokenKind::Unbalanced => Style::default().fg(Color::White).bg(Color::Red),
+        }
+    }
+}
+
+// Highlighted chars `start..end` of one line
+#[derive(Debug, PartialEq, Clone)]
+pub struct Token {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+    pub kind: TokenKind,
+}
+
+// Tokens of the whole buffer, strings and comments may span lines
+pub fn tokenize(syntax: Syntax, lines: &[String]) -> Vec<Token> {
+    if syntax == Syntax::Plain {
+        return vec![];
+    }
+    // (row, col, char) with a '\n' between lines
+    let mut chars: Vec<(usize, usize, char)> = vec![];
+    for (row, line) in lines.iter().enumerate() {
+        if row > 0 {
+            chars.push((row, 0, '\n'));
+        }
+        chars.extend(line.chars().enumerate().map(|(col, ch)| (row, col, ch)));
+    }
+    let at = |index: usize| chars.get(index).map(|(_, _, ch)| *ch);
+    let quotes: &[char] = match syntax {
+        Syntax::Sql => &['\'', '"', '`'],
+        _ => &['\'', '"'],
+    };
+    let is_word_char = |ch: char| match syntax {
+        Syntax::Sql => ch.is_alphanumeric() || ch == '_' || ch == '$' || ch == '.',
+        _ => !ch.is_whitespace() && ch != '(' && ch != ')' && !quotes.contains(&ch),
+    };
+
+    let mut tokens = vec![];
+    let mut push = |start: usize, end: usize, kind: TokenKind| {
+        // one token per line the range covers
+        for part in chars[start..end].split(|(_, _, ch)| *ch == '\n') {
+            if let (Some(first), Some(last)) = (part.first(), part.last()) {
+                tokens.push(Token { row: first.0, start: first.1, end: last.1 + 1, kind });
+            }
+        }
+    };
+    let mut parens = vec![];
+    let mut first_word = true;
+    let mut index = 0;
+    while let Some(ch) = at(index) {
+        let next = at(index + 1);
+        if ch.is_whitespace() {
+            index += 1;
+        } else if syntax == Syntax::Sql && ch == '-' && next == Some('-') {
+            let end = (index..chars.len()).find(|end| at(*end) == Some('\n')).unwrap_or(chars.len());
+            push(index, end, TokenKind::Comment);
+            index = end;
+        } else if syntax == Syntax::Sql && ch == '/' && next == Some('*') {
+            let end = (index + 2..chars.len()).find(|end| at(*end) == Some('*') && at(end + 1) == Some('/')).map(|end| end + 2);
+            match end {
+                Some(end) => push(index, end, TokenKind::Comment),
+                None => push(index, chars.len(), TokenKind::Unbalanced),
+            }
+            index = end.unwrap_or(chars.len());
+        } else if ch == '(' {
+            parens.push(index);
+            index += 1;
+        } else if ch == ')' {
+            if parens.pop().is_none() {
+                push(index, index + 1, TokenKind::Unbalanced);
+            }
+            index += 1;
+        } else if quotes.contains(&ch) {
+            let end = quoted_end(syntax, &at, index, ch);
+            let kind = match end {
+                None => TokenKind::Unbalanced,
+                // SQL double quotes and backticks quote identifiers
+                Some(_) if syntax == Syntax::Sql && ch != '\'' => TokenKind::Identifier,
+                Some(_) => TokenKind::String,
+            };
+            let end = end.unwrap_or(chars.len());
+            push(index, end, kind);
+            first_word = false;
+            index = end;
+        } else if is_word_char(ch) {
+            let end = (index..chars.len()).find(|end| !at(*end).is_some_and(is_word_char)).unwrap_or(chars.len());
+            let word: String = chars[index..end].iter().map(|(_, _, ch)| ch).collect();
+            let kind = if is_number(&word) {
+                Some(TokenKind::Number)
+            } else {
+                match syntax {
+                    Syntax::Redis if first_word => Some(TokenKind::Command),
+                    Syntax::Sql if SQL_KEYWORDS.contains(&word.to_uppercase().as_str()) => Some(TokenKind::Keyword),
+                    Syntax::Sql => Some(TokenKind::Identifier),
+                    _ => None,
+                }
+            };
+            if let Some(kind) = kind {
+                push(index, end, kind);
+            }
+            first_word = false;
+            index = end;
+        } else {
+            index += 1;
+        }
+    }
+    for index in parens {
+        push(index, index + 1, TokenKind::Unbalanced);
+    }
+    tokens.sort_by_key(|token| (token.row, token.start));
+    tokens
+}
+
+// Index after the closing quote, None when the quote is not closed
+fn quoted_end(syntax: Syntax, at: &impl Fn(usize) -> Option<char>, start: usize, quote: char) -> Option<usize> {
+    let mut index = start + 1;
+    loop {
+        match at(index)? {
+            '\\' if syntax == Syntax::Redis => index += 2,
+            // SQL escapes a quote by doubling it
+            ch if ch == quote && syntax == Syntax::Sql && at(index + 1) == Some(quote) => index += 2,
+            ch if ch == quote => return Some(index + 1),
+            _ => index += 1,
+        }
+    }
+}
+
+fn is_number(word: &str) -> bool {
+    let digits = word.strip_prefix(['-', '+']).unwrap_or(word);
+    digits.chars().next().is_some_and(|ch| ch.is_ascii_digit()) && digits.parse::<f64>().is_ok()
+}
+
+pub fn has_unbalanced(tokens: &[Token]) -> bool {
+    tokens.iter().any(|token| token.kind == TokenKind::Unbalanced)
+}
+
+// Style of every char of a line, `len` chars long
+pub fn line_styles(tokens: &[Token], row: usize, len: usize) -> Vec<Style> {
+    let mut styles = vec![Style::default(); len];
+    for token in tokens.iter().filter(|token| token.row == row) {
+        for style in styles.iter_mut().take(token.end).skip(token.start) {
+            *style = token.kind.style();
+        }
+    }
+    styles
+}
@@ -0,0 +1,78 @@
+use crate::ui3::highlight::{has_unbalanced, tokenize, Syntax, TokenKind};
+
+// Text and kind of every token
+fn tokens(syntax: Syntax, lines: &[&str]) -> Vec<(String, TokenKind)> {
+    let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    tokenize(syntax, &lines).into_iter()
+        .map(|token| (lines[token.row].chars().skip(token.start).take(token.end - token.start).collect(), token.kind))
+        .collect()
+}
+
+fn expected(tokens: &[(&str, TokenKind)]) -> Vec<(String, TokenKind)> {
+    tokens.iter().map(|(text, kind)| (text.to_string(), *kind)).collect()
+}
+
+#[test]
+fn test_tokenize_redis() {
+    use TokenKind::*;
+    let cases: [(&str, Vec<(&str, TokenKind)>); 8] = [
+        (r#"SET key "hello world" 10"#, vec![("SET", Command), (r#""hello world""#, String), ("10", Number)]),
+        (r#"set key 'it\'s' "say \"hi\"""#, vec![("set", Command), (r"'it\'s'", String), (r#""say \"hi\"""#, String)]),
+        ("ZADD zset -1.5 member +2 3e2", vec![("ZADD", Command), ("-1.5", Number), ("+2", Number), ("3e2", Number)]),
+        // only the first word is a command, later words that look like one are plain
+        ("GET SET", vec![("GET", Command)]),
+        (r#""GET" key"#, vec![(r#""GET""#, String)]),
+        (r#"GET "unterminated"#, vec![("GET", Command), (r#""unterminated"#, Unbalanced)]),
+        (r#"SET key "ends with \""#, vec![("SET", Command), (r#""ends with \""#, Unbalanced)]),
+        ("PING )", vec![("PING", Command), (")", Unbalanced)]),
+    ];
+    for (line, tokens_expected) in cases {
+        assert_eq!(tokens(Syntax::Redis, &[line]), expected(&tokens_expected), "{}", line);
+    }
+}
+
+#[test]
+fn test_tokenize_sql() {
+    use TokenKind::*;
+    let cases: [(&str, Vec<(&str, TokenKind)>); 5] = [
+        (
+            "SELECT name FROM users WHERE id = 1 -- first",
+            vec![("SELECT", Keyword), ("name", Identifier), ("FROM", Keyword), ("users", Identifier), ("WHERE", Keyword), ("id", Identifier), ("1", Number), ("-- first", Comment)],
+        ),
+        (r#"select 'it''s', "Col", `t`.x"#, vec![("select", Keyword), ("'it''s'", String), (r#""Col""#, Identifier), ("`t`", Identifier), (".x", Identifier)]),
+        ("SELECT (1", vec![("SELECT", Keyword), ("(", Unbalanced), ("1", Number)]),
+        ("SELECT 'open", vec![("SELECT", Keyword), ("'open", Unbalanced)]),
+        ("/* open", vec![("/* open", Unbalanced)]),
+    ];
+    for (line, tokens_expected) in cases {
+        assert_eq!(tokens(Syntax::Sql, &[line]), expected(&tokens_expected), "{}", line);
+    }
+}
+
+#[test]
+fn test_tokenize_multiline() {
+    // a string spanning lines gives one token per line
+    let lines = ["SET key \"first", "second\" 1"];
+    assert_eq!(tokens(Syntax::Redis, &lines), expected(&[
+        ("SET", TokenKind::Command),
+        ("\"first", TokenKind::String),
+        ("second\"", TokenKind::String),
+        ("1", TokenKind::Number),
+    ]));
+    let lines = ["SELECT /* a", "b */ 1"];
+    assert_eq!(tokens(Syntax::Sql, &lines), expected(&[
+        ("SELECT", TokenKind::Keyword),
+        ("/* a", TokenKind::Comment),
+        ("b */", TokenKind::Comment),
+        ("1", TokenKind::Number),
+    ]));
+}
+
+#[test]
+fn test_has_unbalanced() {
+    let lines = |text: &str| vec![text.to_string()];
+    assert!(tokenize(Syntax::Plain, &lines("GET \"open")).is_empty());
+    assert!(has_unbalanced(&tokenize(Syntax::Redis, &lines("GET \"open"))));
+    assert!(!has_unbalanced(&tokenize(Syntax::Redis, &lines("GET \"closed\""))));
+    assert!(!has_unbalanced(&tokenize(Syntax::Sql, &lines("SELECT count(*) FROM t"))));
+}
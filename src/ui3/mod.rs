@@ -1,6 +1,8 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
-use crate::dbclient::fetcher::FetchRequest;
+use ratatui::style::Color;
+
+use crate::{config::{Connection, Environment}, dbclient::fetcher::FetchRequest};
 
 pub mod model;
 pub mod connections_list;
@@ -9,8 +11,35 @@ pub mod query_result;
 pub mod query_input;
 pub mod editor_popup;
 pub mod editor_simple_input;
+pub mod status_line;
+pub mod fuzzy;
+pub mod dashboard;
+pub mod slowlog;
+pub mod monitor;
+pub mod pubsub;
+pub mod functions;
+pub mod session;
+pub mod session_tabs;
+pub mod completion;
+pub mod highlight;
+pub mod text_buffer;
+pub mod vim;
+
+#[cfg(test)]
+mod completion_tests;
+#[cfg(test)]
+mod fuzzy_tests;
+#[cfg(test)]
+mod highlight_tests;
+#[cfg(test)]
+mod vim_tests;
 
 pub const APP_SEARCH_PATTERN: &str = "app-search-pattern";
+pub const APP_CONNECTIONS_ROWS: &str = "app-connections-rows";
+pub const APP_LIST_INDEX: &str = "app-list-index";
+pub const APP_SUBSCRIBE_CHANNELS: &str = "app-subscribe-channels";
+pub const APP_SUBSCRIBE_PATTERNS: &str = "app-subscribe-patterns";
+pub const APP_EDITOR_CONTENT: &str = "app-editor-content";
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Msg {
@@ -20,16 +49,81 @@ pub enum Msg {
     FetchDbObjects,
     FetchDbObject(String),
     AddDbObject(String, String, String),
+    DeleteDbObject(String, bool),
+    RenameDbObject(String, String, bool),
+    CopyDbObject(String, String, Option<String>, bool),
+    ExpireDbObject(String, Option<u64>),
     ExecuteCustomQuery(String),
     ExecuteQuery(FetchRequest),
+    ExecuteConfirmedQuery(FetchRequest),
     EditorAccept,
     EditorPopupNext,
     EditorResult(EditorType, HashMap<&'static str, Vec<String>>),
     SearchPattern(String),
     ToQueryResultWidget,
     ToDbObjectsWidget,
+    ToCompareResultWidget,
     ActivateEditor(EditorType),
     DiactivateEditor,
+    TestConnection(usize),
+    ToggleConnectionsGroup(String),
+    FilterConnections(String),
+    AddConnection,
+    EditConnection(usize),
+    DuplicateConnection(usize),
+    DeleteConnection(usize),
+    SaveConnection(Option<usize>, Connection),
+    RemoveConnection(usize),
+    // connection whose open sessions need a fetcher built from its saved settings
+    RebuildFetchers(usize),
+    ShowStatus(String),
+    ShowError(String),
+    NextSession,
+    PrevSession,
+    ToSession(usize),
+    CloseSession,
+    OpenSplitView(usize),
+    CloseSplitView,
+    SyncResultScroll(usize),
+    OpenKeyspaceDiff,
+    // left connection, right connection, MATCH pattern
+    DiffKeyspace(usize, usize, String),
+    ExportResult(PathBuf),
+    // destination connection, MATCH pattern, replace existing keys, dry run
+    CopyKeys(usize, String, bool, bool),
+    OpenDashboard,
+    CloseDashboard,
+    OpenSlowlog,
+    CloseSlowlog,
+    ResetSlowlog,
+    OpenMonitor,
+    CloseMonitor,
+    FilterMonitor(String),
+    OpenPubSub,
+    ClosePubSub,
+    // channels, patterns
+    Subscribe(Vec<String>, Vec<String>),
+    // channel, message
+    Publish(String, String),
+    ToggleTreeMode,
+    // row of the query result
+    ToggleTreeNode(usize),
+    // expand (true) or collapse every node
+    ExpandTree(bool),
+    // content of the focused popup editor, edited in $VISUAL/$EDITOR
+    EditExternally(Vec<String>),
+    // script body, KEYS, ARGV
+    EvalScript(String, Vec<String>, Vec<String>),
+    OpenFunctions,
+    CloseFunctions,
+    // library code, replace existing library
+    LoadFunction(String, bool),
+    // library name
+    DeleteFunction(String),
+    // WATCH keys
+    BeginTransaction(Vec<String>),
+    ExecTransaction,
+    DiscardTransaction,
     None,
 }
 
@@ -40,14 +134,28 @@ pub enum AppEvent {
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub enum Id {
     ConnectionsList,
-    DbObjects,
+    // components of the session with the given id
+    DbObjects(usize),
     QueryLine,
-    QueryResult,
+    QueryResult(usize),
+    CompareResult(usize),
+    StatusLine,
+    SessionTabs,
+    Dashboard,
+    Slowlog,
+    Monitor,
+    PubSub,
+    Functions,
 }
 
 pub enum Page {
     Connections,
-    Query
+    Query,
+    Dashboard,
+    Slowlog,
+    Monitor,
+    PubSub,
+    Functions,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -55,5 +163,43 @@ pub enum EditorType {
     Search,
     Query,
     AddDbObject,
+    DeleteDbObject(String, bool),
+    RenameDbObject(String),
+    CopyDbObject(String),
+    ExpireDbObject(String),
+    // connection name, message to send once the name is typed
+    ConfirmQuery(String, Box<Msg>),
+    // connection, secret name, message to send once the secret is entered
+    Secret(usize, String, Box<Msg>),
+    ConnectionForm(Option<usize>, Connection),
+    FuzzyFind(String),
+    DeleteConnection(usize, String),
+    SplitView,
+    // left and right connection names
+    KeyspaceDiff(String, String),
+    ExportResult,
+    // MATCH pattern
+    CopyKeys(String),
+    ResetSlowlog,
+    // current filter
+    MonitorFilter(String),
+    Subscribe,
+    // channel
+    Publish(String),
+    Script,
+    // library code, replace existing library
+    LoadFunction(String, bool),
+    // library name
+    DeleteFunction(String),
+    BeginTransaction,
 }
 
+
+pub fn environment_color(environment: &Option<Environment>) -> Color {
+    match environment {
+        Some(Environment::Dev) => Color::Green,
+        Some(Environment::Staging) => Color::Magenta,
+        Some(Environment::Prod) => Color::Red,
+        None => Color::Yellow,
+    }
+}
@@ -1,9 +1,9 @@
-use std::{cmp::min, collections::HashMap, time::Duration, usize};
-use ratatui::layout::{Constraint, Direction, Rect};
-use tuirealm::{props::Layout, terminal::{CrosstermTerminalAdapter, TerminalAdapter, TerminalBridge}, Application, AttrValue, Attribute, EventListenerCfg, PollStrategy, Update};
-use crate::{config::{Config, Connection}, dbclient::{dummy::DummyFetcher, fetcher::{FetchRequest, Fetcher}, query_builder::QueryElement, redis::{RedisConfig, RedisFetcher}}, ui3::{connections_list::ConnectionsListComponent, db_objects::DbObjects, editor_popup::EditorPopup, query_result::QueryResult}};
+use std::{cmp::min, collections::{HashMap, HashSet}, fs, io::{self, Write}, os::unix::fs::OpenOptionsExt, path::PathBuf, process::Command, time::Duration};
+use ratatui::{layout::{Alignment, Constraint, Direction, Rect}, style::Color};
+use tuirealm::{props::{BorderType, Borders, Layout, PropPayload, PropValue, TextSpan}, terminal::{CrosstermTerminalAdapter, TerminalAdapter, TerminalBridge}, Application, AttrValue, Attribute, EventListenerCfg, PollStrategy, Update};
+use crate::{config::{secrets::SecretError, Config, Connection, ConnectionType, Environment}, dbclient::{dummy::DummyFetcher, fetcher::{FetchRequest, FetchResult, Fetcher, FetcherError, Reply}, query_builder::QueryElement, redis::{cluster::{self, RedisClusterFetcher}, commands::{self, CommandDoc}, diff, migrate, pubsub, resp3_uri, scripting, slowlog, RedisConfig, RedisFetcher}}, ui3::{completion::Completions, connections_list::ConnectionsListComponent, dashboard::Dashboard, db_objects::DbObjects, editor_popup::EditorPopup, functions::FunctionsView, highlight::Syntax, monitor::MonitorView, pubsub::PubSubView, query_result::QueryResult, session::{Compare, Session, Transaction}, session_tabs::SessionTabs, slowlog::SlowlogView, status_line::StatusLine}};
 
-use super::{AppEvent, Id, Msg, Page, APP_SEARCH_PATTERN};
+use super::{environment_color, AppEvent, EditorType, Id, Msg, Page, APP_CONNECTIONS_ROWS, APP_EDITOR_CONTENT, APP_LIST_INDEX, APP_SEARCH_PATTERN, APP_SUBSCRIBE_CHANNELS, APP_SUBSCRIBE_PATTERNS};
 
 
 pub struct Model<TermAdapter>
@@ -16,10 +16,19 @@ where
     pub terminal: TerminalBridge<TermAdapter>,
     pub selected_page: Page,
 
+    pub config_path: Option<PathBuf>,
     pub connections: Vec<Connection>,
 
-    pub fetcher: Option<Box<dyn Fetcher>>,
-    pub query_page_selected_widget: Id,
+    pub sessions: Vec<Session>,
+    pub active_session: usize,
+    pub next_session_id: usize,
+    pub secrets: HashMap<usize, HashMap<String, String>>,
+    // Last "test connection" result by connection name
+    pub health: HashMap<String, bool>,
+    pub connections_filter: String,
+    pub collapsed_groups: HashSet<String>,
+    // COMMAND DOCS by connection name, fetched when the query editor is first opened
+    pub command_docs: HashMap<String, Vec<CommandDoc>>,
     pub show_editor: bool,
 }
 
@@ -40,8 +49,8 @@ impl Model<CrosstermTerminalAdapter> {
         );
 
         assert!(app.mount(Id::ConnectionsList, Box::<ConnectionsListComponent>::default(), vec![]).is_ok());
-        assert!(app.mount(Id::DbObjects, Box::<DbObjects>::default(), vec![]).is_ok());
-        assert!(app.mount(Id::QueryResult, Box::<QueryResult>::default(), vec![]).is_ok());
+        assert!(app.mount(Id::SessionTabs, Box::<SessionTabs>::default(), vec![]).is_ok());
+        assert!(app.mount(Id::StatusLine, Box::<StatusLine>::default(), vec![]).is_ok());
 
         assert!(app.active(&Id::ConnectionsList).is_ok());
 
@@ -50,10 +59,17 @@ impl Model<CrosstermTerminalAdapter> {
             quit,
             redraw,
             terminal,
+            config_path: config.path.clone(),
             connections: config.connections.clone(),
             selected_page: Page::Connections,
-            fetcher: None,
-            query_page_selected_widget: Id::DbObjects,
+            sessions: vec![],
+            active_session: 0,
+            next_session_id: 0,
+            secrets: HashMap::new(),
+            health: HashMap::new(),
+            connections_filter: String::new(),
+            collapsed_groups: HashSet::new(),
+            command_docs: HashMap::new(),
             show_editor: false,
         }
     }
@@ -71,29 +87,83 @@ impl Model<CrosstermTerminalAdapter> {
                             .constraints(
                                 [
                                     Constraint::Fill(1),
+                                    Constraint::Length(1),
                                 ].as_ref(),
                             ).chunks(f.area());
                         self.app.view(&Id::ConnectionsList, f, chunks[0]);
+                        self.app.view(&Id::StatusLine, f, chunks[1]);
+                        if self.show_editor {
+                            self.app.view(&Id::QueryLine, f, Self::centered_rect(80, 20, f.area()));
+                        }
                     }).is_ok()
                 );
                 self.reload_connections();
             },
             Page::Query => {
+                let session_ids = self.session().map(|session| {
+                    let compare_id = session.compare.as_ref().map(|_| session.compare_result_id());
+                    (session.db_objects_id(), session.query_result_id(), compare_id)
+                });
                 assert!(self
                     .terminal
                     .raw_mut()
                     .draw(|f| {
+                        let rows = Layout::default()
+                            .direction(Direction::Vertical)
+                            .margin(1)
+                            .constraints(
+                                [
+                                    Constraint::Length(1),
+                                    Constraint::Fill(1),
+                                    Constraint::Length(1),
+                                ].as_ref(),
+                            ).chunks(f.area());
+                        let constraints = match session_ids {
+                            Some((_, _, Some(_))) => vec![Constraint::Fill(1), Constraint::Fill(2), Constraint::Fill(2)],
+                            _ => vec![Constraint::Fill(1), Constraint::Fill(4)],
+                        };
                         let chunks = Layout::default()
                             .direction(Direction::Horizontal)
+                            .constraints(&constraints)
+                            .chunks(rows[1]);
+                        self.app.view(&Id::SessionTabs, f, rows[0]);
+                        if let Some((db_objects_id, query_result_id, compare_id)) = &session_ids {
+                            self.app.view(db_objects_id, f, chunks[0]);
+                            self.app.view(query_result_id, f, chunks[1]);
+                            if let Some(compare_id) = compare_id {
+                                self.app.view(compare_id, f, chunks[2]);
+                            }
+                        }
+                        self.app.view(&Id::StatusLine, f, rows[2]);
+                        if self.show_editor {
+                            self.app.view(&Id::QueryLine, f, Self::centered_rect(80, 20, f.area()));
+                        }
+                    }).is_ok()
+                );
+            },
+            Page::Dashboard | Page::Slowlog | Page::Monitor | Page::PubSub | Page::Functions => {
+                let id = match self.selected_page {
+                    Page::Dashboard => Id::Dashboard,
+                    Page::Slowlog => Id::Slowlog,
+                    Page::Monitor => Id::Monitor,
+                    Page::Functions => Id::Functions,
+                    _ => Id::PubSub,
+                };
+                assert!(self
+                    .terminal
+                    .raw_mut()
+                    .draw(|f| {
+                        let rows = Layout::default()
+                            .direction(Direction::Vertical)
                             .margin(1)
                             .constraints(
                                 [
                                     Constraint::Fill(1),
-                                    Constraint::Fill(4),
+                                    Constraint::Length(1),
                                 ].as_ref(),
                             ).chunks(f.area());
-                        self.app.view(&Id::DbObjects, f, chunks[0]);
-                        self.app.view(&Id::QueryResult, f, chunks[1]);
+                        self.app.view(&id, f, rows[0]);
+                        self.app.view(&Id::StatusLine, f, rows[1]);
                         if self.show_editor {
                             self.app.view(&Id::QueryLine, f, Self::centered_rect(80, 20, f.area()));
                         }
@@ -137,35 +207,335 @@ impl Model<CrosstermTerminalAdapter> {
     }
 
     fn reload_connections(&mut self) -> Option<Msg> {
+        let rows = ConnectionsListComponent::build_connections_rows(&self.connections, &self.connections_filter, &self.collapsed_groups);
         assert!(
             self.app
                 .attr(
                     &Id::ConnectionsList,
                     Attribute::Content,
-                    AttrValue::Table(ConnectionsListComponent::build_connections_table(&self.connections))).is_ok()
+                    AttrValue::Table(ConnectionsListComponent::build_connections_table(&self.connections, &rows, &self.collapsed_groups, &self.health))).is_ok()
+        );
+        assert!(
+            self.app
+                .attr(
+                    &Id::ConnectionsList,
+                    Attribute::Custom(APP_CONNECTIONS_ROWS),
+                    ConnectionsListComponent::build_connections_rows_payload(&rows)).is_ok()
         );
         Some(Msg::None)
     }
 
+    fn filter_connections(&mut self, filter: String) -> Option<Msg> {
+        let title = if filter.trim().is_empty() {
+            "Available connections".to_string()
+        } else {
+            format!("Available connections, filter: {}", filter)
+        };
+        assert!(self.app.attr(&Id::ConnectionsList, Attribute::Title, AttrValue::Title((title, Alignment::Left))).is_ok());
+        assert!(self.app.attr(&Id::ConnectionsList, Attribute::Custom(APP_SEARCH_PATTERN), AttrValue::String(filter.clone())).is_ok());
+        // the rows change, select the best match instead of whatever ends up at the old index
+        assert!(self.app.attr(&Id::ConnectionsList, Attribute::Value, AttrValue::Payload(PropPayload::One(PropValue::Usize(0)))).is_ok());
+        self.connections_filter = filter;
+        None
+    }
+
+    fn toggle_connections_group(&mut self, group: String) -> Option<Msg> {
+        if !self.collapsed_groups.remove(&group) {
+            self.collapsed_groups.insert(group);
+        }
+        None
+    }
+
     fn reload_query_result(&mut self, request: &FetchRequest) -> Option<Msg> {
-        if let Some(ref mut fetcher) = self.fetcher {
-            let result = fetcher.fetch(request).unwrap();
-            assert!(
-                self.app.attr(
-                    &Id::QueryResult,
-                    Attribute::Content,
-                    AttrValue::Table(QueryResult::build_result_table(result))).is_ok()
-            );
+        let mut attributes = vec![];
+        if let Some(session) = self.sessions.get_mut(self.active_session) {
+            let result = session.fetcher.fetch(request).unwrap_or_else(|err| FetchResult::error(&err));
+            attributes = result.reply.as_ref().map(Reply::attributes).unwrap_or_default();
+            session.last_request = Some(request.clone());
+            session.collapsed.clear();
+            match session.compare {
+                Some(ref mut compare) => {
+                    session.reply = None;
+                    session.tree_mode = false;
+                    let other = compare.fetcher.fetch(request).unwrap_or_else(|err| FetchResult::error(&err));
+                    assert!(
+                        self.app.attr(
+                            &session.query_result_id(),
+                            Attribute::Content,
+                            AttrValue::Table(QueryResult::build_compared_table(&result, &other))).is_ok()
+                    );
+                    assert!(
+                        self.app.attr(
+                            &session.compare_result_id(),
+                            Attribute::Content,
+                            AttrValue::Table(QueryResult::build_compared_table(&other, &result))).is_ok()
+                    );
+                },
+                None => {
+                    // nested replies open as a tree, `t` switches to the table
+                    session.reply = result.reply.clone().filter(Reply::is_nested);
+                    session.tree_mode = session.reply.as_ref().is_some_and(Reply::has_nested_items);
+                    let table = match (&session.reply, session.tree_mode) {
+                        (Some(reply), true) => QueryResult::build_tree_table(&reply.tree(&session.collapsed), &session.collapsed),
+                        _ => QueryResult::build_result_table(result),
+                    };
+                    assert!(self.app.attr(&session.query_result_id(), Attribute::Content, AttrValue::Table(table)).is_ok());
+                },
+            }
+        }
+        if !attributes.is_empty() {
+            let attributes: Vec<String> = attributes.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+            self.show_status(format!("Reply attributes: {}", attributes.join(", ")), Color::Gray);
         }
         Some(Msg::FetchDbObjects)
     }
 
+    fn reload_reply(&mut self) -> Option<Msg> {
+        let session = match self.sessions.get(self.active_session) {
+            Some(session) => session,
+            None => return Some(Msg::None),
+        };
+        let reply = match &session.reply {
+            Some(reply) => reply,
+            None => return Some(Msg::ShowStatus("The last reply has no nested values".to_string())),
+        };
+        let table = if session.tree_mode {
+            QueryResult::build_tree_table(&reply.tree(&session.collapsed), &session.collapsed)
+        } else {
+            QueryResult::build_result_table(FetchResult::from_reply(reply.clone()))
+        };
+        assert!(self.app.attr(&session.query_result_id(), Attribute::Content, AttrValue::Table(table)).is_ok());
+        None
+    }
+
+    fn toggle_tree_node(&mut self, row: usize) -> Option<Msg> {
+        let session = match self.sessions.get_mut(self.active_session) {
+            Some(session) if session.tree_mode => session,
+            _ => return None,
+        };
+        // the first row is the header
+        let path = session.reply.as_ref()
+            .and_then(|reply| reply.tree(&session.collapsed).into_iter().nth(row.checked_sub(1)?))
+            .filter(|row| row.children > 0)
+            .map(|row| row.path);
+        match path {
+            Some(path) => {
+                if !session.collapsed.remove(&path) {
+                    session.collapsed.insert(path);
+                }
+                self.reload_reply()
+            },
+            None => None,
+        }
+    }
+
+    fn expand_tree(&mut self, expand: bool) -> Option<Msg> {
+        let session = match self.sessions.get_mut(self.active_session) {
+            Some(session) if session.tree_mode => session,
+            _ => return None,
+        };
+        session.collapsed = match (&session.reply, expand) {
+            (Some(reply), false) => reply.tree(&HashSet::new()).into_iter()
+                .filter(|row| row.children > 0)
+                .map(|row| row.path)
+                .collect(),
+            _ => HashSet::new(),
+        };
+        self.reload_reply()
+    }
+
+    fn show_status(&mut self, text: String, color: Color) -> Option<Msg> {
+        assert!(self.app.attr(&Id::StatusLine, Attribute::Text, AttrValue::String(text)).is_ok());
+        assert!(self.app.attr(&Id::StatusLine, Attribute::Foreground, AttrValue::Color(color)).is_ok());
+        None
+    }
+
+    fn open_connection_form(&mut self, selected_connection: Option<usize>, duplicate: bool) -> Option<Msg> {
+        let connection = match selected_connection.and_then(|index| self.connections.get(index)) {
+            Some(connection) => connection.clone(),
+            None if selected_connection.is_some() => return Some(Msg::None),
+            None => Connection {
+                connection_type: ConnectionType::Redis,
+                name: String::new(),
+                connection_string: String::new(),
+                password_file: None,
+                password_command: None,
+                read_only: false,
+                resp3: false,
+                environment: None,
+                group: None,
+                tags: vec![],
+                source: None,
+            },
+        };
+        if duplicate {
+            // a new connection, saved to the main config file
+            let connection = Connection { name: format!("{} copy", connection.name), source: None, ..connection };
+            return Some(Msg::ActivateEditor(EditorType::ConnectionForm(None, connection)));
+        }
+        Some(Msg::ActivateEditor(EditorType::ConnectionForm(selected_connection, connection)))
+    }
+
+    fn connection_from_form(&self, selected_connection: Option<usize>, original: Connection, editors: &HashMap<&'static str, Vec<String>>) -> Result<Connection, String> {
+        let field = |name: &str| editors.get(name).map(|lines| lines.join("\n")).unwrap_or_default().trim().to_string();
+
+        let name = field("name");
+        if name.is_empty() {
+            return Err("Connection name can't be empty".to_string());
+        }
+        let duplicate = self.connections.iter().enumerate()
+            .any(|(index, connection)| Some(index) != selected_connection && connection.name == name);
+        if duplicate {
+            return Err(format!("Connection '{}' already exists", name));
+        }
+
+        let connection_type = ConnectionType::try_from(field("type").as_str())?;
+
+        let connection_string = field("connection_string");
+        if connection_string.is_empty() {
+            return Err("Connection string can't be empty".to_string());
+        }
+
+        let environment = match field("environment").as_str() {
+            "" => None,
+            environment => Some(Environment::try_from(environment)?),
+        };
+
+        let password_file = Some(field("password_file")).filter(|path| !path.is_empty()).map(PathBuf::from);
+        let password_command = Some(field("password_command")).filter(|command| !command.is_empty());
+        if password_file.is_some() && password_command.is_some() {
+            return Err("Set either a password file or a password command".to_string());
+        }
+
+        let group = Some(field("group")).filter(|group| !group.is_empty());
+        let tags = field("tags").split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+
+        Ok(Connection {
+            connection_type,
+            name,
+            connection_string,
+            password_file,
+            password_command,
+            environment,
+            group,
+            tags,
+            read_only: Self::is_confirmed(&field("read_only")),
+            resp3: Self::is_confirmed(&field("resp3")),
+            ..original
+        })
+    }
+
+    fn save_connection(&mut self, selected_connection: Option<usize>, mut connection: Connection) -> Option<Msg> {
+        let path = match connection.source.clone().or_else(|| self.config_path.clone()) {
+            Some(path) => path,
+            None => return Some(Msg::ShowError("No config file to save the connection to".to_string())),
+        };
+        let original_name = selected_connection
+            .and_then(|index| self.connections.get(index))
+            .map(|connection| connection.name.clone());
+        if let Err(err) = Config::save_connection(&path, original_name.as_deref(), &connection) {
+            return Some(Msg::ShowError(err.to_string()));
+        }
+
+        if let Some(original_name) = &original_name {
+            self.health.remove(original_name);
+            self.command_docs.remove(original_name);
+        }
+
+        let status = format!("Connection '{}' saved to {}", connection.name, path.display());
+        connection.source = Some(path);
+        match selected_connection {
+            Some(index) => {
+                self.connections[index] = connection;
+                self.secrets.remove(&index);
+                self.reload_session_tabs();
+                if let Some(msg) = self.rebuild_fetchers(index) {
+                    return Some(msg);
+                }
+            },
+            None => self.connections.push(connection),
+        }
+        Some(Msg::ShowStatus(status))
+    }
+
+    // Open sessions and split views of an edited connection still hold a fetcher built from the old settings
+    fn rebuild_fetchers(&mut self, selected_connection: usize) -> Option<Msg> {
+        for index in 0..self.sessions.len() {
+            if self.sessions[index].connection == selected_connection {
+                match self.build_fetcher(selected_connection, false, Msg::RebuildFetchers(selected_connection)) {
+                    Ok(fetcher) => self.sessions[index].fetcher = fetcher,
+                    Err(msg) => return Some(msg),
+                }
+            }
+            if self.sessions[index].compare.as_ref().is_some_and(|compare| compare.connection == selected_connection) {
+                match self.build_fetcher(selected_connection, true, Msg::RebuildFetchers(selected_connection)) {
+                    Ok(fetcher) => self.sessions[index].compare.as_mut().unwrap().fetcher = fetcher,
+                    Err(msg) => return Some(msg),
+                }
+            }
+        }
+        None
+    }
+
+    fn remove_connection(&mut self, selected_connection: usize) -> Option<Msg> {
+        let connection = match self.connections.get(selected_connection) {
+            Some(connection) => connection,
+            None => return Some(Msg::None),
+        };
+        let path = match connection.source.clone().or_else(|| self.config_path.clone()) {
+            Some(path) => path,
+            None => return Some(Msg::ShowError("No config file to remove the connection from".to_string())),
+        };
+        if let Err(err) = Config::remove_connection(&path, &connection.name) {
+            return Some(Msg::ShowError(err.to_string()));
+        }
+
+        let connection = self.connections.remove(selected_connection);
+        self.health.remove(&connection.name);
+        self.close_connection_sessions(selected_connection);
+        self.secrets = std::mem::take(&mut self.secrets).into_iter()
+            .filter(|(index, _)| *index != selected_connection)
+            .map(|(index, secrets)| if index > selected_connection { (index - 1, secrets) } else { (index, secrets) })
+            .collect();
+        Some(Msg::ShowStatus(format!("Connection '{}' removed from {}", connection.name, path.display())))
+    }
+
+    fn guard_query(&mut self, request: FetchRequest) -> Option<Msg> {
+        let connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        // a script on a writable connection may change anything, read-only ones run it with EVALSHA_RO
+        let read_only = self.connections[connection].read_only;
+        let destructive = request.query.iter()
+            .any(|element| element.is_destructive() || (!read_only && matches!(element, QueryElement::EvalScript(..))));
+        let then = Msg::ExecuteConfirmedQuery(request);
+        if destructive {
+            Some(self.confirm_on_prod(connection, then))
+        } else {
+            Some(then)
+        }
+    }
+
+    // Destructive actions on prod connections run once the connection name is typed
+    fn confirm_on_prod(&self, selected_connection: usize, then: Msg) -> Msg {
+        match self.connections.get(selected_connection) {
+            Some(connection) if connection.environment == Some(Environment::Prod) => {
+                Msg::ActivateEditor(EditorType::ConfirmQuery(connection.name.clone(), Box::new(then)))
+            },
+            _ => then,
+        }
+    }
+
     fn fetch_db_object(&mut self, object: String) -> Option<Msg> {
         let query = FetchRequest {
             query: vec![QueryElement::ListAllItemsFrom(object)],
             limit: usize::MAX
         };
-        return Some(Msg::ExecuteQuery(query));
+        Some(Msg::ExecuteQuery(query))
     }
 
     fn add_db_object(&mut self, path: String, object_type: String, name: String) -> Option<Msg> {
@@ -173,70 +543,816 @@ impl Model<CrosstermTerminalAdapter> {
             query: vec![QueryElement::AddDatabaseObject(path, object_type, name)],
             limit: usize::MAX
         };
-        return Some(Msg::ExecuteQuery(query));
+        Some(Msg::ExecuteQuery(query))
+    }
+
+    fn delete_db_object(&mut self, name: String, lazy: bool) -> Option<Msg> {
+        let query = FetchRequest {
+            query: vec![QueryElement::DeleteDatabaseObject(name, lazy)],
+            limit: usize::MAX
+        };
+        Some(Msg::ExecuteQuery(query))
+    }
+
+    fn rename_db_object(&mut self, name: String, new_name: String, overwrite: bool) -> Option<Msg> {
+        let query = FetchRequest {
+            query: vec![QueryElement::RenameDatabaseObject(name, new_name, overwrite)],
+            limit: usize::MAX
+        };
+        Some(Msg::ExecuteQuery(query))
+    }
+
+    fn copy_db_object(&mut self, name: String, destination: String, database: Option<String>, overwrite: bool) -> Option<Msg> {
+        let query = FetchRequest {
+            query: vec![QueryElement::CopyDatabaseObject(name, destination, database, overwrite)],
+            limit: usize::MAX
+        };
+        Some(Msg::ExecuteQuery(query))
+    }
+
+    fn expire_db_object(&mut self, name: String, ttl: Option<u64>) -> Option<Msg> {
+        let query = FetchRequest {
+            query: vec![QueryElement::ExpireDatabaseObject(name, ttl)],
+            limit: usize::MAX
+        };
+        Some(Msg::ExecuteQuery(query))
+    }
+
+    fn is_confirmed(answer: &str) -> bool {
+        matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
     }
 
     fn execute_custom_query(&mut self, query: String) -> Option<Msg> {
+        if let Some(session) = self.session_mut() {
+            session.push_history(query.clone());
+        }
         let query = FetchRequest {
             query: vec![QueryElement::RawQuery(query)],
             limit: usize::MAX
         };
-        return Some(Msg::ExecuteQuery(query));
+        Some(Msg::ExecuteQuery(query))
     }
 
     fn search_pattern(&mut self, pattern: String) -> Option<Msg> {
-        match self.query_page_selected_widget {
-            Id::ConnectionsList => {
-                assert!(self.app.attr(&Id::ConnectionsList, Attribute::Custom(APP_SEARCH_PATTERN), AttrValue::String(pattern)).is_ok());
-                Some(Msg::None)
-            },
-            Id::DbObjects => {
-                assert!(self.app.attr(&Id::DbObjects, Attribute::Custom(APP_SEARCH_PATTERN), AttrValue::String(pattern)).is_ok());
-                Some(Msg::None)
-            },
-            Id::QueryLine => {
-                assert!(self.app.attr(&Id::QueryLine, Attribute::Custom(APP_SEARCH_PATTERN), AttrValue::String(pattern)).is_ok());
-                Some(Msg::None)
-            },
-            Id::QueryResult => {
-                assert!(self.app.attr(&Id::QueryResult, Attribute::Custom(APP_SEARCH_PATTERN), AttrValue::String(pattern)).is_ok());
-                Some(Msg::None)
-            },
+        if let Some(widget) = self.session().map(|session| session.selected_widget.clone()) {
+            assert!(self.app.attr(&widget, Attribute::Custom(APP_SEARCH_PATTERN), AttrValue::String(pattern)).is_ok());
         }
+        Some(Msg::None)
     }
 
     fn reload_db_objects(&mut self) -> Option<Msg> {
-        if let Some(ref mut fetcher) = self.fetcher {
-            let result = fetcher.fetch_db_objects().unwrap();
-            let result = result.table.unwrap_or((vec![], HashMap::default()));
-            let list = result.1.iter().last();
+        if let Some(session) = self.sessions.get_mut(self.active_session) {
+            let result = match session.fetcher.fetch_db_objects() {
+                Ok(result) => result,
+                Err(err) => return Some(Msg::ShowError(err.to_string())),
+            };
+            let (index, columns) = result.table.unwrap_or((vec![], HashMap::default()));
+            let objects = index.first().and_then(|column| columns.get(column)).cloned().unwrap_or_default();
             assert!(
                 self.app.attr(
-                    &Id::DbObjects,
+                    &session.db_objects_id(),
                     Attribute::Content,
-                    AttrValue::Table(DbObjects::build_objects_list(list.unwrap_or((&"".to_string(), &vec![])).1))).is_ok()
+                    AttrValue::Table(DbObjects::build_objects_list(&objects, columns.get("node")))).is_ok()
             );
         }
         Some(Msg::None)
     }
 
-    fn init_fetcher(&mut self, selected_connection: usize) -> Option<Msg> {
-
-        let connection = self.connections.get(selected_connection).unwrap();
+    #[allow(clippy::result_large_err)]
+    fn build_fetcher(&self, selected_connection: usize, force_read_only: bool, then: Msg) -> Result<Box<dyn Fetcher>, Msg> {
+        let connection = match self.connections.get(selected_connection) {
+            Some(connection) => connection,
+            None => return Err(Msg::None),
+        };
         let fetcher: Box<dyn Fetcher> = match connection.connection_type {
             crate::config::ConnectionType::Redis => {
-                Box::new(RedisFetcher {
-                    config: RedisConfig { uri: connection.connection_string.clone() }
-                })
+                let mut config = self.build_redis_config(selected_connection, then)?;
+                config.read_only |= force_read_only;
+                Box::new(RedisFetcher::new(config))
+            },
+            crate::config::ConnectionType::RedisCluster => {
+                let mut config = self.build_connection_config(selected_connection, then)?;
+                config.read_only |= force_read_only;
+                Box::new(RedisClusterFetcher::new(config))
             },
             crate::config::ConnectionType::Postgres => Box::new(DummyFetcher::new()),
             crate::config::ConnectionType::MySql => Box::new(DummyFetcher::new()),
         };
+        Ok(fetcher)
+    }
 
-        self.selected_page = Page::Query;
-        self.fetcher = Some(fetcher);
+    // Tools that talk to a single server use the first seed node of a cluster
+    #[allow(clippy::result_large_err)]
+    fn build_redis_config(&self, selected_connection: usize, then: Msg) -> Result<RedisConfig, Msg> {
+        let config = self.build_connection_config(selected_connection, then)?;
+        match self.connections[selected_connection].connection_type {
+            ConnectionType::RedisCluster => Ok(RedisConfig { uri: cluster::seed_uris(&config.uri).into_iter().next().unwrap_or_default(), ..config }),
+            _ => Ok(config),
+        }
+    }
+
+    // Keeps every seed node of a cluster connection string
+    #[allow(clippy::result_large_err)]
+    fn build_connection_config(&self, selected_connection: usize, then: Msg) -> Result<RedisConfig, Msg> {
+        let connection = match self.connections.get(selected_connection) {
+            Some(connection) => connection,
+            None => return Err(Msg::None),
+        };
+        if !connection.connection_type.is_redis() {
+            return Err(Msg::ShowError(format!("Connection '{}' is not a Redis connection", connection.name)));
+        }
+        let secrets = self.secrets.get(&selected_connection).cloned().unwrap_or_default();
+        match connection.resolve_connection_string(&secrets) {
+            Ok(uri) if connection.resp3 && connection.connection_type == ConnectionType::RedisCluster => Ok(RedisConfig {
+                uri: cluster::seed_uris(&uri).iter().map(|seed| resp3_uri(seed)).collect::<Vec<_>>().join(","),
+                read_only: connection.read_only,
+            }),
+            Ok(uri) if connection.resp3 => Ok(RedisConfig { uri: resp3_uri(&uri), read_only: connection.read_only }),
+            Ok(uri) => Ok(RedisConfig { uri, read_only: connection.read_only }),
+            Err(SecretError::Missing(secret)) => Err(Msg::ActivateEditor(EditorType::Secret(selected_connection, secret, Box::new(then)))),
+            Err(err @ SecretError::Failed(_)) => Err(Msg::ShowError(format!("Connection '{}': {}", connection.name, err))),
+        }
+    }
+
+    // Diff and copy scan a single server, the first seed would only hold the keys of its own slots
+    fn reject_cluster(&self, connections: &[usize], tool: &str) -> Option<Msg> {
+        connections.iter()
+            .filter_map(|index| self.connections.get(*index))
+            .find(|connection| connection.connection_type == ConnectionType::RedisCluster)
+            .map(|connection| Msg::ShowError(format!("{} is not supported for cluster connection '{}'", tool, connection.name)))
+    }
+
+    fn open_keyspace_diff(&mut self) -> Option<Msg> {
+        let session = match self.session() {
+            Some(session) => session,
+            None => return Some(Msg::None),
+        };
+        let left = self.connections[session.connection].name.clone();
+        let right = session.compare.as_ref()
+            .map(|compare| self.connections[compare.connection].name.clone())
+            .unwrap_or_default();
+        Some(Msg::ActivateEditor(EditorType::KeyspaceDiff(left, right)))
+    }
+
+    fn diff_keyspace(&mut self, left: usize, right: usize, pattern: String) -> Option<Msg> {
+        if let Some(msg) = self.reject_cluster(&[left, right], "Keyspace diff") {
+            return Some(msg);
+        }
+        let then = Msg::DiffKeyspace(left, right, pattern.clone());
+        let left_config = match self.build_redis_config(left, then.clone()) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        let right_config = match self.build_redis_config(right, then) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        let diff = match diff::diff_keyspace(&left_config, &right_config, &pattern) {
+            Ok(diff) => diff,
+            Err(err) => return Some(Msg::ShowError(err.to_string())),
+        };
+        if let Some(id) = self.session().map(Session::query_result_id) {
+            assert!(
+                self.app.attr(
+                    &id,
+                    Attribute::Content,
+                    AttrValue::Table(QueryResult::build_result_table(diff.to_fetch_result()))).is_ok()
+            );
+        }
+        Some(Msg::ShowStatus(format!("{} vs {}: {}", self.connections[left].name, self.connections[right].name, diff.summary())))
+    }
+
+    fn copy_keys(&mut self, destination: usize, pattern: String, replace: bool, dry_run: bool) -> Option<Msg> {
+        let source = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        if let Some(msg) = self.reject_cluster(&[source, destination], "Copying keys") {
+            return Some(msg);
+        }
+        let then = Msg::CopyKeys(destination, pattern.clone(), replace, dry_run);
+        let source_config = match self.build_redis_config(source, then.clone()) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        let destination_config = match self.build_redis_config(destination, then) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        let destination_name = self.connections[destination].name.clone();
+        let summary = migrate::copy_keys(&source_config, &destination_config, &pattern, replace, dry_run, &mut |done, total| {
+            self.show_status(format!("Copying to {}: {}/{} keys", destination_name, done, total), Color::Gray);
+            self.view();
+        });
+        let summary = match summary {
+            Ok(summary) => summary,
+            Err(err) => return Some(Msg::ShowError(err.to_string())),
+        };
+        if let Some(id) = self.session().map(Session::query_result_id) {
+            assert!(
+                self.app.attr(
+                    &id,
+                    Attribute::Content,
+                    AttrValue::Table(QueryResult::build_result_table(summary.to_fetch_result()))).is_ok()
+            );
+        }
+        Some(Msg::ShowStatus(format!("{} → {}: {}", self.connections[source].name, destination_name, summary.summary())))
+    }
+
+    fn open_dashboard(&mut self) -> Option<Msg> {
+        let selected_connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        let config = match self.build_redis_config(selected_connection, Msg::OpenDashboard) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        let dashboard = Dashboard::new(config, self.connections[selected_connection].name.clone());
+        assert!(self.app.remount(Id::Dashboard, Box::new(dashboard), vec![]).is_ok());
+        assert!(self.app.active(&Id::Dashboard).is_ok());
+        self.selected_page = Page::Dashboard;
+        None
+    }
+
+    fn open_slowlog(&mut self) -> Option<Msg> {
+        let selected_connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        let config = match self.build_redis_config(selected_connection, Msg::OpenSlowlog) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        assert!(self.app.remount(Id::Slowlog, Box::new(SlowlogView::new(config)), vec![]).is_ok());
+        assert!(self.app.active(&Id::Slowlog).is_ok());
+        self.selected_page = Page::Slowlog;
+        None
+    }
+
+    fn reset_slowlog(&mut self) -> Option<Msg> {
+        let selected_connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        let config = match self.build_redis_config(selected_connection, Msg::ResetSlowlog) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        if let Err(err) = slowlog::reset(&config) {
+            return Some(Msg::ShowError(err.to_string()));
+        }
+        // remounting reloads the emptied slow log
+        let msg = self.open_slowlog();
+        msg.or(Some(Msg::ShowStatus(format!("{}: slow log and latency events reset", self.connections[selected_connection].name))))
+    }
+
+    fn open_monitor(&mut self) -> Option<Msg> {
+        let selected_connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        let config = match self.build_redis_config(selected_connection, Msg::OpenMonitor) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        let monitor = MonitorView::new(config, self.connections[selected_connection].name.clone());
+        assert!(self.app.remount(Id::Monitor, Box::new(monitor), vec![]).is_ok());
+        assert!(self.app.active(&Id::Monitor).is_ok());
+        self.selected_page = Page::Monitor;
+        None
+    }
+
+    fn open_pubsub(&mut self) -> Option<Msg> {
+        let selected_connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        let config = match self.build_redis_config(selected_connection, Msg::OpenPubSub) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        let pubsub = PubSubView::new(config, self.connections[selected_connection].name.clone());
+        assert!(self.app.remount(Id::PubSub, Box::new(pubsub), vec![]).is_ok());
+        assert!(self.app.active(&Id::PubSub).is_ok());
+        self.selected_page = Page::PubSub;
+        None
+    }
+
+    fn subscribe(&mut self, channels: Vec<String>, patterns: Vec<String>) -> Option<Msg> {
+        let payload = |names: Vec<String>| AttrValue::Payload(PropPayload::Vec(names.into_iter().map(PropValue::Str).collect()));
+        assert!(self.app.attr(&Id::PubSub, Attribute::Custom(APP_SUBSCRIBE_CHANNELS), payload(channels)).is_ok());
+        assert!(self.app.attr(&Id::PubSub, Attribute::Custom(APP_SUBSCRIBE_PATTERNS), payload(patterns)).is_ok());
+        assert!(self.app.active(&Id::PubSub).is_ok());
+        None
+    }
+
+    fn publish(&mut self, channel: String, message: String) -> Option<Msg> {
+        let selected_connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        let config = match self.build_redis_config(selected_connection, Msg::Publish(channel.clone(), message.clone())) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        assert!(self.app.active(&Id::PubSub).is_ok());
+        match pubsub::publish(&config, &channel, &message) {
+            Ok(receivers) => Some(Msg::ShowStatus(format!("Published to '{}', {} receivers", channel, receivers))),
+            Err(err) => Some(Msg::ShowError(err.to_string())),
+        }
+    }
+
+    fn eval_script(&mut self, body: String, keys: Vec<String>, args: Vec<String>) -> Option<Msg> {
+        let connection = self.session().map(|session| &self.connections[session.connection]);
+        match connection {
+            Some(connection) if !connection.connection_type.is_redis() => {
+                Some(Msg::ShowError(format!("Connection '{}' is not a Redis connection", connection.name)))
+            },
+            Some(_) => Some(Msg::ExecuteQuery(FetchRequest {
+                query: vec![QueryElement::EvalScript(body, keys, args)],
+                limit: usize::MAX,
+            })),
+            None => Some(Msg::None),
+        }
+    }
+
+    fn open_functions(&mut self) -> Option<Msg> {
+        let selected_connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        let config = match self.build_redis_config(selected_connection, Msg::OpenFunctions) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        assert!(self.app.remount(Id::Functions, Box::new(FunctionsView::new(config)), vec![]).is_ok());
+        assert!(self.app.active(&Id::Functions).is_ok());
+        self.selected_page = Page::Functions;
+        None
+    }
+
+    fn load_function(&mut self, code: String, replace: bool) -> Option<Msg> {
+        let selected_connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        let config = match self.build_redis_config(selected_connection, Msg::LoadFunction(code.clone(), replace)) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        match scripting::load_function(&config, &code, replace) {
+            // remounting reloads the libraries
+            Ok(library) => self.open_functions().or(Some(Msg::ShowStatus(format!("Library '{}' loaded", library)))),
+            Err(err) => Some(Msg::ShowError(err.to_string())),
+        }
+    }
+
+    fn delete_function(&mut self, library: String) -> Option<Msg> {
+        let selected_connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Some(Msg::None),
+        };
+        let config = match self.build_redis_config(selected_connection, Msg::DeleteFunction(library.clone())) {
+            Ok(config) => config,
+            Err(msg) => return Some(msg),
+        };
+        match scripting::delete_function(&config, &library) {
+            Ok(()) => self.open_functions().or(Some(Msg::ShowStatus(format!("Library '{}' deleted", library)))),
+            Err(err) => Some(Msg::ShowError(err.to_string())),
+        }
+    }
+
+    // Commands starting, committing and rolling back a transaction on the connection
+    fn transaction_commands(connection_type: &ConnectionType) -> (&'static str, &'static str, &'static str) {
+        match connection_type {
+            ConnectionType::Redis | ConnectionType::RedisCluster => ("MULTI", "EXEC", "DISCARD"),
+            ConnectionType::Postgres | ConnectionType::MySql => ("BEGIN", "COMMIT", "ROLLBACK"),
+        }
+    }
+
+    fn begin_transaction(&mut self, watch: Vec<String>) -> Option<Msg> {
+        let session = match self.sessions.get_mut(self.active_session) {
+            Some(session) => session,
+            None => return Some(Msg::None),
+        };
+        if session.transaction.is_some() {
+            return Some(Msg::ShowError("A transaction is already in progress".to_string()));
+        }
+        if let Err(err) = session.fetcher.begin(&watch) {
+            return Some(Msg::ShowError(err.to_string()));
+        }
+        session.transaction = Some(Transaction { watch, queries: vec![] });
+        self.reload_transaction()
+    }
+
+    fn queue_query(&mut self, request: FetchRequest) -> Option<Msg> {
+        // opening objects and other generated queries run right away
+        let query = match request.query.as_slice() {
+            [QueryElement::RawQuery(query)] => query.clone(),
+            _ => return self.reload_query_result(&request),
+        };
+        if let Some(transaction) = self.session_mut().and_then(|session| session.transaction.as_mut()) {
+            transaction.queries.push(query);
+        }
+        self.reload_transaction()
+    }
+
+    // Shows the queued queries in the query result
+    fn reload_transaction(&mut self) -> Option<Msg> {
+        let session = match self.sessions.get_mut(self.active_session) {
+            Some(session) => session,
+            None => return Some(Msg::None),
+        };
+        let transaction = match &session.transaction {
+            Some(transaction) => transaction,
+            None => return None,
+        };
+        session.reply = None;
+        session.tree_mode = false;
+        let table = QueryResult::build_transaction_table(&transaction.queries, None);
+        assert!(self.app.attr(&session.query_result_id(), Attribute::Content, AttrValue::Table(table)).is_ok());
+        let (begin, commit, rollback) = Self::transaction_commands(&self.connections[session.connection].connection_type);
+        let watch = if transaction.watch.is_empty() { String::new() } else { format!(", watching {}", transaction.watch.join(" ")) };
+        Some(Msg::ShowStatus(format!("{}: {} queued{}, X to {}, R to {}", begin, transaction.queries.len(), watch, commit, rollback)))
+    }
+
+    fn exec_transaction(&mut self) -> Option<Msg> {
+        let session = match self.sessions.get_mut(self.active_session) {
+            Some(session) => session,
+            None => return Some(Msg::None),
+        };
+        let transaction = match session.transaction.take() {
+            Some(transaction) => transaction,
+            None => return Some(Msg::ShowError("No transaction in progress".to_string())),
+        };
+        let (_, commit, _) = Self::transaction_commands(&self.connections[session.connection].connection_type);
+        match session.fetcher.commit(&transaction.queries) {
+            Ok(Some(results)) => {
+                let table = QueryResult::build_transaction_table(&transaction.queries, Some(&results));
+                assert!(self.app.attr(&session.query_result_id(), Attribute::Content, AttrValue::Table(table)).is_ok());
+                self.show_status(format!("{}: {} commands run", commit, results.len()), Color::Gray);
+                Some(Msg::FetchDbObjects)
+            },
+            Ok(None) => Some(Msg::ShowError(format!("{}: transaction aborted, a watched key changed ({})", commit, transaction.watch.join(" ")))),
+            Err(err) => Some(Msg::ShowError(format!("{}: {}", commit, err))),
+        }
+    }
+
+    fn discard_transaction(&mut self) -> Option<Msg> {
+        let session = match self.sessions.get_mut(self.active_session) {
+            Some(session) => session,
+            None => return Some(Msg::None),
+        };
+        let transaction = match session.transaction.take() {
+            Some(transaction) => transaction,
+            None => return Some(Msg::ShowError("No transaction in progress".to_string())),
+        };
+        let (_, _, rollback) = Self::transaction_commands(&self.connections[session.connection].connection_type);
+        if let Err(err) = session.fetcher.rollback() {
+            return Some(Msg::ShowError(err.to_string()));
+        }
+        assert!(self.app.attr(&session.query_result_id(), Attribute::Content, AttrValue::Table(QueryResult::build_result_table(FetchResult::none()))).is_ok());
+        Some(Msg::ShowStatus(format!("{}: {} queued commands dropped", rollback, transaction.queries.len())))
+    }
+
+    fn close_page(&mut self, id: Id) -> Option<Msg> {
+        if self.app.mounted(&id) {
+            assert!(self.app.umount(&id).is_ok());
+        }
+        self.switch_session(self.active_session)
+    }
+
+    fn export_result(&mut self, path: PathBuf) -> Option<Msg> {
+        let id = match self.session() {
+            Some(session) => session.selected_widget.clone(),
+            None => return Some(Msg::None),
+        };
+        let table = match self.app.query(&id, Attribute::Content) {
+            Ok(Some(AttrValue::Table(table))) => table,
+            _ => return Some(Msg::ShowError("Nothing to export".to_string())),
+        };
+        match fs::write(&path, QueryResult::table_to_csv(&table)) {
+            Ok(()) => Some(Msg::ShowStatus(format!("Exported {} rows to {}", table.len().saturating_sub(1), path.display()))),
+            Err(err) => Some(Msg::ShowError(format!("Failed to export to {}: {}", path.display(), err))),
+        }
+    }
+
+    // Suspends the UI while $VISUAL/$EDITOR edits the content in a temp file, then puts the result into the popup
+    fn edit_externally(&mut self, content: Vec<String>) -> Option<Msg> {
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok()
+            .filter(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| "vi".to_string());
+        let extension = match self.session().map(|session| &self.connections[session.connection].connection_type) {
+            Some(ConnectionType::Postgres | ConnectionType::MySql) => "sql",
+            _ => "txt",
+        };
+        let path = match Self::write_temp_file(extension, &content.join("\n")) {
+            Ok(path) => path,
+            Err(err) => return Some(Msg::ShowError(format!("Failed to write a temporary file: {}", err))),
+        };
+
+        // the input listener would otherwise read the keys meant for the editor
+        let _ = self.app.lock_ports();
+        let _ = self.terminal.leave_alternate_screen();
+        let _ = self.terminal.disable_raw_mode();
+        // the shell splits the editor command as it would in the user's shell, e.g. "code --wait"
+        // or a quoted path with spaces, the file is passed as $1
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("sh")
+            .arg(&path)
+            .status();
+        let _ = self.terminal.enter_alternate_screen();
+        let _ = self.terminal.enable_raw_mode();
+        let _ = self.terminal.clear_screen();
+        // the screen was drawn over by the editor, repaint everything
+        let _ = self.terminal.raw_mut().clear();
+        let _ = self.app.unlock_ports();
+
+        let edited = fs::read_to_string(&path);
+        let _ = fs::remove_file(&path);
+        match (status, edited) {
+            (Err(err), _) => Some(Msg::ShowError(format!("Failed to run '{}': {}", editor, err))),
+            (Ok(status), _) if !status.success() => Some(Msg::ShowError(format!("'{}' exited with {}, content left unchanged", editor, status))),
+            (_, Err(err)) => Some(Msg::ShowError(format!("Failed to read {}: {}", path.display(), err))),
+            (_, Ok(edited)) => {
+                let lines = edited.lines().map(|line| PropValue::Str(line.to_string())).collect();
+                assert!(self.app.attr(&Id::QueryLine, Attribute::Custom(APP_EDITOR_CONTENT), AttrValue::Payload(PropPayload::Vec(lines))).is_ok());
+                Some(Msg::None)
+            },
+        }
+    }
+
+    // Readable by the user only and never an existing file, another user can't swap it for a symlink
+    fn write_temp_file(extension: &str, content: &str) -> io::Result<PathBuf> {
+        let mut attempt = 0;
+        loop {
+            let path = std::env::temp_dir().join(format!("dbclient-{}-{}.{}", std::process::id(), attempt, extension));
+            match fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path) {
+                Ok(mut file) => {
+                    if let Err(err) = file.write_all(format!("{}\n", content).as_bytes()) {
+                        let _ = fs::remove_file(&path);
+                        return Err(err);
+                    }
+                    return Ok(path);
+                },
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn test_connection(&mut self, selected_connection: usize) -> Option<Msg> {
+        let mut fetcher = match self.build_fetcher(selected_connection, false, Msg::TestConnection(selected_connection)) {
+            Ok(fetcher) => fetcher,
+            Err(msg) => return Some(msg),
+        };
+        let name = self.connections[selected_connection].name.clone();
+        match fetcher.server_info() {
+            Ok(info) => {
+                self.health.insert(name.clone(), true);
+                let mut status = format!("{}: OK, {:.1} ms", name, info.latency.as_secs_f64() * 1000.0);
+                if let Some(version) = info.version {
+                    status.push_str(&format!(", version {}", version));
+                }
+                if let Some(user) = info.user {
+                    status.push_str(&format!(", user {}", user));
+                }
+                Some(Msg::ShowStatus(status))
+            },
+            // the badge stays unknown, the server may well be up
+            Err(err @ FetcherError::Unsupported(_)) => {
+                self.health.remove(&name);
+                Some(Msg::ShowError(format!("{}: UNSUPPORTED, {}", name, err)))
+            },
+            Err(err) => {
+                self.health.insert(name.clone(), false);
+                Some(Msg::ShowError(format!("{}: FAILED, {}", name, err)))
+            },
+        }
+    }
+
+    fn session(&self) -> Option<&Session> {
+        self.sessions.get(self.active_session)
+    }
+
+    fn session_mut(&mut self) -> Option<&mut Session> {
+        self.sessions.get_mut(self.active_session)
+    }
+
+    fn open_session(&mut self, selected_connection: usize) -> Option<Msg> {
+        if let Some(index) = self.sessions.iter().position(|session| session.connection == selected_connection) {
+            return self.switch_session(index);
+        }
+
+        let fetcher = match self.build_fetcher(selected_connection, false, Msg::ToQueryPage(selected_connection)) {
+            Ok(fetcher) => fetcher,
+            Err(msg) => return Some(msg),
+        };
+        let session = Session::new(self.next_session_id, selected_connection, fetcher);
+        self.next_session_id += 1;
+
+        let connection = &self.connections[selected_connection];
+        let borders = Borders::default()
+            .modifiers(BorderType::Rounded)
+            .color(environment_color(&connection.environment));
+        let title = if connection.read_only { "Available Objects 🔒" } else { "Available Objects" };
+
+        assert!(self.app.mount(session.db_objects_id(), Box::<DbObjects>::default(), vec![]).is_ok());
+        assert!(self.app.mount(session.query_result_id(), Box::<QueryResult>::default(), vec![]).is_ok());
+        assert!(self.app.attr(&session.db_objects_id(), Attribute::Borders, AttrValue::Borders(borders)).is_ok());
+        assert!(self.app.attr(&session.query_result_id(), Attribute::Borders, AttrValue::Borders(borders)).is_ok());
+        assert!(self.app.attr(&session.db_objects_id(), Attribute::Title, AttrValue::Title((title.to_string(), Alignment::Left))).is_ok());
+
+        self.sessions.push(session);
+        self.switch_session(self.sessions.len() - 1);
         Some(Msg::FetchDbObjects)
     }
+
+    fn switch_session(&mut self, index: usize) -> Option<Msg> {
+        let widget = match self.sessions.get(index) {
+            Some(session) => session.selected_widget.clone(),
+            None => return Some(Msg::None),
+        };
+        self.active_session = index;
+        self.selected_page = Page::Query;
+        assert!(self.app.active(&widget).is_ok());
+        self.reload_session_tabs()
+    }
+
+    fn close_session(&mut self) -> Option<Msg> {
+        if self.active_session >= self.sessions.len() {
+            return Some(Msg::None);
+        }
+        let session = self.sessions.remove(self.active_session);
+        assert!(self.app.umount(&session.db_objects_id()).is_ok());
+        assert!(self.app.umount(&session.query_result_id()).is_ok());
+        if session.compare.is_some() {
+            assert!(self.app.umount(&session.compare_result_id()).is_ok());
+        }
+
+        if self.sessions.is_empty() {
+            self.active_session = 0;
+            return Some(Msg::ToConnectionsPage);
+        }
+        self.switch_session(min(self.active_session, self.sessions.len() - 1))
+    }
+
+    // Called after a connection is removed from the list, connection indexes after it shift by one
+    fn close_connection_sessions(&mut self, removed_connection: usize) {
+        for session in self.sessions.iter_mut() {
+            let compare_removed = session.compare.as_ref().is_some_and(|compare| compare.connection == removed_connection);
+            if session.connection == removed_connection || compare_removed {
+                if session.compare.take().is_some() {
+                    assert!(self.app.umount(&session.compare_result_id()).is_ok());
+                }
+                session.selected_widget = session.db_objects_id();
+            }
+            if session.connection == removed_connection {
+                assert!(self.app.umount(&session.db_objects_id()).is_ok());
+                assert!(self.app.umount(&session.query_result_id()).is_ok());
+            }
+        }
+        self.sessions.retain(|session| session.connection != removed_connection);
+        for session in self.sessions.iter_mut() {
+            if session.connection > removed_connection {
+                session.connection -= 1;
+            }
+            if let Some(compare) = session.compare.as_mut().filter(|compare| compare.connection > removed_connection) {
+                compare.connection -= 1;
+            }
+        }
+        self.active_session = min(self.active_session, self.sessions.len().saturating_sub(1));
+        self.reload_session_tabs();
+    }
+
+    fn reload_session_tabs(&mut self) -> Option<Msg> {
+        let tabs = self.sessions.iter().enumerate().map(|(index, session)| {
+            let connection = &self.connections[session.connection];
+            TextSpan::from(format!(" {}:{} ", index + 1, connection.name)).fg(environment_color(&connection.environment))
+        }).collect();
+        assert!(self.app.attr(&Id::SessionTabs, Attribute::Text, SessionTabs::build_tabs_payload(tabs)).is_ok());
+        assert!(self.app.attr(&Id::SessionTabs, Attribute::Value, AttrValue::Number(self.active_session as isize)).is_ok());
+        None
+    }
+
+    // The split view pane is read-only, queries run on both connections
+    fn open_split_view(&mut self, selected_connection: usize) -> Option<Msg> {
+        if self.session().is_none() {
+            return Some(Msg::None);
+        }
+        let fetcher = match self.build_fetcher(selected_connection, true, Msg::OpenSplitView(selected_connection)) {
+            Ok(fetcher) => fetcher,
+            Err(msg) => return Some(msg),
+        };
+        self.close_split_view();
+
+        let connection = &self.connections[selected_connection];
+        let borders = Borders::default()
+            .modifiers(BorderType::Rounded)
+            .color(environment_color(&connection.environment));
+        let title = format!("Result: {} 🔒", connection.name);
+
+        let session = &mut self.sessions[self.active_session];
+        assert!(self.app.mount(session.compare_result_id(), Box::new(QueryResult::compare()), vec![]).is_ok());
+        assert!(self.app.attr(&session.compare_result_id(), Attribute::Borders, AttrValue::Borders(borders)).is_ok());
+        assert!(self.app.attr(&session.compare_result_id(), Attribute::Title, AttrValue::Title((title, Alignment::Left))).is_ok());
+        session.compare = Some(Compare { connection: selected_connection, fetcher });
+
+        // Only re-run the last query when it can't change data on the main connection
+        match session.last_request.clone() {
+            Some(request) if request.query.iter().all(|element| matches!(element, QueryElement::ListAllItemsFrom(_))) => {
+                Some(Msg::ExecuteConfirmedQuery(request))
+            },
+            _ => None,
+        }
+    }
+
+    fn close_split_view(&mut self) -> Option<Msg> {
+        if let Some(session) = self.sessions.get_mut(self.active_session) {
+            if session.compare.take().is_some() {
+                assert!(self.app.umount(&session.compare_result_id()).is_ok());
+                if session.selected_widget == session.compare_result_id() {
+                    session.selected_widget = session.query_result_id();
+                    assert!(self.app.active(&session.selected_widget).is_ok());
+                }
+            }
+        }
+        None
+    }
+
+    fn sync_result_scroll(&mut self, index: usize) -> Option<Msg> {
+        let ids = self.session()
+            .filter(|session| session.compare.is_some())
+            .map(|session| [session.query_result_id(), session.compare_result_id()]);
+        for id in ids.into_iter().flatten() {
+            assert!(self.app.attr(&id, Attribute::Custom(APP_LIST_INDEX), AttrValue::Number(index as isize)).is_ok());
+        }
+        None
+    }
+
+    fn activate_editor(&mut self, editor_type: EditorType) -> Option<Msg> {
+        let is_query = matches!(editor_type, EditorType::Query);
+        let is_script = matches!(editor_type, EditorType::Script);
+        let completions = if is_query { self.completions() } else { Completions::default() };
+        let mut popup = EditorPopup::new(editor_type);
+        if let (true, Some(session)) = (is_script, self.session()) {
+            for (editor, content) in &session.script {
+                popup = popup.with_content(editor, content.clone());
+            }
+        }
+        if let (true, Some(session)) = (is_query, self.session()) {
+            popup = popup
+                .with_content("query", session.query.clone())
+                .with_history(session.history.clone())
+                .with_completions("query", completions)
+                .with_syntax("query", match self.connections[session.connection].connection_type {
+                    ConnectionType::Redis | ConnectionType::RedisCluster => Syntax::Redis,
+                    ConnectionType::Postgres | ConnectionType::MySql => Syntax::Sql,
+                });
+        }
+        self.show_editor = true;
+        assert!(self.app.mount(Id::QueryLine, Box::new(popup), vec![]).is_ok());
+        assert!(self.app.active(&Id::QueryLine).is_ok());
+        None
+    }
+
+    // Commands of the session connection, cached per connection, and the loaded key names
+    fn completions(&mut self) -> Completions {
+        let connection = match self.session() {
+            Some(session) => session.connection,
+            None => return Completions::default(),
+        };
+        let name = self.connections[connection].name.clone();
+        if !self.command_docs.contains_key(&name) {
+            // secrets are resolved when the session is opened, a failed fetch is retried on the next completion
+            let docs = self.build_redis_config(connection, Msg::None).ok()
+                .and_then(|config| commands::fetch_command_docs(&config).ok());
+            if let Some(docs) = docs {
+                self.command_docs.insert(name.clone(), docs);
+            }
+        }
+        let keys = match self.session().map(|session| self.app.query(&session.db_objects_id(), Attribute::Content)) {
+            Some(Ok(Some(AttrValue::Table(table)))) => table.iter()
+                .filter_map(|row| row.first().map(|cell| cell.content.clone()))
+                .collect(),
+            _ => vec![],
+        };
+        Completions { commands: self.command_docs.get(&name).cloned().unwrap_or_default(), keys }
+    }
+
+    fn select_query_page_widget(&mut self, widget: fn(&Session) -> Id) -> Option<Msg> {
+        if let Some(session) = self.session_mut() {
+            session.selected_widget = widget(session);
+            let widget = session.selected_widget.clone();
+            assert!(self.app.active(&widget).is_ok());
+        }
+        None
+    }
 }
 
 impl Update<Msg> for Model<CrosstermTerminalAdapter>
@@ -250,11 +1366,7 @@ impl Update<Msg> for Model<CrosstermTerminalAdapter>
                     self.quit = true;
                     None
                 },
-                Msg::ToQueryPage(selected_connection) => {
-                    let result = self.init_fetcher(selected_connection);
-                    assert!(self.app.active(&self.query_page_selected_widget).is_ok());
-                    result
-                },
+                Msg::ToQueryPage(selected_connection) => self.open_session(selected_connection),
                 Msg::ToConnectionsPage => {
                     self.selected_page = Page::Connections;
                     assert!(self.app.active(&Id::ConnectionsList).is_ok());
@@ -262,36 +1374,155 @@ impl Update<Msg> for Model<CrosstermTerminalAdapter>
                 },
                 Msg::FetchDbObjects => self.reload_db_objects(),
 
+                Msg::NextSession if !self.sessions.is_empty() => self.switch_session((self.active_session + 1) % self.sessions.len()),
+
+                Msg::PrevSession if !self.sessions.is_empty() => self.switch_session((self.active_session + self.sessions.len() - 1) % self.sessions.len()),
+
+                Msg::NextSession | Msg::PrevSession => None,
+
+                Msg::ToSession(index) => self.switch_session(index),
+
+                Msg::CloseSession => self.close_session(),
+
+                Msg::TestConnection(selected_connection) => self.test_connection(selected_connection),
+
+                Msg::ToggleConnectionsGroup(group) => self.toggle_connections_group(group),
+
+                Msg::FilterConnections(filter) => self.filter_connections(filter),
+
+                Msg::AddConnection => self.open_connection_form(None, false),
+
+                Msg::EditConnection(selected_connection) => self.open_connection_form(Some(selected_connection), false),
+
+                Msg::DuplicateConnection(selected_connection) => self.open_connection_form(Some(selected_connection), true),
+
+                Msg::DeleteConnection(selected_connection) => self.connections.get(selected_connection)
+                    .map(|connection| Msg::ActivateEditor(EditorType::DeleteConnection(selected_connection, connection.name.clone()))),
+
+                Msg::SaveConnection(selected_connection, connection) => self.save_connection(selected_connection, connection),
+
+                Msg::RemoveConnection(selected_connection) => self.remove_connection(selected_connection),
+
+                Msg::RebuildFetchers(selected_connection) => self.rebuild_fetchers(selected_connection),
+
+                Msg::ShowStatus(text) => self.show_status(text, Color::Gray),
+
+                Msg::ShowError(text) => self.show_status(text, Color::Red),
+
                 Msg::FetchDbObject(object) => self.fetch_db_object(object),
 
                 Msg::AddDbObject(path, object_type, name) => self.add_db_object(path, object_type, name),
 
+                Msg::DeleteDbObject(name, lazy) => self.delete_db_object(name, lazy),
+
+                Msg::RenameDbObject(name, new_name, overwrite) => self.rename_db_object(name, new_name, overwrite),
+
+                Msg::CopyDbObject(name, destination, database, overwrite) => self.copy_db_object(name, destination, database, overwrite),
+
+                Msg::ExpireDbObject(name, ttl) => self.expire_db_object(name, ttl),
+
                 Msg::ExecuteCustomQuery(query) => self.execute_custom_query(query),
 
                 Msg::SearchPattern(pattern) => self.search_pattern(pattern),
 
-                Msg::ExecuteQuery(query) => self.reload_query_result(&query),
+                Msg::ExecuteQuery(query) => self.guard_query(query),
+
+                Msg::ExecuteConfirmedQuery(query) if self.session().is_some_and(|session| session.transaction.is_some()) => self.queue_query(query),
+
+                Msg::ExecuteConfirmedQuery(query) => self.reload_query_result(&query),
                 
-                Msg::ToDbObjectsWidget => {
-                    self.query_page_selected_widget = Id::DbObjects;
-                    assert!(self.app.active(&Id::DbObjects).is_ok());
-                    None
+                Msg::ToDbObjectsWidget => self.select_query_page_widget(Session::db_objects_id),
+
+                Msg::ToQueryResultWidget => self.select_query_page_widget(Session::query_result_id),
+
+                Msg::ToCompareResultWidget if self.session().is_some_and(|session| session.compare.is_some()) => {
+                    self.select_query_page_widget(Session::compare_result_id)
                 },
 
-                Msg::ToQueryResultWidget => {
-                    self.query_page_selected_widget = Id::QueryResult;
-                    assert!(self.app.active(&Id::QueryResult).is_ok());
-                    None
+                Msg::ToCompareResultWidget => None,
+
+                Msg::OpenSplitView(selected_connection) => self.open_split_view(selected_connection),
+
+                Msg::CloseSplitView => self.close_split_view(),
+
+                Msg::SyncResultScroll(index) => self.sync_result_scroll(index),
+
+                Msg::OpenKeyspaceDiff => self.open_keyspace_diff(),
+
+                Msg::DiffKeyspace(left, right, pattern) => self.diff_keyspace(left, right, pattern),
+
+                Msg::ExportResult(path) => self.export_result(path),
+
+                Msg::CopyKeys(destination, pattern, replace, dry_run) => self.copy_keys(destination, pattern, replace, dry_run),
+
+                Msg::OpenDashboard => self.open_dashboard(),
+
+                Msg::CloseDashboard => self.close_page(Id::Dashboard),
+
+                Msg::OpenSlowlog => self.open_slowlog(),
+
+                Msg::CloseSlowlog => self.close_page(Id::Slowlog),
+
+                Msg::ResetSlowlog => self.reset_slowlog(),
+
+                Msg::OpenMonitor => self.open_monitor(),
+
+                Msg::CloseMonitor => self.close_page(Id::Monitor),
+
+                Msg::OpenPubSub => self.open_pubsub(),
+
+                Msg::ClosePubSub => self.close_page(Id::PubSub),
+
+                Msg::Subscribe(channels, patterns) => self.subscribe(channels, patterns),
+
+                Msg::Publish(channel, message) => self.publish(channel, message),
+
+                Msg::EvalScript(body, keys, args) => self.eval_script(body, keys, args),
+
+                Msg::OpenFunctions => self.open_functions(),
+
+                Msg::CloseFunctions => self.close_page(Id::Functions),
+
+                Msg::LoadFunction(code, replace) => self.load_function(code, replace),
+
+                Msg::DeleteFunction(library) => self.delete_function(library),
+
+                Msg::BeginTransaction(watch) => self.begin_transaction(watch),
+
+                Msg::ExecTransaction => self.exec_transaction(),
+
+                Msg::DiscardTransaction => self.discard_transaction(),
+
+                Msg::ToggleTreeMode => {
+                    if let Some(session) = self.session_mut().filter(|session| session.reply.is_some()) {
+                        session.tree_mode = !session.tree_mode;
+                    }
+                    self.reload_reply()
                 },
-                Msg::ActivateEditor(widget_kind) => {
-                    self.show_editor = true;
-                    assert!(self.app.mount(Id::QueryLine, Box::new(EditorPopup::new(widget_kind)), vec![]).is_ok());
-                    assert!(self.app.active(&Id::QueryLine).is_ok());
+
+                Msg::ToggleTreeNode(row) => self.toggle_tree_node(row),
+
+                Msg::ExpandTree(expand) => self.expand_tree(expand),
+                Msg::EditExternally(content) => self.edit_externally(content),
+
+                Msg::FilterMonitor(filter) => {
+                    assert!(self.app.attr(&Id::Monitor, Attribute::Custom(APP_SEARCH_PATTERN), AttrValue::String(filter)).is_ok());
+                    assert!(self.app.active(&Id::Monitor).is_ok());
                     None
                 },
+
+                Msg::ActivateEditor(widget_kind) => self.activate_editor(widget_kind),
                 Msg::DiactivateEditor => {
                     self.show_editor = false;
-                    assert!(self.app.active(&self.query_page_selected_widget).is_ok());
+                    let widget = match (&self.selected_page, self.session()) {
+                        (Page::Query, Some(session)) => session.selected_widget.clone(),
+                        (Page::Slowlog, _) => Id::Slowlog,
+                        (Page::Monitor, _) => Id::Monitor,
+                        (Page::PubSub, _) => Id::PubSub,
+                        (Page::Functions, _) => Id::Functions,
+                        _ => Id::ConnectionsList,
+                    };
+                    assert!(self.app.active(&widget).is_ok());
                     if self.app.mounted(&Id::QueryLine) {
                         assert!(self.app.umount(&Id::QueryLine).is_ok());
                     }
@@ -309,6 +1540,9 @@ impl Update<Msg> for Model<CrosstermTerminalAdapter>
                             Some(Msg::SearchPattern(pattern))
                         },
                         super::EditorType::Query => {
+                            if let Some(session) = self.session_mut() {
+                                session.query = editors.get("query").cloned().unwrap_or_default();
+                            }
                             let query = editors.get("query").unwrap_or(&vec![]).join("\n");
                             Some(Msg::ExecuteCustomQuery(query))
                         },
@@ -318,6 +1552,201 @@ impl Update<Msg> for Model<CrosstermTerminalAdapter>
                             let name = editors.get("name").unwrap_or(&vec![]).join("\n");
                             Some(Msg::AddDbObject(root, obj_type, name))
                         },
+                        super::EditorType::DeleteDbObject(name, lazy) => {
+                            let confirm = editors.get("confirm").unwrap_or(&vec![]).join("\n");
+                            if Self::is_confirmed(&confirm) {
+                                Some(Msg::DeleteDbObject(name, lazy))
+                            } else {
+                                Some(Msg::None)
+                            }
+                        },
+                        super::EditorType::RenameDbObject(name) => {
+                            let new_name = editors.get("name").unwrap_or(&vec![]).join("\n");
+                            let overwrite = editors.get("overwrite").unwrap_or(&vec![]).join("\n");
+                            if new_name.is_empty() {
+                                return Some(Msg::None);
+                            }
+                            Some(Msg::RenameDbObject(name, new_name, Self::is_confirmed(&overwrite)))
+                        },
+                        super::EditorType::CopyDbObject(name) => {
+                            let destination = editors.get("name").unwrap_or(&vec![]).join("\n");
+                            let database = editors.get("database").unwrap_or(&vec![]).join("\n");
+                            let overwrite = editors.get("overwrite").unwrap_or(&vec![]).join("\n");
+                            if destination.is_empty() {
+                                return Some(Msg::None);
+                            }
+                            let database = Some(database.trim().to_string()).filter(|db| !db.is_empty());
+                            Some(Msg::CopyDbObject(name, destination, database, Self::is_confirmed(&overwrite)))
+                        },
+                        super::EditorType::ExpireDbObject(name) => {
+                            let ttl = editors.get("ttl").unwrap_or(&vec![]).join("\n");
+                            let ttl = ttl.trim();
+                            if ttl.is_empty() {
+                                return Some(Msg::ExpireDbObject(name, None));
+                            }
+                            match ttl.parse::<u64>() {
+                                Ok(seconds) => Some(Msg::ExpireDbObject(name, Some(seconds))),
+                                Err(_) => Some(Msg::None),
+                            }
+                        },
+                        super::EditorType::Secret(connection, secret, then) => {
+                            let value = editors.get("secret").unwrap_or(&vec![]).join("\n");
+                            self.secrets.entry(connection).or_default().insert(secret, value);
+                            Some(*then)
+                        },
+                        super::EditorType::ConnectionForm(selected_connection, original) => {
+                            match self.connection_from_form(selected_connection, original, &editors) {
+                                Ok(connection) => Some(Msg::SaveConnection(selected_connection, connection)),
+                                Err(err) => Some(Msg::ShowError(err)),
+                            }
+                        },
+                        super::EditorType::FuzzyFind(_) => {
+                            let filter = editors.get("search").unwrap_or(&vec![]).join("");
+                            Some(Msg::FilterConnections(filter))
+                        },
+                        super::EditorType::DeleteConnection(selected_connection, _) => {
+                            let confirm = editors.get("confirm").unwrap_or(&vec![]).join("\n");
+                            if Self::is_confirmed(&confirm) {
+                                Some(Msg::RemoveConnection(selected_connection))
+                            } else {
+                                Some(Msg::None)
+                            }
+                        },
+                        super::EditorType::SplitView => {
+                            let name = editors.get("connection").unwrap_or(&vec![]).join("");
+                            let name = name.trim();
+                            if name.is_empty() {
+                                return Some(Msg::CloseSplitView);
+                            }
+                            match self.connections.iter().position(|connection| connection.name == name) {
+                                Some(selected_connection) => Some(Msg::OpenSplitView(selected_connection)),
+                                None => Some(Msg::ShowError(format!("Connection '{}' not found", name))),
+                            }
+                        },
+                        super::EditorType::KeyspaceDiff(..) => {
+                            #[allow(clippy::result_large_err)]
+                            let find_connection = |field: &str| {
+                                let name = editors.get(field).unwrap_or(&vec![]).join("").trim().to_string();
+                                self.connections.iter().position(|connection| connection.name == name)
+                                    .ok_or_else(|| Msg::ShowError(format!("Connection '{}' not found", name)))
+                            };
+                            let pattern = editors.get("pattern").unwrap_or(&vec![]).join("").trim().to_string();
+                            let pattern = if pattern.is_empty() { "*".to_string() } else { pattern };
+                            match (find_connection("left"), find_connection("right")) {
+                                (Ok(left), Ok(right)) => Some(Msg::DiffKeyspace(left, right, pattern)),
+                                (Err(msg), _) | (_, Err(msg)) => Some(msg),
+                            }
+                        },
+                        super::EditorType::CopyKeys(_) => {
+                            let pattern = editors.get("pattern").unwrap_or(&vec![]).join("").trim().to_string();
+                            let name = editors.get("connection").unwrap_or(&vec![]).join("").trim().to_string();
+                            let overwrite = editors.get("overwrite").unwrap_or(&vec![]).join("");
+                            let dry_run = editors.get("dry_run").unwrap_or(&vec![]).join("");
+                            if pattern.is_empty() {
+                                return Some(Msg::None);
+                            }
+                            match self.connections.iter().position(|connection| connection.name == name) {
+                                Some(destination) => {
+                                    let (replace, dry_run) = (Self::is_confirmed(&overwrite), Self::is_confirmed(&dry_run));
+                                    let then = Msg::CopyKeys(destination, pattern, replace, dry_run);
+                                    // overwriting keys of a prod destination needs the typed confirmation
+                                    if replace && !dry_run {
+                                        Some(self.confirm_on_prod(destination, then))
+                                    } else {
+                                        Some(then)
+                                    }
+                                },
+                                None => Some(Msg::ShowError(format!("Connection '{}' not found", name))),
+                            }
+                        },
+                        super::EditorType::ExportResult => {
+                            let path = editors.get("path").unwrap_or(&vec![]).join("").trim().to_string();
+                            if path.is_empty() {
+                                return Some(Msg::None);
+                            }
+                            Some(Msg::ExportResult(PathBuf::from(path)))
+                        },
+                        super::EditorType::ResetSlowlog => {
+                            let confirm = editors.get("confirm").unwrap_or(&vec![]).join("\n");
+                            match self.session().map(|session| session.connection) {
+                                Some(selected_connection) if Self::is_confirmed(&confirm) => Some(self.confirm_on_prod(selected_connection, Msg::ResetSlowlog)),
+                                _ => Some(Msg::None),
+                            }
+                        },
+                        super::EditorType::Subscribe => {
+                            let names = |field: &str| editors.get(field).unwrap_or(&vec![]).join(" ")
+                                .split_whitespace()
+                                .map(str::to_string)
+                                .collect::<Vec<_>>();
+                            let (channels, patterns) = (names("channels"), names("patterns"));
+                            if channels.is_empty() && patterns.is_empty() {
+                                return Some(Msg::None);
+                            }
+                            Some(Msg::Subscribe(channels, patterns))
+                        },
+                        super::EditorType::Publish(_) => {
+                            let channel = editors.get("channel").unwrap_or(&vec![]).join("").trim().to_string();
+                            let message = editors.get("message").unwrap_or(&vec![]).join("");
+                            if channel.is_empty() {
+                                return Some(Msg::None);
+                            }
+                            Some(Msg::Publish(channel, message))
+                        },
+                        super::EditorType::Script => {
+                            let body = editors.get("script").unwrap_or(&vec![]).join("\n");
+                            // one value per line, so that values may contain spaces
+                            let values = |field: &str| editors.get(field).unwrap_or(&vec![]).iter()
+                                .filter(|line| !line.is_empty())
+                                .cloned()
+                                .collect::<Vec<_>>();
+                            let (keys, args) = (values("keys"), values("args"));
+                            if let Some(session) = self.session_mut() {
+                                session.script = editors;
+                            }
+                            if body.trim().is_empty() {
+                                return Some(Msg::None);
+                            }
+                            Some(Msg::EvalScript(body, keys, args))
+                        },
+                        super::EditorType::LoadFunction(..) => {
+                            let code = editors.get("code").unwrap_or(&vec![]).join("\n");
+                            let replace = editors.get("replace").unwrap_or(&vec![]).join("");
+                            if code.trim().is_empty() {
+                                return Some(Msg::None);
+                            }
+                            let replace = Self::is_confirmed(&replace);
+                            match self.session().map(|session| session.connection) {
+                                // replacing drops the functions of the existing library
+                                Some(selected_connection) if replace => Some(self.confirm_on_prod(selected_connection, Msg::LoadFunction(code, replace))),
+                                _ => Some(Msg::LoadFunction(code, replace)),
+                            }
+                        },
+                        super::EditorType::DeleteFunction(library) => {
+                            let confirm = editors.get("confirm").unwrap_or(&vec![]).join("\n");
+                            match self.session().map(|session| session.connection) {
+                                Some(selected_connection) if Self::is_confirmed(&confirm) => Some(self.confirm_on_prod(selected_connection, Msg::DeleteFunction(library))),
+                                _ => Some(Msg::None),
+                            }
+                        },
+                        super::EditorType::BeginTransaction => {
+                            let watch = editors.get("watch").unwrap_or(&vec![]).join(" ")
+                                .split_whitespace()
+                                .map(str::to_string)
+                                .collect();
+                            Some(Msg::BeginTransaction(watch))
+                        },
+                        super::EditorType::MonitorFilter(_) => {
+                            let filter = editors.get("filter").unwrap_or(&vec![]).join("");
+                            Some(Msg::FilterMonitor(filter))
+                        },
+                        super::EditorType::ConfirmQuery(connection_name, then) => {
+                            let confirm = editors.get("confirm").unwrap_or(&vec![]).join("\n");
+                            if confirm.trim() == connection_name {
+                                Some(*then)
+                            } else {
+                                Some(Msg::None)
+                            }
+                        },
                     }
                 },
 
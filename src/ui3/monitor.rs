@@ -0,0 +1,192 @@
+use std::collections::VecDeque;
+
+use ratatui::{layout::{Alignment, Rect}, style::Color, Frame};
+use tuirealm::{command::{Cmd, CmdResult}, event::{Key, KeyEvent}, props::{BorderType, Borders, Props, TableBuilder, TextSpan}, AttrValue, Attribute, Component, Event, MockComponent, State};
+
+use crate::dbclient::redis::{monitor::{Monitor, MonitorLine}, RedisConfig};
+
+use super::{AppEvent, EditorType, Msg, APP_SEARCH_PATTERN};
+
+// Lines kept in the pane, older ones are dropped
+const MAX_LINES: usize = 1000;
+
+// Live `MONITOR` output, the monitor connection is closed when the pane is unmounted
+pub struct MonitorView {
+    props: Props,
+    component: tui_realm_stdlib::Table,
+    connection_name: String,
+    monitor: Option<Monitor>,
+    error: Option<String>,
+    lines: VecDeque<MonitorLine>,
+    filter: String,
+    paused: bool,
+}
+
+impl MonitorView {
+    pub fn new(config: RedisConfig, connection_name: String) -> Self {
+        let (monitor, error) = match Monitor::start(&config) {
+            Ok(monitor) => (Some(monitor), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        let mut view = Self {
+            props: Props::default(),
+            component: tui_realm_stdlib::Table::default()
+                .highlighted_color(Color::Yellow)
+                .highlighted_str("> ")
+                .scroll(true)
+                .widths(&[18, 22, 4, 16, 40]),
+            connection_name,
+            monitor,
+            error,
+            lines: VecDeque::new(),
+            filter: String::new(),
+            paused: false,
+        };
+        view.reload();
+        view
+    }
+
+    fn poll(&mut self) -> bool {
+        let lines = match self.monitor.as_ref().map(Monitor::poll) {
+            Some(Ok(lines)) => lines,
+            Some(Err(err)) => {
+                self.monitor = None;
+                self.error = Some(err);
+                return true;
+            },
+            None => return false,
+        };
+        if lines.is_empty() {
+            return false;
+        }
+        self.lines.extend(lines);
+        while self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+        true
+    }
+
+    fn matches(&self, line: &MonitorLine) -> bool {
+        if self.filter.is_empty() {
+            return true;
+        }
+        let filter = self.filter.to_lowercase();
+        [&line.client, &line.db, &line.command].iter().any(|field| field.to_lowercase().contains(&filter))
+            || line.args.iter().any(|arg| arg.to_lowercase().contains(&filter))
+    }
+
+    fn reload(&mut self) {
+        let mut table = TableBuilder::default();
+        table
+            .add_col(TextSpan::from("time").bold())
+            .add_col(TextSpan::from("client").bold())
+            .add_col(TextSpan::from("db").bold())
+            .add_col(TextSpan::from("command").bold())
+            .add_col(TextSpan::from("args").bold());
+        for line in self.lines.iter().filter(|line| self.matches(line)) {
+            table.add_row()
+                .add_col(TextSpan::from(line.timestamp.clone()).fg(Color::Gray))
+                .add_col(TextSpan::from(line.client.clone()))
+                .add_col(TextSpan::from(line.db.clone()))
+                .add_col(TextSpan::from(line.command.clone()).fg(Color::Blue))
+                .add_col(TextSpan::from(line.args.join(" ")));
+        }
+        let table = table.build();
+        let rows = table.len();
+        self.component.attr(Attribute::Content, AttrValue::Table(table));
+
+        // follow the tail while running
+        if !self.paused {
+            self.component.states.list_index_at_last();
+            if rows <= 1 {
+                self.component.states.list_index_at_first();
+            }
+        }
+        self.update_title();
+    }
+
+    fn update_title(&mut self) {
+        let (status, color) = match (&self.error, self.paused) {
+            (Some(err), _) => (format!("stopped: {}", err), Color::Red),
+            (None, true) => ("paused".to_string(), Color::Yellow),
+            (None, false) => ("running".to_string(), Color::Green),
+        };
+        let filter = if self.filter.is_empty() { String::new() } else { format!(", filter '{}'", self.filter) };
+        let title = format!("MONITOR {} ({}{})", self.connection_name, status, filter);
+        self.component.attr(Attribute::Title, AttrValue::Title((title, Alignment::Left)));
+        self.component.attr(Attribute::Borders, AttrValue::Borders(Borders::default().modifiers(BorderType::Rounded).color(color)));
+    }
+}
+
+impl MockComponent for MonitorView {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        self.component.view(frame, area);
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.props.get(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        if let (Attribute::Custom(APP_SEARCH_PATTERN), AttrValue::String(filter)) = (&attr, &value) {
+            self.filter = filter.trim().to_string();
+            self.reload();
+        }
+        self.props.set(attr, value);
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, AppEvent> for MonitorView {
+    fn on(&mut self, ev: Event<AppEvent>) -> Option<Msg> {
+        match ev {
+            Event::Tick => {
+                // lines keep coming while paused, the table is only rebuilt on resume
+                if !self.poll() {
+                    return None;
+                }
+                if self.paused {
+                    self.update_title();
+                } else {
+                    self.reload();
+                }
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('p') | Key::Char(' '), .. }) => {
+                self.paused = !self.paused;
+                self.reload();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('c'), .. }) => {
+                self.lines.clear();
+                self.reload();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('/'), .. }) => Some(Msg::ActivateEditor(EditorType::MonitorFilter(self.filter.clone()))),
+            Event::Keyboard(KeyEvent { code: Key::Char('j') | Key::Down, .. }) => {
+                self.component.states.incr_list_index(false);
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('k') | Key::Up, .. }) => {
+                self.component.states.decr_list_index(false);
+                if self.component.states.list_index == 0 {
+                    self.component.states.incr_list_index(false);
+                }
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('G'), .. }) => {
+                self.component.states.list_index_at_last();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Esc | Key::Char('q'), .. }) => Some(Msg::CloseMonitor),
+            _ => None,
+        }
+    }
+}
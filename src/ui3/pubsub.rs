@@ -0,0 +1,287 @@
+use std::{collections::VecDeque, time::{Duration, Instant}};
+
+use ratatui::{layout::{Alignment, Constraint, Direction, Layout, Rect}, style::Color, Frame};
+use tuirealm::{command::{Cmd, CmdResult}, event::{Key, KeyEvent}, props::{BorderType, Borders, PropPayload, PropValue, Props, Table, TableBuilder, TextSpan}, AttrValue, Attribute, Component, Event, MockComponent, State};
+
+use crate::dbclient::redis::{pubsub::{fetch_channels, PubSubMessage, Subscriber}, slowlog::format_timestamp, PollConnection, RedisConfig};
+
+use super::{AppEvent, EditorType, Msg, APP_SUBSCRIBE_CHANNELS, APP_SUBSCRIBE_PATTERNS};
+
+// Messages kept in the pane, older ones are dropped
+const MAX_MESSAGES: usize = 1000;
+const CHANNELS_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(PartialEq)]
+enum Pane {
+    Messages,
+    Channels,
+}
+
+// Received messages on top, active channels of the server below
+pub struct PubSubView {
+    props: Props,
+    // polls PUBSUB CHANNELS, the subscriber has its own connection
+    connection: PollConnection,
+    connection_name: String,
+    messages_table: tui_realm_stdlib::Table,
+    channels_table: tui_realm_stdlib::Table,
+    subscriber: Option<Subscriber>,
+    error: Option<String>,
+    messages: VecDeque<PubSubMessage>,
+    channels: Vec<(String, u64)>,
+    subscriptions: Vec<String>,
+    refreshed_at: Option<Instant>,
+    focus: Pane,
+}
+
+impl PubSubView {
+    pub fn new(config: RedisConfig, connection_name: String) -> Self {
+        let (subscriber, error) = match Subscriber::start(&config) {
+            Ok(subscriber) => (Some(subscriber), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        let mut view = Self {
+            props: Props::default(),
+            connection: PollConnection::new(&config.uri),
+            connection_name,
+            messages_table: Self::table().widths(&[25, 25, 15, 35]),
+            channels_table: Self::table().widths(&[70, 30]),
+            subscriber,
+            error,
+            messages: VecDeque::new(),
+            channels: vec![],
+            subscriptions: vec![],
+            refreshed_at: None,
+            focus: Pane::Messages,
+        };
+        view.reload_messages();
+        view.refresh_channels();
+        view.update_focus();
+        view
+    }
+
+    fn table() -> tui_realm_stdlib::Table {
+        tui_realm_stdlib::Table::default()
+            .highlighted_color(Color::Yellow)
+            .highlighted_str("> ")
+            .scroll(true)
+            .rewind(true)
+    }
+
+    fn poll(&mut self) -> bool {
+        let messages = match self.subscriber.as_ref().map(Subscriber::poll) {
+            Some(Ok(messages)) => messages,
+            Some(Err(err)) => {
+                self.subscriber = None;
+                self.error = Some(err);
+                return true;
+            },
+            None => return false,
+        };
+        if messages.is_empty() {
+            return false;
+        }
+        self.messages.extend(messages);
+        while self.messages.len() > MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+        true
+    }
+
+    fn reload_messages(&mut self) {
+        let mut table = TableBuilder::default();
+        table
+            .add_col(TextSpan::from("received").bold())
+            .add_col(TextSpan::from("channel").bold())
+            .add_col(TextSpan::from("pattern").bold())
+            .add_col(TextSpan::from("message").bold());
+        for message in &self.messages {
+            table.add_row()
+                .add_col(TextSpan::from(format!("{}.{:03}", format_timestamp(message.received_at / 1000), message.received_at % 1000)).fg(Color::Gray))
+                .add_col(TextSpan::from(message.channel.clone()).fg(Color::Blue))
+                .add_col(TextSpan::from(message.pattern.clone().unwrap_or_default()))
+                .add_col(TextSpan::from(message.payload.clone()));
+        }
+        // follow the newest message unless the user is reading older ones
+        let states = &self.messages_table.states;
+        let following = states.list_index + 1 >= states.list_len;
+        self.messages_table.attr(Attribute::Content, AttrValue::Table(table.build()));
+        if following {
+            self.messages_table.states.list_index_at_last();
+        }
+
+        let status = match (&self.error, self.subscriptions.is_empty()) {
+            (Some(err), _) => format!("stopped: {}", err),
+            (None, true) => "not subscribed, <s> to subscribe".to_string(),
+            (None, false) => self.subscriptions.join(" "),
+        };
+        let title = format!("Pub/Sub {} ({})", self.connection_name, status);
+        self.messages_table.attr(Attribute::Title, AttrValue::Title((title, Alignment::Left)));
+    }
+
+    fn refresh_channels(&mut self) {
+        self.refreshed_at = Some(Instant::now());
+        let table = match self.connection.run(|connection| fetch_channels(connection, "*")) {
+            Ok(channels) => {
+                self.channels = channels;
+                let mut table = TableBuilder::default();
+                table
+                    .add_col(TextSpan::from("channel").bold())
+                    .add_col(TextSpan::from("subscribers").bold());
+                for (channel, subscribers) in &self.channels {
+                    table.add_row()
+                        .add_col(TextSpan::from(channel.clone()).fg(Color::Blue))
+                        .add_col(TextSpan::from(subscribers.to_string()));
+                }
+                table.build()
+            },
+            Err(err) => Self::error_table(err.to_string()),
+        };
+        self.channels_table.attr(Attribute::Content, AttrValue::Table(table));
+        self.channels_table.attr(Attribute::Title, AttrValue::Title(("Active channels (PUBSUB CHANNELS)".to_string(), Alignment::Left)));
+    }
+
+    fn error_table(err: String) -> Table {
+        let mut table = TableBuilder::default();
+        table.add_col(TextSpan::from(err).fg(Color::Red));
+        table.build()
+    }
+
+    fn update_focus(&mut self) {
+        let color = |focused: bool| if focused { Color::Yellow } else { Color::Gray };
+        self.messages_table.attr(Attribute::Borders, AttrValue::Borders(Borders::default().modifiers(BorderType::Rounded).color(color(self.focus == Pane::Messages))));
+        self.channels_table.attr(Attribute::Borders, AttrValue::Borders(Borders::default().modifiers(BorderType::Rounded).color(color(self.focus == Pane::Channels))));
+    }
+
+    fn focused_table(&mut self) -> &mut tui_realm_stdlib::Table {
+        match self.focus {
+            Pane::Messages => &mut self.messages_table,
+            Pane::Channels => &mut self.channels_table,
+        }
+    }
+
+    // Selected channel, or the channel of the selected message, to prefill the publish popup
+    fn selected_channel(&self) -> String {
+        let index = |table: &tui_realm_stdlib::Table| table.states.list_index.checked_sub(1);
+        let channel = match self.focus {
+            Pane::Channels => index(&self.channels_table).and_then(|index| self.channels.get(index)).map(|(channel, _)| channel.clone()),
+            Pane::Messages => index(&self.messages_table).and_then(|index| self.messages.get(index)).map(|message| message.channel.clone()),
+        };
+        channel.unwrap_or_default()
+    }
+
+    fn subscribe(&mut self, value: AttrValue, pattern: bool) {
+        let names: Vec<String> = match value {
+            AttrValue::Payload(PropPayload::Vec(values)) => values.into_iter()
+                .filter_map(|value| match value {
+                    PropValue::Str(name) => Some(name),
+                    _ => None,
+                })
+                .collect(),
+            _ => return,
+        };
+        if let Some(subscriber) = &self.subscriber {
+            for name in names {
+                if pattern {
+                    subscriber.psubscribe(name.clone());
+                } else {
+                    subscriber.subscribe(name.clone());
+                }
+                self.subscriptions.push(name);
+            }
+        }
+        self.reload_messages();
+    }
+}
+
+impl MockComponent for PubSubView {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(2), Constraint::Fill(1)])
+            .split(area);
+        self.messages_table.view(frame, chunks[0]);
+        self.channels_table.view(frame, chunks[1]);
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.props.get(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        match attr {
+            Attribute::Custom(APP_SUBSCRIBE_CHANNELS) => self.subscribe(value, false),
+            Attribute::Custom(APP_SUBSCRIBE_PATTERNS) => self.subscribe(value, true),
+            _ => self.props.set(attr, value),
+        }
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, AppEvent> for PubSubView {
+    fn on(&mut self, ev: Event<AppEvent>) -> Option<Msg> {
+        match ev {
+            Event::Tick => {
+                let received = self.poll();
+                if received {
+                    self.reload_messages();
+                }
+                if self.refreshed_at.is_none_or(|refreshed_at| refreshed_at.elapsed() >= CHANNELS_REFRESH_INTERVAL) {
+                    self.refresh_channels();
+                    return Some(Msg::None);
+                }
+                received.then_some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('j') | Key::Down, .. }) => {
+                // the first row is the header
+                let table = self.focused_table();
+                table.states.incr_list_index(true);
+                if table.states.list_index == 0 {
+                    table.states.incr_list_index(true);
+                }
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('k') | Key::Up, .. }) => {
+                let table = self.focused_table();
+                table.states.decr_list_index(true);
+                if table.states.list_index == 0 {
+                    table.states.decr_list_index(true);
+                }
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                self.focus = if self.focus == Pane::Messages { Pane::Channels } else { Pane::Messages };
+                self.update_focus();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('s'), .. }) => Some(Msg::ActivateEditor(EditorType::Subscribe)),
+            Event::Keyboard(KeyEvent { code: Key::Char('u'), .. }) => {
+                if let Some(subscriber) = &self.subscriber {
+                    subscriber.unsubscribe_all();
+                }
+                self.subscriptions.clear();
+                self.reload_messages();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('P'), .. }) => Some(Msg::ActivateEditor(EditorType::Publish(self.selected_channel()))),
+            Event::Keyboard(KeyEvent { code: Key::Char('c'), .. }) => {
+                self.messages.clear();
+                self.reload_messages();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('r'), .. }) => {
+                self.refresh_channels();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Esc | Key::Char('q'), .. }) => Some(Msg::ClosePubSub),
+            _ => None,
+        }
+    }
+}
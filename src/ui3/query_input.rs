@@ -1,207 +1,218 @@
-use ratatui::{layout::Alignment, style::{Color, Modifier, Style}};
-use tui_realm_textarea::{TextArea, TEXTAREA_CMD_NEWLINE, TEXTAREA_STATUS_FMT};
-use tuirealm::{command::{Cmd, CmdResult, Direction, Position}, event::{Key, KeyEvent}, props::{BorderType, Borders, PropPayload, PropValue}, AttrValue, Attribute, Component, Event, MockComponent};
+use ratatui::{layout::Rect, style::{Color, Style}, text::{Line, Span}, widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph}, Frame};
+use tuirealm::{command::{Cmd, CmdResult}, event::Key, props::Props, AttrValue, Attribute, Component, Event, MockComponent, State, StateValue};
 
-use super::{AppEvent, Msg, editor_popup::EditorPopupWidget};
+use super::{completion::{common_prefix, Candidate, Completions}, highlight::{has_unbalanced, line_styles, tokenize, Syntax, TokenKind}, vim::{Mode, Vim}, AppEvent, Msg, editor_popup::EditorPopupWidget};
 
-#[derive(Clone)]
-enum InputMode {
-    Input,
-    Normal,
+// Open completion popup, `start` is the column of the completed word
+struct CompletionPopup {
+    start: usize,
+    candidates: Vec<Candidate>,
+    selected: usize,
 }
 
-impl Into<String> for InputMode {
-    fn into(self) -> String {
-        match self {
-            InputMode::Input => "INPUT".to_string(),
-            InputMode::Normal => "NORMAL".to_string(),
-        }
-    }
-}
-
-impl Into<Style> for InputMode {
-    fn into(self) -> Style {
-        match self {
-            InputMode::Input => Style::default().fg(Color::Black).bg(Color::Green),
-            InputMode::Normal => Style::default().fg(Color::Black).bg(Color::Blue),
-        }
-    }
-}
-
-
 pub struct EditorInput {
-    component: TextArea<'static>,
-    input_mode: InputMode,
+    props: Props,
+    vim: Vim,
+    title: &'static str,
     pub editor_type: &'static str,
+    completions: Completions,
+    completion: Option<CompletionPopup>,
+    syntax: Syntax,
+    // first visible row and column
+    scroll: (usize, usize),
 }
 
 impl EditorInput {
     pub fn new(title: &'static str, editor_type: &'static str) -> Self {
         Self {
-            component: TextArea::default()
-                .title(title, Alignment::Left)
-                .layout_margin(0)
-                .scroll_step(1)
-                .cursor_line_style(Style::default())
-                .cursor_style(Style::default().add_modifier(Modifier::REVERSED))
-                .borders(
-                    Borders::default()
-                        .modifiers(BorderType::Rounded)
-                        .color(Color::Yellow)
-                )
-            ,
-            input_mode: InputMode::Input,
-            editor_type
+            props: Props::default(),
+            vim: Vim::new(vec![], false),
+            title,
+            editor_type,
+            completions: Completions::default(),
+            completion: None,
+            syntax: Syntax::Plain,
+            scroll: (0, 0),
+        }
+    }
+
+    // Tab in insert mode: completes a single candidate, otherwise the common prefix and opens the popup
+    fn complete(&mut self) {
+        let (start, candidates) = self.completions.candidates(self.vim.buffer.before_cursor());
+        match candidates.len() {
+            0 => {},
+            1 => self.vim.buffer.replace_before_cursor(start, &candidates[0].text),
+            _ => {
+                let prefix = common_prefix(&candidates);
+                let typed = self.vim.buffer.before_cursor().chars().count() - start;
+                if prefix.chars().count() > typed {
+                    self.vim.buffer.replace_before_cursor(start, &prefix);
+                }
+                self.completion = Some(CompletionPopup { start, candidates, selected: 0 });
+            },
+        }
+    }
+
+    // Keys of the open completion popup, None when the key closes it and is handled as usual
+    fn handle_completion(&mut self, code: Key) -> Option<Msg> {
+        let popup = self.completion.as_mut()?;
+        let len = popup.candidates.len();
+        match code {
+            Key::Tab | Key::Down => popup.selected = (popup.selected + 1) % len,
+            Key::BackTab | Key::Up => popup.selected = (popup.selected + len - 1) % len,
+            Key::Enter => {
+                let text = popup.candidates[popup.selected].text.clone();
+                let start = popup.start;
+                self.vim.buffer.replace_before_cursor(start, &text);
+                self.completion = None;
+            },
+            Key::Esc => self.completion = None,
+            _ => {
+                self.completion = None;
+                return None;
+            },
         }
+        Some(Msg::None)
+    }
+
+    fn view_completion(&self, frame: &mut Frame, cursor: (u16, u16)) {
+        let popup = match &self.completion {
+            Some(popup) => popup,
+            None => return,
+        };
+        let screen = frame.area();
+        let items: Vec<ListItem> = popup.candidates.iter()
+            .map(|candidate| ListItem::new(Line::from(vec![
+                Span::raw(candidate.text.clone()),
+                Span::raw(" "),
+                Span::styled(candidate.detail.clone(), Style::default().fg(Color::Gray)),
+            ])))
+            .collect();
+        let width = popup.candidates.iter()
+            .map(|candidate| candidate.text.chars().count() + candidate.detail.chars().count() + 3)
+            .max()
+            .unwrap_or(0)
+            .min(60) as u16;
+        let width = width.min(screen.width);
+        let height = (popup.candidates.len() as u16 + 2).min(screen.height);
+        // below the cursor line when it fits, above otherwise
+        let y = if cursor.1 + 1 + height <= screen.bottom() { cursor.1 + 1 } else { cursor.1.saturating_sub(height) };
+        let x = cursor.0.min(screen.right().saturating_sub(width));
+        let area = Rect { x, y, width, height };
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded).border_style(Style::default().fg(Color::Gray)))
+            .highlight_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+        let mut state = ListState::default().with_selected(Some(popup.selected));
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut state);
     }
 }
 
 impl Component<Msg, AppEvent> for EditorInput {
     fn on(&mut self, ev: tuirealm::Event<AppEvent>) -> Option<Msg> {
-        self.component.attr(
-            Attribute::Custom(TEXTAREA_STATUS_FMT),
-            AttrValue::Payload(PropPayload::Tup2((
-                PropValue::Str(self.input_mode.clone().into()),
-                PropValue::Style(self.input_mode.clone().into()),
-            ))),
-        );
-
-        match self.input_mode {
-            InputMode::Input => {
-                match ev {
-                    Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
-                        self.input_mode = InputMode::Normal;
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Char(ch), .. }) => {
-                        self.component.perform(Cmd::Type(ch));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Left, .. }) => {
-                        self.component.perform(Cmd::Move(Direction::Left));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Right, .. }) => {
-                        self.component.perform(Cmd::Move(Direction::Right));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Down, .. }) => {
-                        self.component.perform(Cmd::Move(Direction::Down));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
-                        self.component.perform(Cmd::Move(Direction::Up));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Backspace, .. }) => {
-                        self.component.perform(Cmd::Delete);
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Delete, .. }) => {
-                        self.component.perform(Cmd::Cancel);
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Enter, .. }) => {
-                        self.component.perform(Cmd::Custom(TEXTAREA_CMD_NEWLINE));
-                        Some(Msg::None)
-                    }
-                    _ => Some(Msg::None)
+        let key = match ev {
+            Event::Keyboard(key) => key,
+            _ => return Some(Msg::None),
+        };
+        match self.vim.mode {
+            Mode::Insert => {
+                if let Some(msg) = self.handle_completion(key.code) {
+                    return Some(msg);
                 }
-            },
-            InputMode::Normal => {
-                match ev {
-                    Event::Keyboard(KeyEvent { code: Key::Enter, ..}) => Some(Msg::EditorAccept),
-                    Event::Keyboard(KeyEvent { code: Key::Tab, ..}) => Some(Msg::EditorPopupNext),
-                
-                    Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => Some(Msg::DiactivateEditor),
-                    Event::Keyboard(KeyEvent { code: Key::Char('i'), .. }) => {
-                        self.input_mode = InputMode::Input;
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Char('a'), .. }) => {
-                        self.input_mode = InputMode::Input;
-                        self.component.perform(Cmd::Move(Direction::Right));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Char('I'), .. }) => {
-                        self.input_mode = InputMode::Input;
-                        self.component.perform(Cmd::GoTo(Position::Begin));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Char('A'), .. }) => {
-                        self.input_mode = InputMode::Input;
-                        self.component.perform(Cmd::GoTo(Position::End));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Char('h'), .. }) => {
-                        self.component.perform(Cmd::Move(Direction::Left));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Char('l'), .. }) => {
-                        self.component.perform(Cmd::Move(Direction::Right));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Char('j'), .. }) => {
-                        self.component.perform(Cmd::Move(Direction::Down));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Char('k'), .. }) => {
-                        self.component.perform(Cmd::Move(Direction::Up));
-                        Some(Msg::None)
-                    },
-                    Event::Keyboard(KeyEvent { code: Key::Char('o'), .. }) => {
-                        self.component.perform(Cmd::Custom(TEXTAREA_CMD_NEWLINE));
-                        Some(Msg::None)
-                    },
-                    _ => Some(Msg::None)
+                if key.code == Key::Tab {
+                    self.complete();
+                    return Some(Msg::None);
                 }
             },
+            mode => match key.code {
+                Key::Enter if mode == Mode::Normal => return Some(Msg::EditorAccept),
+                Key::Tab => return Some(Msg::EditorPopupNext),
+                Key::Esc if self.vim.is_idle() => return Some(Msg::DiactivateEditor),
+                _ => {},
+            },
         }
+        self.vim.handle_key(key);
+        Some(Msg::None)
     }
 }
 
 impl MockComponent for EditorInput {
     fn view(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect) {
-        self.component.view(frame, area);
+        let focused = self.props.get_or(Attribute::Focus, AttrValue::Flag(false)).unwrap_flag();
+        let mut block = Block::default()
+            .title(self.title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title_bottom(Line::from(Span::styled(format!(" {} ", self.vim.mode.name()), self.vim.mode.style())));
+        let tokens = tokenize(self.syntax, self.vim.buffer.lines());
+        if has_unbalanced(&tokens) {
+            block = block.title_bottom(Line::from(Span::styled(" unbalanced quote or parenthesis ", TokenKind::Unbalanced.style())));
+        }
+        if let Some(hint) = self.completions.hint(self.vim.buffer.current_line()) {
+            block = block.title_bottom(Line::from(Span::styled(format!(" {} ", hint), Style::default().fg(Color::Gray))));
+        }
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // keep the cursor in view
+        let (row, col) = self.vim.buffer.cursor();
+        let (height, width) = (inner.height.max(1) as usize, inner.width.max(1) as usize);
+        let (top, left) = (Vim::scroll_to(self.scroll.0, row, height), Vim::scroll_to(self.scroll.1, col, width));
+        self.scroll = (top, left);
+
+        let lines: Vec<Line> = (top..self.vim.buffer.line_count()).take(height)
+            .map(|index| {
+                let styles = line_styles(&tokens, index, self.vim.buffer.line_len(index));
+                self.vim.styled_line(index, left, styles, focused, None)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), inner);
+
+        if focused {
+            let cursor = (inner.x + (col - left) as u16, inner.y + (row - top) as u16);
+            self.view_completion(frame, cursor);
+        }
     }
 
     fn query(&self, attr: Attribute) -> Option<AttrValue> {
-        self.component.query(attr)
+        self.props.get(attr)
     }
 
     fn attr(&mut self, attr: Attribute, value: AttrValue) {
-        self.component.attr(attr, value);
+        self.props.set(attr, value);
     }
 
-    fn state(&self) -> tuirealm::State {
-        self.component.state()
+    fn state(&self) -> State {
+        State::Vec(self.vim.buffer.lines().iter().map(|line| StateValue::String(line.clone())).collect())
     }
 
-    fn perform(&mut self, cmd: Cmd) -> CmdResult {
-        self.component.perform(cmd)
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
     }
 }
 
 impl EditorPopupWidget for EditorInput {
     fn get_content(&self) -> Vec<String> {
-        match self.component.state() {
-            tuirealm::State::One(text_val) => {
-                text_val.unwrap_string()
-                    .lines()
-                    .map(|s| s.to_string())
-                    .collect()
-            },
-            tuirealm::State::Vec(lines) => {
-                lines.iter()
-                    .map(|v| v.clone().unwrap_string())
-                    .collect()
-            },
-            _ => vec![]
-        }
+        self.vim.buffer.lines().to_vec()
     }
-    
+
     fn get_editor_type(&self) -> &'static str {
         self.editor_type
     }
-}
 
+    fn set_content(&mut self, content: Vec<String>) {
+        self.vim.set_content(content);
+        self.completion = None;
+        self.scroll = (0, 0);
+    }
+
+    fn set_completions(&mut self, completions: Completions) {
+        self.completions = completions;
+    }
+
+    fn set_syntax(&mut self, syntax: Syntax) {
+        self.syntax = syntax;
+    }
+}
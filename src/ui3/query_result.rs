@@ -1,13 +1,19 @@
 use ratatui::{layout::Alignment, style::Color};
-use tuirealm::{event::{Key, KeyEvent}, props::{BorderType, Borders, Table, TableBuilder, TextSpan}, AttrValue, Attribute, Component, Event, MockComponent};
+use tuirealm::{command::{Cmd, CmdResult}, event::{Key, KeyEvent}, props::{BorderType, Borders, Table, TableBuilder, TextSpan}, AttrValue, Attribute, Component, Event, MockComponent, State};
 
-use crate::dbclient::fetcher::FetchResult;
+use std::collections::{HashMap, HashSet};
 
-use super::{AppEvent, EditorType, Msg, APP_SEARCH_PATTERN};
+use crate::dbclient::fetcher::{FetchResult, TreeRow};
+
+use super::{AppEvent, EditorType, Msg, APP_LIST_INDEX, APP_SEARCH_PATTERN};
+
+const DIFF_COLOR: Color = Color::Red;
 
-#[derive(MockComponent)]
 pub struct QueryResult {
     component: tui_realm_stdlib::Table,
+    pending_g: bool,
+    // the right pane of the split view
+    compare: bool,
 }
 
 impl Default for QueryResult {
@@ -25,16 +31,91 @@ impl Default for QueryResult {
             );
 
         Self {
-            component: table
+            component: table,
+            pending_g: false,
+            compare: false,
         }
     }
 }
 
+impl MockComponent for QueryResult {
+    fn view(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect) {
+        self.component.view(frame, area);
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.component.query(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        match (attr, value) {
+            (Attribute::Custom(APP_LIST_INDEX), AttrValue::Number(index)) => {
+                self.component.states.list_index = index.max(0) as usize;
+                self.component.states.fix_list_index();
+            },
+            (attr, value) => self.component.attr(attr, value),
+        }
+    }
+
+    fn state(&self) -> State {
+        self.component.state()
+    }
+
+    fn perform(&mut self, cmd: Cmd) -> CmdResult {
+        self.component.perform(cmd)
+    }
+}
+
 impl Component<Msg, AppEvent> for QueryResult {
     fn on(&mut self, ev: tuirealm::Event<AppEvent>) -> Option<Msg> {
+        let list_index = self.component.states.list_index;
+        let msg = self.handle_event(ev);
+        if self.component.states.list_index != list_index && matches!(msg, Some(Msg::None) | None) {
+            return Some(Msg::SyncResultScroll(self.component.states.list_index));
+        }
+        msg
+    }
+}
+
+impl QueryResult {
+    pub fn compare() -> Self {
+        Self {
+            compare: true,
+            ..Self::default()
+        }
+    }
+
+    fn handle_event(&mut self, ev: tuirealm::Event<AppEvent>) -> Option<Msg> {
+        let pending_g = std::mem::take(&mut self.pending_g);
         match ev {
+            Event::Keyboard(KeyEvent { code: Key::Char('g'), .. }) if !pending_g => {
+                self.pending_g = true;
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('t'), .. }) if pending_g => Some(Msg::NextSession),
+            Event::Keyboard(KeyEvent { code: Key::Char('T'), .. }) if pending_g => Some(Msg::PrevSession),
+            Event::Keyboard(KeyEvent { code: Key::Char(number @ '1'..='9'), .. }) => Some(Msg::ToSession(number as usize - '1' as usize)),
+            Event::Keyboard(KeyEvent { code: Key::Char('x'), .. }) => Some(Msg::CloseSession),
+            Event::Keyboard(KeyEvent { code: Key::Char('I'), .. }) => Some(Msg::OpenDashboard),
+            Event::Keyboard(KeyEvent { code: Key::Char('O'), .. }) => Some(Msg::OpenSlowlog),
+            Event::Keyboard(KeyEvent { code: Key::Char('W'), .. }) => Some(Msg::OpenMonitor),
+            Event::Keyboard(KeyEvent { code: Key::Char('P'), .. }) => Some(Msg::OpenPubSub),
+            Event::Keyboard(KeyEvent { code: Key::Char('F'), .. }) => Some(Msg::OpenFunctions),
+            Event::Keyboard(KeyEvent { code: Key::Char('E'), .. }) => Some(Msg::ActivateEditor(EditorType::Script)),
+            Event::Keyboard(KeyEvent { code: Key::Char('B'), .. }) => Some(Msg::ActivateEditor(EditorType::BeginTransaction)),
+            Event::Keyboard(KeyEvent { code: Key::Char('X'), .. }) => Some(Msg::ExecTransaction),
+            Event::Keyboard(KeyEvent { code: Key::Char('R'), .. }) => Some(Msg::DiscardTransaction),
             Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => Some(Msg::ToConnectionsPage),
+            Event::Keyboard(KeyEvent { code: Key::Char('H') | Key::Left, ..}) if self.compare => Some(Msg::ToQueryResultWidget),
             Event::Keyboard(KeyEvent { code: Key::Char('H') | Key::Left, ..}) => Some(Msg::ToDbObjectsWidget),
+            Event::Keyboard(KeyEvent { code: Key::Char('L') | Key::Right, ..}) if !self.compare => Some(Msg::ToCompareResultWidget),
+            Event::Keyboard(KeyEvent { code: Key::Char('s'), ..}) => Some(Msg::ActivateEditor(EditorType::SplitView)),
+            Event::Keyboard(KeyEvent { code: Key::Char('C'), ..}) => Some(Msg::OpenKeyspaceDiff),
+            Event::Keyboard(KeyEvent { code: Key::Char('w'), ..}) => Some(Msg::ActivateEditor(EditorType::ExportResult)),
+            Event::Keyboard(KeyEvent { code: Key::Char('t'), ..}) if !self.compare => Some(Msg::ToggleTreeMode),
+            Event::Keyboard(KeyEvent { code: Key::Enter | Key::Char(' '), ..}) if !self.compare => Some(Msg::ToggleTreeNode(self.component.states.list_index)),
+            Event::Keyboard(KeyEvent { code: Key::Char('+'), ..}) if !self.compare => Some(Msg::ExpandTree(true)),
+            Event::Keyboard(KeyEvent { code: Key::Char('-'), ..}) if !self.compare => Some(Msg::ExpandTree(false)),
             Event::Keyboard(KeyEvent { code: Key::Char('q'), ..}) => Some(Msg::ActivateEditor(EditorType::Query)),
             Event::Keyboard(KeyEvent { code: Key::Char('j') | Key::Down, ..}) => {
                 self.component.states.incr_list_index(true);
@@ -100,32 +181,146 @@ impl Component<Msg, AppEvent> for QueryResult {
             _ => Some(Msg::None)
         }
     }
-}
 
-impl QueryResult {
     pub fn build_result_table(result: FetchResult) -> Table {
-        if result.table.is_none() {
-            return vec![];
-        }
+        Self::build_table(&result, None)
+    }
+
+    // Cells that differ from the same column and row of `other` (the same member or field for sets and hashes) are highlighted
+    pub fn build_compared_table(result: &FetchResult, other: &FetchResult) -> Table {
+        Self::build_table(result, Some(other))
+    }
+
+    fn build_table(result: &FetchResult, other: Option<&FetchResult>) -> Table {
+        let table = match &result.table {
+            Some(table) => table,
+            None => return vec![],
+        };
+        let other_table = other.map(|other| other.table.as_ref().map(|table| &table.1));
         let mut table_builder = TableBuilder::default();
-        let table = result.table.unwrap();
 
-        let headers: Vec<TextSpan> = table.1.keys().cloned().map(|key| TextSpan::new(key)).collect();
-        for header in headers {
-            table_builder.add_col(header);
+        // index columns in their order, then the rest by name
+        let mut headers: Vec<&String> = vec![];
+        for column in &table.0 {
+            if table.1.contains_key(column) && !headers.contains(&column) {
+                headers.push(column);
+            }
+        }
+        let mut rest: Vec<&String> = table.1.keys().filter(|key| !headers.contains(key)).collect();
+        rest.sort();
+        headers.extend(rest);
+
+        for header in &headers {
+            let span = TextSpan::new(header.as_str());
+            match other_table {
+                Some(other) if !other.is_some_and(|other| other.contains_key(*header)) => table_builder.add_col(span.fg(DIFF_COLOR)),
+                _ => table_builder.add_col(span),
+            };
         }
 
+        // members of sets and fields of hashes come back in server order, their rows are matched
+        // by the first index column instead of by position
+        let row_key = table.0.first().filter(|_| result.is_unordered() && other.is_some_and(FetchResult::is_unordered));
+        let other_rows: Option<HashMap<&String, usize>> = row_key.map(|row_key| other_table
+            .flatten()
+            .and_then(|other| other.get(row_key))
+            .map(|column| column.iter().enumerate().map(|(index, key)| (key, index)).collect())
+            .unwrap_or_default());
+
         let max_len = table.1.values().map(|v| v.len()).max().unwrap_or(0);
         for row_index in 0..max_len {
             table_builder.add_row();
-            for column in table.1.values() {
-                let val = column.get(row_index).cloned().unwrap_or_else(|| "".to_string());
-                table_builder.add_col(TextSpan::new(val));
+            let other_index = match (&other_rows, row_key) {
+                (Some(other_rows), Some(row_key)) => table.1[row_key].get(row_index).and_then(|key| other_rows.get(key).copied()),
+                _ => Some(row_index),
+            };
+            for header in &headers {
+                let val = table.1[*header].get(row_index).cloned().unwrap_or_else(|| "".to_string());
+                let other_val = other_table.map(|other| other
+                    .and_then(|other| other.get(*header))
+                    .and_then(|column| other_index.and_then(|other_index| column.get(other_index)))
+                    .cloned()
+                    .unwrap_or_default());
+                match other_val {
+                    // a row missing on the other side differs even when its cells are empty
+                    Some(_) if other_index.is_none() => table_builder.add_col(TextSpan::new(val).fg(DIFF_COLOR)),
+                    Some(other_val) if other_val != val => table_builder.add_col(TextSpan::new(val).fg(DIFF_COLOR)),
+                    _ => table_builder.add_col(TextSpan::new(val)),
+                };
             }
         }
         table_builder.build()
     }
 
+    // Nested reply as indented rows, `▸` marks collapsed nodes and `▾` expanded ones
+    pub fn build_tree_table(rows: &[TreeRow], collapsed: &HashSet<String>) -> Table {
+        let mut table_builder = TableBuilder::default();
+        table_builder
+            .add_col(TextSpan::new("index"))
+            .add_col(TextSpan::new("type"))
+            .add_col(TextSpan::new("value"));
+        for row in rows {
+            let marker = match (row.children, collapsed.contains(&row.path)) {
+                (0, _) => "  ",
+                (_, true) => "▸ ",
+                (_, false) => "▾ ",
+            };
+            table_builder.add_row()
+                .add_col(TextSpan::new(format!("{}{}{}", "  ".repeat(row.depth), marker, row.key)))
+                .add_col(TextSpan::new(&row.type_name).fg(Color::Cyan))
+                .add_col(TextSpan::new(&row.value));
+        }
+        table_builder.build()
+    }
+
+    // Queued commands of a transaction, with the reply of each one once it ran
+    pub fn build_transaction_table(queries: &[String], results: Option<&[FetchResult]>) -> Table {
+        let mut table_builder = TableBuilder::default();
+        table_builder
+            .add_col(TextSpan::new("#"))
+            .add_col(TextSpan::new("command"));
+        if results.is_some() {
+            table_builder.add_col(TextSpan::new("result"));
+        }
+        for (index, query) in queries.iter().enumerate() {
+            table_builder.add_row()
+                .add_col(TextSpan::new((index + 1).to_string()))
+                .add_col(TextSpan::new(query).fg(Color::Blue));
+            if let Some(result) = results.and_then(|results| results.get(index)) {
+                table_builder.add_col(Self::result_cell(result));
+            }
+        }
+        table_builder.build()
+    }
+
+    // The whole result in one cell, nested values inline
+    fn result_cell(result: &FetchResult) -> TextSpan {
+        match (&result.reply, &result.table) {
+            (Some(reply), _) => TextSpan::new(reply.inline()),
+            (None, Some((_, columns))) if columns.contains_key("error") => TextSpan::new(columns["error"].join(", ")).fg(Color::Red),
+            (None, Some((index, columns))) => TextSpan::new(index.iter()
+                .filter_map(|column| columns.get(column))
+                .flatten()
+                .cloned()
+                .collect::<Vec<String>>()
+                .join(", ")),
+            (None, None) => TextSpan::new("(nil)"),
+        }
+    }
+
+    // The first row of the table is the header
+    pub fn table_to_csv(table: &Table) -> String {
+        let escape = |cell: &str| if cell.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.to_string()
+        };
+        table.iter()
+            .map(|row| row.iter().map(|span| escape(&span.content)).collect::<Vec<String>>().join(","))
+            .map(|line| line + "\n")
+            .collect()
+    }
+
     pub fn get_current_table(&self) -> Vec<Vec<String>> {
         self.component.query(Attribute::Content).and_then(|val| {
             match val {
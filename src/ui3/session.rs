@@ -0,0 +1,82 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::dbclient::fetcher::{FetchRequest, Fetcher, Reply};
+
+use super::Id;
+
+const HISTORY_SIZE: usize = 100;
+
+// Everything the query page needs for one open connection.
+// Each session has its own DbObjects and QueryResult components mounted under its id.
+pub struct Session {
+    pub id: usize,
+    pub connection: usize,
+    pub fetcher: Box<dyn Fetcher>,
+    pub selected_widget: Id,
+    pub query: Vec<String>,
+    pub history: Vec<String>,
+    pub last_request: Option<FetchRequest>,
+    pub compare: Option<Compare>,
+    // nested reply of the last query, shown as a tree in tree mode
+    pub reply: Option<Reply>,
+    pub tree_mode: bool,
+    // tree paths whose children are hidden
+    pub collapsed: HashSet<String>,
+    // fields of the last script editor, restored when it opens again
+    pub script: HashMap<&'static str, Vec<String>>,
+    pub transaction: Option<Transaction>,
+}
+
+// Queries typed while a transaction is in progress, run together on commit
+pub struct Transaction {
+    pub watch: Vec<String>,
+    pub queries: Vec<String>,
+}
+
+// Connection bound to the right pane of the split view
+pub struct Compare {
+    pub connection: usize,
+    pub fetcher: Box<dyn Fetcher>,
+}
+
+impl Session {
+    pub fn new(id: usize, connection: usize, fetcher: Box<dyn Fetcher>) -> Self {
+        Self {
+            id,
+            connection,
+            fetcher,
+            selected_widget: Id::DbObjects(id),
+            query: vec![],
+            history: vec![],
+            last_request: None,
+            compare: None,
+            reply: None,
+            tree_mode: false,
+            collapsed: HashSet::new(),
+            script: HashMap::new(),
+            transaction: None,
+        }
+    }
+
+    pub fn db_objects_id(&self) -> Id {
+        Id::DbObjects(self.id)
+    }
+
+    pub fn query_result_id(&self) -> Id {
+        Id::QueryResult(self.id)
+    }
+
+    pub fn compare_result_id(&self) -> Id {
+        Id::CompareResult(self.id)
+    }
+
+    pub fn push_history(&mut self, query: String) {
+        if query.trim().is_empty() || self.history.last() == Some(&query) {
+            return;
+        }
+        self.history.push(query);
+        if self.history.len() > HISTORY_SIZE {
+            self.history.remove(0);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use ratatui::{style::{Modifier, Style}, text::{Line, Span}, widgets::Tabs};
+use tuirealm::{command::{Cmd, CmdResult}, props::{PropPayload, PropValue, Props, TextSpan}, AttrValue, Attribute, Component, MockComponent, State};
+
+use super::{AppEvent, Msg};
+
+// One line tab bar with the open sessions, never focused
+#[derive(Default)]
+pub struct SessionTabs {
+    props: Props,
+}
+
+impl SessionTabs {
+    pub fn build_tabs_payload(tabs: Vec<TextSpan>) -> AttrValue {
+        AttrValue::Payload(PropPayload::Vec(tabs.into_iter().map(PropValue::TextSpan).collect()))
+    }
+}
+
+impl MockComponent for SessionTabs {
+    fn view(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect) {
+        let titles: Vec<Line> = match self.props.get(Attribute::Text) {
+            Some(AttrValue::Payload(PropPayload::Vec(tabs))) => tabs.into_iter().filter_map(|tab| match tab {
+                PropValue::TextSpan(span) => Some(Line::from(Span::styled(span.content, Style::default().fg(span.fg)))),
+                _ => None,
+            }).collect(),
+            _ => vec![],
+        };
+        let selected = match self.props.get(Attribute::Value) {
+            Some(AttrValue::Number(selected)) => selected.max(0) as usize,
+            _ => 0,
+        };
+        let tabs = Tabs::new(titles)
+            .select(selected)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD))
+            .divider("|");
+        frame.render_widget(tabs, area);
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.props.get(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        self.props.set(attr, value);
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, AppEvent> for SessionTabs {
+    fn on(&mut self, _ev: tuirealm::Event<AppEvent>) -> Option<Msg> {
+        None
+    }
+}
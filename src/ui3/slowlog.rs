@@ -0,0 +1,255 @@
+use ratatui::{layout::{Alignment, Constraint, Direction, Layout, Rect}, style::Color, Frame};
+use tuirealm::{command::{Cmd, CmdResult}, event::{Key, KeyEvent}, props::{BorderType, Borders, Props, Table, TableBuilder, TextSpan}, AttrValue, Attribute, Component, Event, MockComponent, State};
+
+use crate::dbclient::redis::{slowlog::{fetch_latency_history, fetch_latency_latest, fetch_slowlog, format_timestamp, LatencyEvent, SlowlogEntry}, PollConnection, RedisConfig};
+
+use super::{AppEvent, EditorType, Msg};
+
+const SLOWLOG_COLUMNS: [&str; 5] = ["id", "timestamp", "duration", "command", "client"];
+
+#[derive(PartialEq)]
+enum Pane {
+    Slowlog,
+    Latency,
+}
+
+// SLOWLOG entries on top, LATENCY LATEST events (or the HISTORY of one of them) below
+pub struct SlowlogView {
+    props: Props,
+    connection: PollConnection,
+    slowlog: tui_realm_stdlib::Table,
+    latency: tui_realm_stdlib::Table,
+    entries: Vec<SlowlogEntry>,
+    events: Vec<LatencyEvent>,
+    // event whose history is shown instead of the latest events
+    history: Option<String>,
+    sort_column: usize,
+    descending: bool,
+    focus: Pane,
+}
+
+impl SlowlogView {
+    pub fn new(config: RedisConfig) -> Self {
+        let mut view = Self {
+            props: Props::default(),
+            connection: PollConnection::new(&config.uri),
+            slowlog: Self::table("Slow log").widths(&[8, 20, 12, 40, 20]),
+            latency: Self::table("Latency events").widths(&[30, 25, 20, 25]),
+            entries: vec![],
+            events: vec![],
+            history: None,
+            sort_column: 0,
+            descending: true,
+            focus: Pane::Slowlog,
+        };
+        view.refresh();
+        view
+    }
+
+    fn table(title: &str) -> tui_realm_stdlib::Table {
+        tui_realm_stdlib::Table::default()
+            .title(title, Alignment::Left)
+            .highlighted_color(Color::Yellow)
+            .highlighted_str("> ")
+            .scroll(true)
+            .rewind(true)
+            .borders(Borders::default().modifiers(BorderType::Rounded).color(Color::Gray))
+    }
+
+    fn refresh(&mut self) {
+        match self.connection.run(fetch_slowlog) {
+            Ok(entries) => {
+                self.entries = entries;
+                self.reload_slowlog();
+            },
+            Err(err) => self.slowlog.attr(Attribute::Content, AttrValue::Table(Self::error_table(err.to_string()))),
+        }
+        self.history = None;
+        match self.connection.run(fetch_latency_latest) {
+            Ok(events) => {
+                self.events = events;
+                self.reload_latency();
+            },
+            Err(err) => self.latency.attr(Attribute::Content, AttrValue::Table(Self::error_table(err.to_string()))),
+        }
+        self.update_focus();
+    }
+
+    fn error_table(err: String) -> Table {
+        let mut table = TableBuilder::default();
+        table.add_col(TextSpan::from(err).fg(Color::Red));
+        table.build()
+    }
+
+    fn reload_slowlog(&mut self) {
+        let mut entries: Vec<&SlowlogEntry> = self.entries.iter().collect();
+        entries.sort_by(|left, right| match self.sort_column {
+            0 => left.id.cmp(&right.id),
+            1 => left.timestamp.cmp(&right.timestamp),
+            2 => left.duration.cmp(&right.duration),
+            3 => left.command.cmp(&right.command),
+            _ => left.client.cmp(&right.client),
+        });
+        if self.descending {
+            entries.reverse();
+        }
+
+        let mut table = TableBuilder::default();
+        for (index, column) in SLOWLOG_COLUMNS.iter().enumerate() {
+            let header = match (index == self.sort_column, self.descending) {
+                (true, true) => format!("{} ▼", column),
+                (true, false) => format!("{} ▲", column),
+                (false, _) => column.to_string(),
+            };
+            table.add_col(TextSpan::from(header).bold());
+        }
+        for entry in entries {
+            table.add_row()
+                .add_col(TextSpan::from(entry.id.to_string()))
+                .add_col(TextSpan::from(format_timestamp(entry.timestamp)))
+                .add_col(TextSpan::from(format!("{} µs", entry.duration)).fg(Color::Yellow))
+                .add_col(TextSpan::from(entry.command.clone()).fg(Color::Blue))
+                .add_col(TextSpan::from(entry.client.clone()).fg(Color::Gray));
+        }
+        self.slowlog.attr(Attribute::Content, AttrValue::Table(table.build()));
+    }
+
+    fn reload_latency(&mut self) {
+        let mut table = TableBuilder::default();
+        table
+            .add_col(TextSpan::from("event").bold())
+            .add_col(TextSpan::from("timestamp").bold())
+            .add_col(TextSpan::from("latest").bold())
+            .add_col(TextSpan::from("max").bold());
+        for event in &self.events {
+            table.add_row()
+                .add_col(TextSpan::from(event.name.clone()).fg(Color::Blue))
+                .add_col(TextSpan::from(format_timestamp(event.timestamp)))
+                .add_col(TextSpan::from(format!("{} ms", event.latest)).fg(Color::Yellow))
+                .add_col(TextSpan::from(format!("{} ms", event.max)).fg(Color::Red));
+        }
+        self.latency.attr(Attribute::Title, AttrValue::Title(("Latency events".to_string(), Alignment::Left)));
+        self.latency.attr(Attribute::Content, AttrValue::Table(table.build()));
+    }
+
+    fn show_history(&mut self, event: String) {
+        let table = match self.connection.run(|connection| fetch_latency_history(connection, &event)) {
+            Ok(samples) => {
+                let mut table = TableBuilder::default();
+                table
+                    .add_col(TextSpan::from("timestamp").bold())
+                    .add_col(TextSpan::from("latency").bold());
+                for (timestamp, latency) in samples.iter().rev() {
+                    table.add_row()
+                        .add_col(TextSpan::from(format_timestamp(*timestamp)))
+                        .add_col(TextSpan::from(format!("{} ms", latency)).fg(Color::Yellow));
+                }
+                table.build()
+            },
+            Err(err) => Self::error_table(err.to_string()),
+        };
+        let title = format!("Latency history: {} (<Backspace> to go back)", event);
+        self.latency.attr(Attribute::Title, AttrValue::Title((title, Alignment::Left)));
+        self.latency.attr(Attribute::Content, AttrValue::Table(table));
+        self.history = Some(event);
+    }
+
+    fn update_focus(&mut self) {
+        let color = |focused: bool| if focused { Color::Yellow } else { Color::Gray };
+        self.slowlog.attr(Attribute::Borders, AttrValue::Borders(Borders::default().modifiers(BorderType::Rounded).color(color(self.focus == Pane::Slowlog))));
+        self.latency.attr(Attribute::Borders, AttrValue::Borders(Borders::default().modifiers(BorderType::Rounded).color(color(self.focus == Pane::Latency))));
+    }
+
+    fn focused_table(&mut self) -> &mut tui_realm_stdlib::Table {
+        match self.focus {
+            Pane::Slowlog => &mut self.slowlog,
+            Pane::Latency => &mut self.latency,
+        }
+    }
+}
+
+impl MockComponent for SlowlogView {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(2), Constraint::Fill(1)])
+            .split(area);
+        self.slowlog.view(frame, chunks[0]);
+        self.latency.view(frame, chunks[1]);
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.props.get(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        self.props.set(attr, value);
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, AppEvent> for SlowlogView {
+    fn on(&mut self, ev: Event<AppEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc | Key::Char('q'), .. }) => Some(Msg::CloseSlowlog),
+            Event::Keyboard(KeyEvent { code: Key::Char('j') | Key::Down, .. }) => {
+                // the first row is the header
+                let table = self.focused_table();
+                table.states.incr_list_index(true);
+                if table.states.list_index == 0 {
+                    table.states.incr_list_index(true);
+                }
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('k') | Key::Up, .. }) => {
+                let table = self.focused_table();
+                table.states.decr_list_index(true);
+                if table.states.list_index == 0 {
+                    table.states.decr_list_index(true);
+                }
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                self.focus = if self.focus == Pane::Slowlog { Pane::Latency } else { Pane::Slowlog };
+                self.update_focus();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('s'), .. }) => {
+                self.sort_column = (self.sort_column + 1) % SLOWLOG_COLUMNS.len();
+                self.reload_slowlog();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('S'), .. }) => {
+                self.descending = !self.descending;
+                self.reload_slowlog();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Enter, .. }) if self.focus == Pane::Latency && self.history.is_none() => {
+                let index = self.latency.states.list_index;
+                if let Some(event) = index.checked_sub(1).and_then(|index| self.events.get(index)) {
+                    let name = event.name.clone();
+                    self.show_history(name);
+                }
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Backspace, .. }) if self.history.is_some() => {
+                self.history = None;
+                self.reload_latency();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('r'), .. }) => {
+                self.refresh();
+                Some(Msg::None)
+            },
+            Event::Keyboard(KeyEvent { code: Key::Char('R'), .. }) => Some(Msg::ActivateEditor(EditorType::ResetSlowlog)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use ratatui::{layout::Alignment, style::Color};
+use tui_realm_stdlib::Label;
+use tuirealm::{Component, MockComponent};
+
+use super::{AppEvent, Msg};
+
+#[derive(MockComponent)]
+pub struct StatusLine {
+    component: Label,
+}
+
+impl Default for StatusLine {
+    fn default() -> Self {
+        Self {
+            component: Label::default()
+                .text("")
+                .alignment(Alignment::Left)
+                .foreground(Color::Gray)
+        }
+    }
+}
+
+impl Component<Msg, AppEvent> for StatusLine {
+    fn on(&mut self, _: tuirealm::Event<AppEvent>) -> Option<Msg> {
+        None
+    }
+}
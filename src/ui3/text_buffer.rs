@@ -0,0 +1,199 @@
+// Lines of text with a cursor, the cursor column counts chars, not bytes
+#[derive(Debug, PartialEq, Clone)]
+pub struct TextBuffer {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+}
+
+impl Default for TextBuffer {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl TextBuffer {
+    pub fn new(lines: Vec<String>) -> Self {
+        let lines = if lines.is_empty() { vec![String::new()] } else { lines };
+        Self { lines, row: 0, col: 0 }
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    pub fn current_line(&self) -> &str {
+        &self.lines[self.row]
+    }
+
+    // Text of the current line left of the cursor
+    pub fn before_cursor(&self) -> &str {
+        let line = self.current_line();
+        &line[..byte_index(line, self.col)]
+    }
+
+    pub fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn char_at(&self, (row, col): (usize, usize)) -> Option<char> {
+        self.lines.get(row).and_then(|line| line.chars().nth(col))
+    }
+
+    // Moves the cursor, clamped to the buffer
+    pub fn set_cursor(&mut self, (row, col): (usize, usize)) {
+        self.row = row.min(self.lines.len() - 1);
+        self.col = col.min(self.line_len(self.row));
+    }
+
+    // Text from `from` up to `to` excluded, lines joined with '\n'
+    pub fn text_range(&self, from: (usize, usize), to: (usize, usize)) -> String {
+        let (from, to) = (from.min(to), from.max(to));
+        let mut text = String::new();
+        for row in from.0..=to.0.min(self.lines.len() - 1) {
+            let line = &self.lines[row];
+            let start = if row == from.0 { byte_index(line, from.1) } else { 0 };
+            let end = if row == to.0 { byte_index(line, to.1) } else { line.len() };
+            text.push_str(&line[start..end.max(start)]);
+            if row < to.0 {
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    // Removes the text from `from` up to `to` excluded and returns it, the cursor moves to `from`
+    pub fn delete_range(&mut self, from: (usize, usize), to: (usize, usize)) -> String {
+        let (from, to) = (from.min(to), from.max(to));
+        let to = (to.0.min(self.lines.len() - 1), to.1);
+        let text = self.text_range(from, to);
+        let tail = {
+            let line = &self.lines[to.0];
+            line[byte_index(line, to.1)..].to_string()
+        };
+        let head = {
+            let line = &self.lines[from.0];
+            line[..byte_index(line, from.1)].to_string()
+        };
+        self.lines.splice(from.0..=to.0, [head + &tail]);
+        self.set_cursor(from);
+        text
+    }
+
+    // Removes up to `count` lines from `row`, an emptied buffer keeps one empty line
+    pub fn delete_lines(&mut self, row: usize, count: usize) -> Vec<String> {
+        let end = (row + count).min(self.lines.len());
+        let removed: Vec<String> = self.lines.drain(row..end).collect();
+        if self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.set_cursor((row, 0));
+        removed
+    }
+
+    // Inserts whole lines before `row`, the cursor moves to the first of them
+    pub fn insert_lines(&mut self, row: usize, lines: Vec<String>) {
+        let row = row.min(self.lines.len());
+        self.lines.splice(row..row, lines);
+        self.set_cursor((row, 0));
+    }
+
+    pub fn insert_char(&mut self, ch: char) {
+        let index = byte_index(&self.lines[self.row], self.col);
+        self.lines[self.row].insert(index, ch);
+        self.col += 1;
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        for ch in text.chars() {
+            if ch == '\n' {
+                self.newline();
+            } else {
+                self.insert_char(ch);
+            }
+        }
+    }
+
+    pub fn newline(&mut self) {
+        let index = byte_index(&self.lines[self.row], self.col);
+        let rest = self.lines[self.row].split_off(index);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.col = 0;
+    }
+
+    // Removes the char left of the cursor, joining lines at the line start
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let index = byte_index(&self.lines[self.row], self.col);
+            self.lines[self.row].remove(index);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    // Removes the char under the cursor, joining lines at the line end
+    pub fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let index = byte_index(&self.lines[self.row], self.col);
+            self.lines[self.row].remove(index);
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    // Replaces the text between `start` and the cursor on the current line
+    pub fn replace_before_cursor(&mut self, start: usize, text: &str) {
+        let line = &mut self.lines[self.row];
+        let range = byte_index(line, start.min(self.col))..byte_index(line, self.col);
+        line.replace_range(range, text);
+        self.col = start.min(self.col) + text.chars().count();
+    }
+
+    pub fn move_left(&mut self) {
+        self.col = self.col.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.col = (self.col + 1).min(self.line_len(self.row));
+    }
+
+    pub fn move_up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn move_line_start(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn move_line_end(&mut self) {
+        self.col = self.line_len(self.row);
+    }
+}
+
+pub fn byte_index(line: &str, col: usize) -> usize {
+    line.char_indices().nth(col).map(|(index, _)| index).unwrap_or(line.len())
+}
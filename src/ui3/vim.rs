@@ -0,0 +1,604 @@
+use ratatui::{style::{Color, Modifier, Style}, text::{Line, Span}};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers};
+
+use super::text_buffer::TextBuffer;
+
+// Undo steps kept per editor
+const UNDO_SIZE: usize = 100;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Mode {
+    Insert,
+    Normal,
+    Visual,
+    VisualLine,
+}
+
+impl Mode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Insert => "INPUT",
+            Mode::Normal => "NORMAL",
+            Mode::Visual => "VISUAL",
+            Mode::VisualLine => "VISUAL LINE",
+        }
+    }
+
+    pub fn style(&self) -> Style {
+        match self {
+            Mode::Insert => Style::default().fg(Color::Black).bg(Color::Green),
+            Mode::Normal => Style::default().fg(Color::Black).bg(Color::Blue),
+            Mode::Visual | Mode::VisualLine => Style::default().fg(Color::Black).bg(Color::Magenta),
+        }
+    }
+}
+
+// Yanked or deleted text
+#[derive(Debug, PartialEq, Clone)]
+pub enum Register {
+    Chars(String),
+    Lines(Vec<String>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum CharClass {
+    Blank,
+    Word,
+    Punctuation,
+}
+
+// Modal editing shared by the editor widgets: the widget handles its own keys
+// (accept, next field, close) first and passes the rest to `handle_key`
+pub struct Vim {
+    pub buffer: TextBuffer,
+    pub mode: Mode,
+    // single line inputs ignore line breaks and line motions
+    single_line: bool,
+    count: Option<usize>,
+    // first key of `gg`, `dd`, `yy`, `d{motion}` and `y{motion}`
+    pending: Option<char>,
+    register: Option<Register>,
+    visual_start: (usize, usize),
+    undo: Vec<TextBuffer>,
+    redo: Vec<TextBuffer>,
+    // buffer when insert mode was entered, one undo step per insert
+    insert_start: Option<TextBuffer>,
+}
+
+impl Vim {
+    pub fn new(lines: Vec<String>, single_line: bool) -> Self {
+        let buffer = TextBuffer::new(lines);
+        Self {
+            insert_start: Some(buffer.clone()),
+            buffer,
+            mode: Mode::Insert,
+            single_line,
+            count: None,
+            pending: None,
+            register: None,
+            visual_start: (0, 0),
+            undo: vec![],
+            redo: vec![],
+        }
+    }
+
+    // Replaces the text, e.g. from the history, the cursor goes to the end
+    pub fn set_content(&mut self, lines: Vec<String>) {
+        if self.buffer.lines().iter().any(|line| !line.is_empty()) {
+            self.save_undo();
+        }
+        self.buffer = TextBuffer::new(lines);
+        let last = self.buffer.line_count() - 1;
+        self.buffer.set_cursor((last, self.buffer.line_len(last)));
+        if self.mode == Mode::Insert {
+            self.insert_start = Some(self.buffer.clone());
+        } else {
+            self.mode = Mode::Normal;
+            self.clamp_normal();
+        }
+    }
+
+    // Whether Esc has nothing left to cancel, widgets close on it then
+    pub fn is_idle(&self) -> bool {
+        self.mode == Mode::Normal && self.count.is_none() && self.pending.is_none()
+    }
+
+    // Still in the insert mode it started in, without edits. One-line popups close on Esc then
+    // instead of switching to normal mode first
+    pub fn is_untouched(&self) -> bool {
+        self.mode == Mode::Insert
+            && self.undo.is_empty()
+            && self.insert_start.as_ref().is_some_and(|start| start.lines() == self.buffer.lines())
+    }
+
+    // Selection as ordered start and end, both included
+    pub fn selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        let cursor = self.buffer.cursor();
+        let (start, end) = (self.visual_start.min(cursor), self.visual_start.max(cursor));
+        match self.mode {
+            Mode::Visual => Some((start, end)),
+            Mode::VisualLine => Some(((start.0, 0), (end.0, self.buffer.line_len(end.0).saturating_sub(1)))),
+            _ => None,
+        }
+    }
+
+    pub fn is_selected(&self, position: (usize, usize)) -> bool {
+        match self.mode {
+            Mode::VisualLine => self.selection().is_some_and(|(start, end)| (start.0..=end.0).contains(&position.0)),
+            _ => self.selection().is_some_and(|(start, end)| start <= position && position <= end),
+        }
+    }
+
+    // One buffer line from column `left` with the given char styles, the selection and the cursor.
+    // `mask` replaces every char, e.g. for passwords.
+    pub fn styled_line(&self, row: usize, left: usize, mut styles: Vec<Style>, focused: bool, mask: Option<char>) -> Line<'static> {
+        let mut chars: Vec<char> = self.buffer.lines()[row].chars().map(|ch| mask.unwrap_or(ch)).collect();
+        styles.resize(chars.len(), Style::default());
+        for (col, style) in styles.iter_mut().enumerate() {
+            if self.is_selected((row, col)) {
+                *style = style.bg(Color::DarkGray);
+            }
+        }
+        let (cursor_row, cursor_col) = self.buffer.cursor();
+        if focused && row == cursor_row {
+            // the cursor past the line end is drawn on a space
+            if cursor_col == chars.len() {
+                chars.push(' ');
+                styles.push(Style::default());
+            }
+            styles[cursor_col] = styles[cursor_col].add_modifier(Modifier::REVERSED);
+        }
+        let mut spans: Vec<Span> = vec![];
+        let mut text = String::new();
+        let mut style = Style::default();
+        for (ch, ch_style) in chars.into_iter().zip(styles).skip(left) {
+            if ch_style != style && !text.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut text), style));
+            }
+            style = ch_style;
+            text.push(ch);
+        }
+        spans.push(Span::styled(text, style));
+        Line::from(spans)
+    }
+
+    // First visible column or row that keeps `cursor` within `size` cells
+    pub fn scroll_to(offset: usize, cursor: usize, size: usize) -> usize {
+        let size = size.max(1);
+        if cursor < offset {
+            cursor
+        } else if cursor >= offset + size {
+            cursor + 1 - size
+        } else {
+            offset
+        }
+    }
+
+    // Returns whether the key was used
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match self.mode {
+            Mode::Insert => self.handle_insert(key),
+            _ => self.handle_normal(key),
+        }
+    }
+
+    fn handle_insert(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            Key::Esc => {
+                self.mode = Mode::Normal;
+                if let Some(start) = self.insert_start.take() {
+                    if start.lines() != self.buffer.lines() {
+                        self.push_undo(start);
+                    }
+                }
+                self.buffer.move_left();
+            },
+            Key::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.buffer.insert_char(ch),
+            Key::Enter if !self.single_line => self.buffer.newline(),
+            Key::Backspace => self.buffer.backspace(),
+            Key::Delete => self.buffer.delete(),
+            Key::Left => self.buffer.move_left(),
+            Key::Right => self.buffer.move_right(),
+            Key::Up => self.buffer.move_up(),
+            Key::Down => self.buffer.move_down(),
+            Key::Home => self.buffer.move_line_start(),
+            Key::End => self.buffer.move_line_end(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn handle_normal(&mut self, key: KeyEvent) -> bool {
+        let ch = match key.code {
+            Key::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let count = self.take_count();
+                for _ in 0..count {
+                    self.redo();
+                }
+                return true;
+            },
+            Key::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => ch,
+            Key::Left => 'h',
+            Key::Right => 'l',
+            Key::Up => 'k',
+            Key::Down => 'j',
+            Key::Home => '0',
+            Key::End => '$',
+            Key::Esc if self.mode != Mode::Normal => {
+                self.mode = Mode::Normal;
+                self.clamp_normal();
+                return true;
+            },
+            Key::Esc if !self.is_idle() => {
+                self.count = None;
+                self.pending = None;
+                return true;
+            },
+            _ => return false,
+        };
+
+        // counts, a leading 0 is the line start motion
+        if let Some(digit) = ch.to_digit(10) {
+            if digit != 0 || self.count.is_some() {
+                self.count = Some(self.count.unwrap_or(0) * 10 + digit as usize);
+                return true;
+            }
+        }
+
+        match self.pending.take() {
+            Some('g') => {
+                if ch == 'g' {
+                    let row = self.count.take().map(|count| count.saturating_sub(1)).unwrap_or(0);
+                    self.buffer.set_cursor((row, 0));
+                }
+                self.count = None;
+                return true;
+            },
+            Some(operator @ ('d' | 'y')) => {
+                self.operator(operator, ch);
+                return true;
+            },
+            _ => {},
+        }
+
+        let visual = self.mode != Mode::Normal;
+        match ch {
+            'g' => self.pending = Some(ch),
+            'd' | 'y' if !visual => self.pending = Some(ch),
+            'd' | 'x' if visual => self.delete_selection(),
+            'y' if visual => {
+                self.yank_selection();
+                self.mode = Mode::Normal;
+            },
+            'v' | 'V' if visual => self.mode = Mode::Normal,
+            'v' => self.start_visual(Mode::Visual),
+            'V' => self.start_visual(Mode::VisualLine),
+            'x' => {
+                let count = self.take_count();
+                let (row, col) = self.buffer.cursor();
+                if self.buffer.line_len(row) > 0 {
+                    self.save_undo();
+                    let end = (col + count).min(self.buffer.line_len(row));
+                    let text = self.buffer.delete_range((row, col), (row, end));
+                    self.register = Some(Register::Chars(text));
+                    self.clamp_normal();
+                }
+            },
+            'p' | 'P' => {
+                let count = self.take_count();
+                self.paste(ch == 'p', count);
+            },
+            'u' => {
+                let count = self.take_count();
+                for _ in 0..count {
+                    self.undo();
+                }
+            },
+            'i' => self.start_insert(),
+            'a' => {
+                self.start_insert();
+                self.buffer.move_right();
+            },
+            'I' => {
+                self.start_insert();
+                self.buffer.move_line_start();
+            },
+            'A' => {
+                self.start_insert();
+                self.buffer.move_line_end();
+            },
+            'o' | 'O' if !self.single_line => {
+                self.start_insert();
+                let row = self.buffer.cursor().0 + usize::from(ch == 'o');
+                self.buffer.insert_lines(row, vec![String::new()]);
+            },
+            _ => match self.motion(ch) {
+                Some(position) => {
+                    self.buffer.set_cursor(position);
+                    if self.mode == Mode::Normal {
+                        self.clamp_normal();
+                    }
+                },
+                None => {
+                    self.count = None;
+                    return false;
+                },
+            },
+        }
+        true
+    }
+
+    fn take_count(&mut self) -> usize {
+        self.count.take().unwrap_or(1).max(1)
+    }
+
+    // Cursor position after a motion key, repeated by the count
+    fn motion(&mut self, ch: char) -> Option<(usize, usize)> {
+        let count = self.count.take();
+        let times = count.unwrap_or(1).max(1);
+        let (row, col) = self.buffer.cursor();
+        let buffer = &self.buffer;
+        let repeat = |motion: fn(&TextBuffer, (usize, usize)) -> (usize, usize)| {
+            (0..times).fold((row, col), |position, _| motion(buffer, position))
+        };
+        let last_row = buffer.line_count() - 1;
+        let position = match ch {
+            'h' => (row, col.saturating_sub(times)),
+            'l' => (row, (col + times).min(buffer.line_len(row).saturating_sub(1))),
+            'j' if !self.single_line => ((row + times).min(last_row), col),
+            'k' if !self.single_line => (row.saturating_sub(times), col),
+            'w' => repeat(word_forward),
+            'b' => repeat(word_backward),
+            'e' => repeat(word_end),
+            '0' => (row, 0),
+            '$' => (row, buffer.line_len(row).saturating_sub(1)),
+            'G' => (count.map(|count| count.saturating_sub(1)).unwrap_or(last_row).min(last_row), 0),
+            _ => return None,
+        };
+        Some(position)
+    }
+
+    // `dd`/`yy` on whole lines, otherwise the text the motion moves over
+    fn operator(&mut self, operator: char, ch: char) {
+        let (row, col) = self.buffer.cursor();
+        if ch == operator {
+            let count = self.take_count();
+            let end = (row + count).min(self.buffer.line_count());
+            let lines = self.buffer.lines()[row..end].to_vec();
+            if operator == 'd' {
+                self.save_undo();
+                self.buffer.delete_lines(row, count);
+                if self.buffer.cursor().0 < row {
+                    // the last lines were deleted
+                    self.buffer.set_cursor((row.saturating_sub(1), 0));
+                }
+            }
+            self.register = Some(Register::Lines(lines));
+            return;
+        }
+        let target = match self.motion(ch) {
+            Some(target) => target,
+            None => return,
+        };
+        let end = match ch {
+            // `e` and `$` include the char they stop on
+            'e' | '$' => (target.0, target.1 + 1),
+            // `w` on the last word stops at the line end
+            'w' if target.0 > row => (row, self.buffer.line_len(row)),
+            _ => target,
+        };
+        let (start, end) = ((row, col).min(end), (row, col).max(end));
+        let text = self.buffer.text_range(start, end);
+        if operator == 'd' {
+            self.save_undo();
+            self.buffer.delete_range(start, end);
+            self.clamp_normal();
+        } else {
+            self.buffer.set_cursor(start);
+        }
+        self.register = Some(Register::Chars(text));
+    }
+
+    fn start_visual(&mut self, mode: Mode) {
+        self.count = None;
+        self.visual_start = self.buffer.cursor();
+        self.mode = mode;
+    }
+
+    fn yank_selection(&mut self) {
+        let (start, end) = match self.selection() {
+            Some(selection) => selection,
+            None => return,
+        };
+        self.register = Some(match self.mode {
+            Mode::VisualLine => Register::Lines(self.buffer.lines()[start.0..=end.0].to_vec()),
+            _ => Register::Chars(self.buffer.text_range(start, next_position(&self.buffer, end))),
+        });
+        self.buffer.set_cursor(start);
+    }
+
+    fn delete_selection(&mut self) {
+        let (start, end) = match self.selection() {
+            Some(selection) => selection,
+            None => return,
+        };
+        self.yank_selection();
+        self.save_undo();
+        match self.mode {
+            Mode::VisualLine => {
+                self.buffer.delete_lines(start.0, end.0 - start.0 + 1);
+                if self.buffer.cursor().0 < start.0 {
+                    self.buffer.set_cursor((start.0.saturating_sub(1), 0));
+                }
+            },
+            _ => {
+                self.buffer.delete_range(start, next_position(&self.buffer, end));
+            },
+        }
+        self.mode = Mode::Normal;
+        self.clamp_normal();
+    }
+
+    fn paste(&mut self, after: bool, count: usize) {
+        let register = match &self.register {
+            Some(register) => register.clone(),
+            None => return,
+        };
+        self.save_undo();
+        let (row, col) = self.buffer.cursor();
+        match register {
+            Register::Lines(lines) if !self.single_line => {
+                let lines: Vec<String> = (0..count).flat_map(|_| lines.clone()).collect();
+                self.buffer.insert_lines(row + usize::from(after), lines);
+            },
+            register => {
+                let text = match register {
+                    Register::Chars(text) => text,
+                    Register::Lines(lines) => lines.join(" "),
+                };
+                let text = if self.single_line { text.replace('\n', " ") } else { text };
+                let text = text.repeat(count);
+                let at = if after && self.buffer.line_len(row) > 0 { col + 1 } else { col };
+                self.buffer.set_cursor((row, at));
+                self.buffer.insert_str(&text);
+                self.buffer.move_left();
+            },
+        }
+    }
+
+    fn start_insert(&mut self) {
+        self.count = None;
+        self.mode = Mode::Insert;
+        self.insert_start = Some(self.buffer.clone());
+    }
+
+    // Normal mode keeps the cursor on a char, not past the line end
+    fn clamp_normal(&mut self) {
+        let (row, col) = self.buffer.cursor();
+        self.buffer.set_cursor((row, col.min(self.buffer.line_len(row).saturating_sub(1))));
+    }
+
+    fn save_undo(&mut self) {
+        let snapshot = self.buffer.clone();
+        self.push_undo(snapshot);
+    }
+
+    fn push_undo(&mut self, snapshot: TextBuffer) {
+        self.undo.push(snapshot);
+        if self.undo.len() > UNDO_SIZE {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    fn undo(&mut self) {
+        if let Some(previous) = self.undo.pop() {
+            self.redo.push(std::mem::replace(&mut self.buffer, previous));
+            self.clamp_normal();
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(next) = self.redo.pop() {
+            self.undo.push(std::mem::replace(&mut self.buffer, next));
+            self.clamp_normal();
+        }
+    }
+}
+
+fn char_class(buffer: &TextBuffer, position: (usize, usize)) -> CharClass {
+    match buffer.char_at(position) {
+        None => CharClass::Blank,
+        Some(ch) if ch.is_whitespace() => CharClass::Blank,
+        Some(ch) if ch.is_alphanumeric() || ch == '_' => CharClass::Word,
+        Some(_) => CharClass::Punctuation,
+    }
+}
+
+// Next position including the line end, which stands for the line break
+fn next_position(buffer: &TextBuffer, (row, col): (usize, usize)) -> (usize, usize) {
+    if col < buffer.line_len(row) {
+        (row, col + 1)
+    } else if row + 1 < buffer.line_count() {
+        (row + 1, 0)
+    } else {
+        (row, col)
+    }
+}
+
+fn prev_position(buffer: &TextBuffer, (row, col): (usize, usize)) -> Option<(usize, usize)> {
+    if col > 0 {
+        Some((row, col - 1))
+    } else if row > 0 {
+        Some((row - 1, buffer.line_len(row - 1)))
+    } else {
+        None
+    }
+}
+
+fn is_empty_line(buffer: &TextBuffer, (row, _): (usize, usize)) -> bool {
+    buffer.line_len(row) == 0
+}
+
+// `w`: start of the next word, an empty line counts as a word.
+// Past the last word it stops at the end of the buffer, normal mode moves back onto the last char.
+pub fn word_forward(buffer: &TextBuffer, start: (usize, usize)) -> (usize, usize) {
+    let mut position = start;
+    let class = char_class(buffer, position);
+    while class != CharClass::Blank && char_class(buffer, position) == class {
+        let next = next_position(buffer, position);
+        if next == position {
+            return position;
+        }
+        position = next;
+    }
+    loop {
+        if char_class(buffer, position) != CharClass::Blank || (position.0 != start.0 && is_empty_line(buffer, position)) {
+            return position;
+        }
+        let next = next_position(buffer, position);
+        if next == position {
+            return position;
+        }
+        position = next;
+    }
+}
+
+// `e`: end of the current or next word
+pub fn word_end(buffer: &TextBuffer, start: (usize, usize)) -> (usize, usize) {
+    let mut position = next_position(buffer, start);
+    while char_class(buffer, position) == CharClass::Blank {
+        let next = next_position(buffer, position);
+        if next == position {
+            return start;
+        }
+        position = next;
+    }
+    let class = char_class(buffer, position);
+    while position.1 + 1 < buffer.line_len(position.0) && char_class(buffer, (position.0, position.1 + 1)) == class {
+        position.1 += 1;
+    }
+    position
+}
+
+// `b`: start of the current or previous word, an empty line counts as a word
+pub fn word_backward(buffer: &TextBuffer, start: (usize, usize)) -> (usize, usize) {
+    let mut position = match prev_position(buffer, start) {
+        Some(position) => position,
+        None => return start,
+    };
+    while char_class(buffer, position) == CharClass::Blank {
+        if is_empty_line(buffer, position) {
+            return position;
+        }
+        position = match prev_position(buffer, position) {
+            Some(position) => position,
+            None => return (0, 0),
+        };
+    }
+    let class = char_class(buffer, position);
+    while position.1 > 0 && char_class(buffer, (position.0, position.1 - 1)) == class {
+        position.1 -= 1;
+    }
+    position
+}
@@ -0,0 +1,282 @@
+use tuirealm::event::{Key, KeyEvent, KeyModifiers};
+
+use crate::ui3::vim::{Mode, Vim};
+
+// Editor in normal mode with the cursor at `cursor`
+fn normal(lines: &[&str], cursor: (usize, usize)) -> Vim {
+    let mut vim = Vim::new(lines.iter().map(|line| line.to_string()).collect(), false);
+    vim.handle_key(KeyEvent::from(Key::Esc));
+    vim.buffer.set_cursor(cursor);
+    vim
+}
+
+fn keys(vim: &mut Vim, keys: &str) {
+    for ch in keys.chars() {
+        vim.handle_key(KeyEvent::from(Key::Char(ch)));
+    }
+}
+
+fn lines(vim: &Vim) -> Vec<&str> {
+    vim.buffer.lines().iter().map(|line| line.as_str()).collect()
+}
+
+#[test]
+fn test_w() {
+    let mut vim = normal(&["SET key value", "GET key"], (0, 0));
+    keys(&mut vim, "w");
+    assert_eq!(vim.buffer.cursor(), (0, 4));
+    keys(&mut vim, "w");
+    assert_eq!(vim.buffer.cursor(), (0, 8));
+    keys(&mut vim, "w");
+    assert_eq!(vim.buffer.cursor(), (1, 0));
+}
+
+#[test]
+fn test_w_punctuation_and_empty_line() {
+    let mut vim = normal(&["foo.bar baz", "", "end"], (0, 0));
+    keys(&mut vim, "w");
+    assert_eq!(vim.buffer.cursor(), (0, 3));
+    keys(&mut vim, "w");
+    assert_eq!(vim.buffer.cursor(), (0, 4));
+    keys(&mut vim, "ww");
+    assert_eq!(vim.buffer.cursor(), (1, 0));
+    keys(&mut vim, "w");
+    assert_eq!(vim.buffer.cursor(), (2, 0));
+    // past the last word
+    keys(&mut vim, "w");
+    assert_eq!(vim.buffer.cursor(), (2, 2));
+}
+
+#[test]
+fn test_b() {
+    let mut vim = normal(&["SET key value", "GET key"], (0, 10));
+    keys(&mut vim, "b");
+    assert_eq!(vim.buffer.cursor(), (0, 8));
+    keys(&mut vim, "b");
+    assert_eq!(vim.buffer.cursor(), (0, 4));
+
+    let mut vim = normal(&["SET key value", "", "GET key"], (2, 0));
+    keys(&mut vim, "b");
+    assert_eq!(vim.buffer.cursor(), (1, 0));
+    keys(&mut vim, "b");
+    assert_eq!(vim.buffer.cursor(), (0, 8));
+}
+
+#[test]
+fn test_e() {
+    let mut vim = normal(&["SET key value", "GET key"], (0, 0));
+    keys(&mut vim, "e");
+    assert_eq!(vim.buffer.cursor(), (0, 2));
+    keys(&mut vim, "e");
+    assert_eq!(vim.buffer.cursor(), (0, 6));
+    keys(&mut vim, "e");
+    assert_eq!(vim.buffer.cursor(), (0, 12));
+    keys(&mut vim, "e");
+    assert_eq!(vim.buffer.cursor(), (1, 2));
+}
+
+#[test]
+fn test_line_start_and_end() {
+    let mut vim = normal(&["SET key value"], (0, 5));
+    keys(&mut vim, "$");
+    assert_eq!(vim.buffer.cursor(), (0, 12));
+    keys(&mut vim, "0");
+    assert_eq!(vim.buffer.cursor(), (0, 0));
+}
+
+#[test]
+fn test_gg_and_g() {
+    let mut vim = normal(&["a", "b", "c"], (0, 0));
+    keys(&mut vim, "G");
+    assert_eq!(vim.buffer.cursor(), (2, 0));
+    keys(&mut vim, "gg");
+    assert_eq!(vim.buffer.cursor(), (0, 0));
+    keys(&mut vim, "2G");
+    assert_eq!(vim.buffer.cursor(), (1, 0));
+    keys(&mut vim, "3gg");
+    assert_eq!(vim.buffer.cursor(), (2, 0));
+}
+
+#[test]
+fn test_hjkl_with_counts() {
+    let mut vim = normal(&["abcd", "ef", "ghij"], (0, 0));
+    keys(&mut vim, "3l");
+    assert_eq!(vim.buffer.cursor(), (0, 3));
+    keys(&mut vim, "9l");
+    assert_eq!(vim.buffer.cursor(), (0, 3));
+    keys(&mut vim, "2h");
+    assert_eq!(vim.buffer.cursor(), (0, 1));
+    keys(&mut vim, "2j");
+    assert_eq!(vim.buffer.cursor(), (2, 1));
+    keys(&mut vim, "k");
+    assert_eq!(vim.buffer.cursor(), (1, 1));
+}
+
+#[test]
+fn test_count_motion() {
+    let mut vim = normal(&["SET key value"], (0, 0));
+    keys(&mut vim, "2w");
+    assert_eq!(vim.buffer.cursor(), (0, 8));
+    keys(&mut vim, "2b");
+    assert_eq!(vim.buffer.cursor(), (0, 0));
+}
+
+#[test]
+fn test_x() {
+    let mut vim = normal(&["abcd"], (0, 0));
+    keys(&mut vim, "x");
+    assert_eq!(lines(&vim), vec!["bcd"]);
+    keys(&mut vim, "2x");
+    assert_eq!(lines(&vim), vec!["d"]);
+    keys(&mut vim, "x");
+    assert_eq!(lines(&vim), vec![""]);
+    assert_eq!(vim.buffer.cursor(), (0, 0));
+}
+
+#[test]
+fn test_dd() {
+    let mut vim = normal(&["a", "b", "c"], (1, 0));
+    keys(&mut vim, "dd");
+    assert_eq!(lines(&vim), vec!["a", "c"]);
+    assert_eq!(vim.buffer.cursor(), (1, 0));
+    keys(&mut vim, "dd");
+    assert_eq!(lines(&vim), vec!["a"]);
+    assert_eq!(vim.buffer.cursor(), (0, 0));
+
+    let mut vim = normal(&["a", "b", "c"], (0, 0));
+    keys(&mut vim, "2dd");
+    assert_eq!(lines(&vim), vec!["c"]);
+}
+
+#[test]
+fn test_d_motion() {
+    let mut vim = normal(&["SET key value"], (0, 0));
+    keys(&mut vim, "dw");
+    assert_eq!(lines(&vim), vec!["key value"]);
+    keys(&mut vim, "de");
+    assert_eq!(lines(&vim), vec![" value"]);
+    keys(&mut vim, "d$");
+    assert_eq!(lines(&vim), vec![""]);
+
+    // the last word
+    let mut vim = normal(&["SET key"], (0, 4));
+    keys(&mut vim, "dw");
+    assert_eq!(lines(&vim), vec!["SET "]);
+}
+
+#[test]
+fn test_yy_and_p() {
+    let mut vim = normal(&["a", "b"], (0, 0));
+    keys(&mut vim, "yyp");
+    assert_eq!(lines(&vim), vec!["a", "a", "b"]);
+    assert_eq!(vim.buffer.cursor(), (1, 0));
+    keys(&mut vim, "GP");
+    assert_eq!(lines(&vim), vec!["a", "a", "a", "b"]);
+    assert_eq!(vim.buffer.cursor(), (2, 0));
+}
+
+#[test]
+fn test_yw_and_p() {
+    let mut vim = normal(&["ab cd"], (0, 0));
+    keys(&mut vim, "yw");
+    assert_eq!(lines(&vim), vec!["ab cd"]);
+    keys(&mut vim, "$p");
+    assert_eq!(lines(&vim), vec!["ab cdab "]);
+    assert_eq!(vim.buffer.cursor(), (0, 7));
+}
+
+#[test]
+fn test_undo_and_redo() {
+    let mut vim = normal(&["abc"], (0, 0));
+    keys(&mut vim, "x");
+    assert_eq!(lines(&vim), vec!["bc"]);
+    keys(&mut vim, "u");
+    assert_eq!(lines(&vim), vec!["abc"]);
+    vim.handle_key(KeyEvent::new(Key::Char('r'), KeyModifiers::CONTROL));
+    assert_eq!(lines(&vim), vec!["bc"]);
+}
+
+#[test]
+fn test_undo_insert() {
+    let mut vim = Vim::new(vec!["a".to_string()], false);
+    keys(&mut vim, "bc");
+    vim.handle_key(KeyEvent::from(Key::Esc));
+    assert_eq!(lines(&vim), vec!["bca"]);
+    keys(&mut vim, "u");
+    assert_eq!(lines(&vim), vec!["a"]);
+}
+
+#[test]
+fn test_o_and_upper_o() {
+    let mut vim = normal(&["a", "b"], (0, 0));
+    keys(&mut vim, "o");
+    assert_eq!(vim.mode, Mode::Insert);
+    keys(&mut vim, "x");
+    vim.handle_key(KeyEvent::from(Key::Esc));
+    assert_eq!(lines(&vim), vec!["a", "x", "b"]);
+    keys(&mut vim, "ggO");
+    assert_eq!(lines(&vim), vec!["", "a", "x", "b"]);
+    assert_eq!(vim.buffer.cursor(), (0, 0));
+}
+
+#[test]
+fn test_visual() {
+    let mut vim = normal(&["SET key value"], (0, 4));
+    keys(&mut vim, "ve");
+    assert_eq!(vim.mode, Mode::Visual);
+    assert_eq!(vim.selection(), Some(((0, 4), (0, 6))));
+    keys(&mut vim, "d");
+    assert_eq!(vim.mode, Mode::Normal);
+    assert_eq!(lines(&vim), vec!["SET  value"]);
+}
+
+#[test]
+fn test_visual_line() {
+    let mut vim = normal(&["a", "b", "c"], (0, 0));
+    keys(&mut vim, "Vjy");
+    assert_eq!(vim.mode, Mode::Normal);
+    keys(&mut vim, "Gp");
+    assert_eq!(lines(&vim), vec!["a", "b", "c", "a", "b"]);
+    keys(&mut vim, "ggVjd");
+    assert_eq!(lines(&vim), vec!["c", "a", "b"]);
+}
+
+#[test]
+fn test_esc_cancels_pending() {
+    let mut vim = normal(&["abc"], (0, 0));
+    keys(&mut vim, "2d");
+    assert!(!vim.is_idle());
+    vim.handle_key(KeyEvent::from(Key::Esc));
+    assert!(vim.is_idle());
+    keys(&mut vim, "x");
+    assert_eq!(lines(&vim), vec!["bc"]);
+}
+
+#[test]
+fn test_untouched() {
+    let mut vim = Vim::new(vec![], true);
+    assert!(vim.is_untouched());
+    vim.set_content(vec!["prefilled".to_string()]);
+    assert!(vim.is_untouched());
+    keys(&mut vim, "x");
+    assert!(!vim.is_untouched());
+    vim.handle_key(KeyEvent::from(Key::Backspace));
+    // back to the initial text, but the insert was not left yet
+    assert!(vim.is_untouched());
+
+    keys(&mut vim, "!");
+    vim.handle_key(KeyEvent::from(Key::Esc));
+    keys(&mut vim, "a");
+    assert_eq!(vim.mode, Mode::Insert);
+    assert!(!vim.is_untouched());
+}
+
+#[test]
+fn test_single_line() {
+    let mut vim = Vim::new(vec!["abc".to_string()], true);
+    assert!(!vim.handle_key(KeyEvent::from(Key::Enter)));
+    vim.handle_key(KeyEvent::from(Key::Esc));
+    assert!(!vim.handle_key(KeyEvent::from(Key::Char('j'))));
+    keys(&mut vim, "o");
+    assert_eq!(lines(&vim), vec!["abc"]);
+}